@@ -1,11 +1,13 @@
 use hashbrown::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use num_bigint::BigInt;
+use num_rational::BigRational;
 
 use crate::{environment::Value, token::Token};
 
 pub type Program = Vec<Statement>;
 pub type Block = Vec<Statement>;
 pub type Identifier = String;
-pub type Numerator = u8;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConditionBlock {
@@ -13,19 +15,51 @@ pub struct ConditionBlock {
 	pub then: Block,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+	Wildcard,
+	Binding(Identifier),
+	Literal(Expression),
+	Range(Expression),
+	List { items: Vec<Pattern>, rest: Option<Option<Identifier>> },
+	Struct { name: Option<Identifier>, fields: Vec<(Identifier, Pattern)> },
+	Variant { name: Identifier, variant: Identifier, fields: Option<Vec<Pattern>> },
+	Default(Box<Pattern>, Expression),
+}
+
+// A variant of an `enum`, `fields` is `None` for unit variants like `Shape.Empty`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+	pub name: Identifier,
+	pub fields: Option<Vec<Identifier>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+	pub pattern: Pattern,
+	pub guard: Option<Expression>,
+	pub body: Block,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
 	Return { value: Expression },
+	Yield { value: Expression },
 	Break,
 	Continue,
-	FunctionDeclaration { name: Identifier, params: Vec<Parameter>, body: Block },
-	StructDeclaration { name: Identifier, fields: Vec<Parameter> },
-	CreateDeclaration { name: Identifier, initial: Option<Expression> },
-	ConstDeclaration { name: Identifier, initial: Expression },
+	FunctionDeclaration { name: Identifier, params: Vec<Parameter>, body: Block, return_type: Option<Type>, is_async: bool },
+	StructDeclaration { name: Identifier, extends: Vec<Identifier>, fields: Vec<Parameter> },
+	InterfaceDeclaration { name: Identifier, methods: Vec<(Identifier, Vec<Parameter>)> },
+	EnumDeclaration { name: Identifier, variants: Vec<Variant>, methods: Vec<Parameter> },
+	CreateDeclaration { name: Identifier, kind: Option<Type>, initial: Option<Expression> },
+	ConstDeclaration { name: Identifier, kind: Option<Type>, initial: Expression },
+	DestructuringDeclaration { pattern: Pattern, initial: Expression, constant: bool },
 	If { condition: ConditionBlock, others_conditions: Option<Vec<ConditionBlock>>, otherwise: Option<Block> },
 	For { iterable: Expression, value: Identifier, index: Option<Identifier>, then: Block },
 	While { condition: ConditionBlock },
 	Loop { body: Block },
+	// `try { ... } catch error { ... }`, the handler runs with the error message bound to `name`.
+	Try { body: Block, name: Option<Identifier>, handler: Block },
 	Expression { expression: Expression },
 }
 
@@ -44,6 +78,15 @@ pub struct ArgumentValues {
 pub struct Parameter {
 	pub name: String,
 	pub initial: Option<Expression>,
+	pub kind: Option<Type>,
+}
+
+// Optional annotation such as `number`, `list<string>` or `User | null`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+	Named(Identifier),
+	List(Box<Type>),
+	Union(Vec<Type>),
 }
 #[derive(Debug, Clone, PartialEq)]
 pub struct Argument {
@@ -51,9 +94,10 @@ pub struct Argument {
 	expression: Expression,
 }
 
+#[allow(dead_code)]
 impl Parameter {
 	pub fn new(name: String, initial: Option<Expression>) -> Self {
-		Self { name, initial }
+		Self { name, initial, kind: None }
 	}
 
 	pub fn get_name(&self) -> String {
@@ -67,6 +111,10 @@ impl Parameter {
 	pub fn has_initial(&self) -> bool {
 		self.initial.is_some()
 	}
+
+	pub fn is_this(&self) -> bool {
+		self.name == "this" && self.initial.is_none()
+	}
 }
 
 impl ArgumentValued {
@@ -83,6 +131,7 @@ impl ArgumentValued {
 	}
 }
 
+#[allow(dead_code)]
 impl ArgumentValues {
 	pub fn new() -> ArgumentValues {
 		ArgumentValues { params_values: Vec::new() }
@@ -144,7 +193,11 @@ impl Iterator for ArgumentValues {
 	type Item = ArgumentValued;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		self.params_values.pop()
+		if self.params_values.is_empty() {
+			None
+		} else {
+			Some(self.params_values.remove(0))
+		}
 	}
 }
 
@@ -184,6 +237,8 @@ impl CallArguments {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
 	Number(f64),
+	BigInt(BigInt),
+	Rational(BigRational),
 	String(String),
 	Bool(bool),
 	Null,
@@ -194,12 +249,16 @@ pub enum Expression {
 	Prefix(Op, Box<Expression>),
 	Call(Box<Expression>, CallArguments),
 	Struct(Box<Expression>, HashMap<Identifier, Expression>),
-	Closure(Vec<Parameter>, Vec<Statement>),
+	Closure(Vec<Parameter>, Vec<Statement>, Option<Type>, bool),
 	MethodCall(Box<Expression>, Identifier, CallArguments),
 	GetProperty(Box<Expression>, Identifier),
 	SetProperty(Box<Expression>, Identifier, Box<Expression>),
 	Index(Box<Expression>, Option<Box<Expression>>),
 	List(Vec<Expression>),
+	Range { start: Box<Expression>, end: Box<Expression>, step: Option<Box<Expression>>, inclusive: bool },
+	Match(Box<Expression>, Vec<MatchArm>),
+	Await(Box<Expression>),
+	Spawn(Box<Expression>),
 }
 
 #[allow(dead_code)]
@@ -235,6 +294,77 @@ pub enum Op {
 	NotIn,
 }
 
+impl Display for Type {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Type::Named(name) => write!(f, "{}", name),
+			Type::List(item) => write!(f, "list<{}>", item),
+			Type::Union(types) => write!(f, "{}", types.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(" | ")),
+		}
+	}
+}
+
+impl Statement {
+	// Whether `predicate` holds for this statement or one nested in its blocks, nested functions aren't entered.
+	fn contains(&self, predicate: &dyn Fn(&Statement) -> bool) -> bool {
+		predicate(self)
+			|| match self {
+				Statement::If { condition, others_conditions, otherwise } => {
+					condition.then.iter().any(|s| s.contains(predicate))
+						|| others_conditions.iter().flatten().any(|c| c.then.iter().any(|s| s.contains(predicate)))
+						|| otherwise.iter().flatten().any(|s| s.contains(predicate))
+				}
+				Statement::For { then: body, .. } | Statement::While { condition: ConditionBlock { then: body, .. } } | Statement::Loop { body } => {
+					body.iter().any(|s| s.contains(predicate))
+				}
+				Statement::Try { body, handler, .. } => body.iter().chain(handler).any(|s| s.contains(predicate)),
+				// Only a `match` that is a statement of its own, its arms are stepped through like the branches of an `if`.
+				Statement::Expression { expression: Expression::Match(_, arms) } => arms.iter().flat_map(|arm| &arm.body).any(|s| s.contains(predicate)),
+				_ => false,
+			}
+	}
+
+	// A function is a generator when `yield` appears in its body, outside of nested functions.
+	pub fn yields(&self) -> bool {
+		self.contains(&|s| matches!(s, Statement::Yield { .. }))
+	}
+
+	// Coroutines can stop at a `yield` or at an `await` that is the whole value of a statement.
+	pub fn suspends(&self) -> bool {
+		self.contains(&|s| matches!(s, Statement::Yield { .. }) || s.split_await().is_some())
+	}
+
+	// `create x = await e`, `x = await e`, `return await e` and `await e` are split into the awaited expression and
+	// the same statement reading the result from `$await`, so a coroutine can finish it once the value is ready.
+	pub fn split_await(&self) -> Option<(Expression, Statement)> {
+		let result = || Expression::Identifier("$await".to_string());
+
+		Some(match self.clone() {
+			Statement::Expression { expression: Expression::Await(awaited) } => (*awaited, Statement::Expression { expression: result() }),
+			Statement::Expression { expression: Expression::Assign(target, value) } => match *value {
+				Expression::Await(awaited) => (*awaited, Statement::Expression { expression: Expression::Assign(target, result().boxed()) }),
+				_ => return None,
+			},
+			Statement::CreateDeclaration { name, kind, initial: Some(Expression::Await(awaited)) } => {
+				(*awaited, Statement::CreateDeclaration { name, kind, initial: Some(result()) })
+			}
+			Statement::ConstDeclaration { name, kind, initial: Expression::Await(awaited) } => (*awaited, Statement::ConstDeclaration { name, kind, initial: result() }),
+			Statement::DestructuringDeclaration { pattern, initial: Expression::Await(awaited), constant } => {
+				(*awaited, Statement::DestructuringDeclaration { pattern, initial: result(), constant })
+			}
+			Statement::Return { value: Expression::Await(awaited) } => (*awaited, Statement::Return { value: result() }),
+			_ => return None,
+		})
+	}
+}
+
+impl Pattern {
+	// Patterns that match every value, an arm using one without a guard makes the match exhaustive.
+	pub fn is_irrefutable(&self) -> bool {
+		matches!(self, Pattern::Wildcard | Pattern::Binding(..))
+	}
+}
+
 impl Op {
 	pub fn token(token: Token) -> Self {
 		match token {
@@ -259,4 +389,27 @@ impl Op {
 			_ => unreachable!("{:?}", token),
 		}
 	}
+
+	pub fn symbol(&self) -> &'static str {
+		match self {
+			Self::Add => "+",
+			Self::Subtract => "-",
+			Self::Multiply => "*",
+			Self::Divide => "/",
+			Self::Modulo => "%",
+			Self::Bang => "!",
+			Self::Equals => "==",
+			Self::NotEquals => "!=",
+			Self::Assign => "=",
+			Self::LessThan => "<",
+			Self::GreaterThan => ">",
+			Self::LessThanOrEquals => "<=",
+			Self::GreaterThanOrEquals => ">=",
+			Self::And => "&&",
+			Self::Or => "||",
+			Self::Pow => "**",
+			Self::In => "in",
+			Self::NotIn => "not in",
+		}
+	}
 }
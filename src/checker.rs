@@ -0,0 +1,526 @@
+use colored::*;
+use hashbrown::HashMap;
+
+use crate::ast::*;
+
+// Static half of the gradual type checker. Only annotated declarations are checked, expressions whose type can't be
+// inferred are `any` and are left to the runtime checks at function boundaries. Warnings, like a `match` that isn't
+// exhaustive, don't stop the program from running.
+pub fn check(program: &Program) -> Diagnostics {
+	let mut checker = Checker::new();
+
+	checker.declare(program);
+	checker.check_block(program);
+
+	Diagnostics { errors: checker.errors, warnings: checker.warnings }
+}
+
+pub struct Diagnostics {
+	pub errors: Vec<String>,
+	pub warnings: Vec<String>,
+}
+
+// Prints every warning and type error, stops before the program runs when there are errors.
+pub fn report(diagnostics: Diagnostics) {
+	for warning in diagnostics.warnings {
+		eprintln!("{}", warning.yellow().bold());
+	}
+
+	if diagnostics.errors.is_empty() {
+		return;
+	}
+
+	for error in diagnostics.errors {
+		eprintln!("{}", error.red().bold());
+	}
+
+	std::process::exit(1);
+}
+
+fn any() -> Type {
+	Type::Named("any".to_string())
+}
+
+fn named(name: &str) -> Type {
+	Type::Named(name.to_string())
+}
+
+struct Variable {
+	kind: Type,
+	annotated: bool,
+}
+
+struct Checker {
+	functions: HashMap<Identifier, (Vec<Parameter>, Option<Type>, bool)>,
+	structs: HashMap<Identifier, (Vec<Identifier>, Vec<Parameter>)>,
+	// Variant names of every enum, used to check `match` exhaustiveness.
+	enums: HashMap<Identifier, Vec<Identifier>>,
+	scopes: Vec<HashMap<Identifier, Variable>>,
+	returns: Vec<(Identifier, Option<Type>)>,
+	errors: Vec<String>,
+	warnings: Vec<String>,
+}
+
+impl Checker {
+	fn new() -> Self {
+		Self {
+			functions: HashMap::new(),
+			structs: HashMap::new(),
+			enums: HashMap::new(),
+			scopes: vec![HashMap::new()],
+			returns: Vec::new(),
+			errors: Vec::new(),
+			warnings: Vec::new(),
+		}
+	}
+
+	// Functions, structs and enums can be used before their declaration, so they're collected up front.
+	fn declare(&mut self, program: &Program) {
+		for statement in program {
+			match statement {
+				Statement::FunctionDeclaration { name, params, return_type, is_async, .. } => {
+					self.functions.insert(name.clone(), (params.clone(), return_type.clone(), *is_async));
+				}
+				Statement::StructDeclaration { name, extends, fields } => {
+					self.structs.insert(name.clone(), (extends.clone(), fields.clone()));
+				}
+				Statement::EnumDeclaration { name, variants, .. } => {
+					self.enums.insert(name.clone(), variants.iter().map(|variant| variant.name.clone()).collect());
+				}
+				_ => (),
+			}
+		}
+	}
+
+	fn error(&mut self, context: String, expected: &Type, actual: &Type) {
+		self.errors.push(format!("TypeError: {} expects {}, got {}.", context, expected, actual));
+	}
+
+	fn expect(&mut self, actual: &Type, expected: &Option<Type>, context: impl FnOnce() -> String) {
+		if let Some(expected) = expected {
+			if !self.assignable(actual, expected) {
+				self.error(context(), expected, actual);
+			}
+		}
+	}
+
+	fn assignable(&self, actual: &Type, expected: &Type) -> bool {
+		match (actual, expected) {
+			(Type::Named(a), _) if a == "any" => true,
+			(_, Type::Named(e)) if e == "any" => true,
+			(_, Type::Union(expected)) => expected.iter().any(|e| self.assignable(actual, e)),
+			(Type::Union(actual), _) => actual.iter().all(|a| self.assignable(a, expected)),
+			(Type::List(a), Type::List(e)) => self.assignable(a, e),
+			(Type::List(..), Type::Named(e)) | (Type::Named(e), Type::List(..)) => e == "list",
+			(Type::Named(a), Type::Named(e)) => a == e || a.starts_with(&format!("{}.", e)) || self.extends(a, e),
+		}
+	}
+
+	fn extends(&self, name: &str, ancestor: &str) -> bool {
+		match self.structs.get(name) {
+			Some((extends, _)) => extends.iter().any(|parent| parent == ancestor || self.extends(parent, ancestor)),
+			None => false,
+		}
+	}
+
+	fn lookup(&self, name: &str) -> Option<&Variable> {
+		self.scopes.iter().rev().find_map(|scope| scope.get(name))
+	}
+
+	fn define(&mut self, name: &str, kind: Type, annotated: bool) {
+		self.scopes.last_mut().unwrap().insert(name.to_string(), Variable { kind, annotated });
+	}
+
+	fn check_block(&mut self, block: &[Statement]) {
+		for statement in block {
+			self.check_statement(statement);
+		}
+	}
+
+	fn check_function(&mut self, name: &str, params: &[Parameter], body: &[Statement], return_type: &Option<Type>) {
+		self.scopes.push(HashMap::new());
+		self.returns.push((name.to_string(), return_type.clone()));
+
+		for param in params {
+			if let Some(initial) = &param.initial {
+				let actual = self.infer(initial);
+				self.expect(&actual, &param.kind, || format!("argument {} of {}()", param.name, name));
+			}
+
+			self.define(&param.name, param.kind.clone().unwrap_or_else(any), param.kind.is_some());
+		}
+
+		self.check_block(body);
+
+		self.returns.pop();
+		self.scopes.pop();
+	}
+
+	fn check_statement(&mut self, statement: &Statement) {
+		match statement {
+			Statement::CreateDeclaration { name, kind, initial } => {
+				let actual = initial.as_ref().map_or_else(|| named("null"), |initial| self.infer(initial));
+
+				self.expect(&actual, kind, || format!("create {}", name));
+				// Without an annotation the variable can be reassigned to anything, so its type is unknown.
+				self.define(name, kind.clone().unwrap_or_else(any), kind.is_some());
+			}
+			Statement::ConstDeclaration { name, kind, initial } => {
+				let actual = self.infer(initial);
+
+				self.expect(&actual, kind, || format!("const {}", name));
+				self.define(name, kind.clone().unwrap_or(actual), true);
+			}
+			Statement::DestructuringDeclaration { initial, .. } => {
+				self.infer(initial);
+			}
+			Statement::FunctionDeclaration { name, params, body, return_type, .. } => self.check_function(name, params, body, return_type),
+			Statement::StructDeclaration { name, fields, .. } => {
+				for field in fields {
+					match &field.initial {
+						Some(Expression::Closure(params, body, return_type, _)) => self.check_function(&field.name, params, body, return_type),
+						Some(initial) => {
+							let actual = self.infer(initial);
+							self.expect(&actual, &field.kind, || format!("field {} of {}", field.name, name));
+						}
+						None => (),
+					}
+				}
+			}
+			Statement::EnumDeclaration { methods, .. } => {
+				for method in methods {
+					if let Some(Expression::Closure(params, body, return_type, _)) = &method.initial {
+						self.check_function(&method.name, params, body, return_type);
+					}
+				}
+			}
+			Statement::Yield { value } => {
+				self.infer(value);
+			}
+			Statement::Return { value } => {
+				let actual = self.infer(value);
+
+				if let Some((name, returns)) = self.returns.last().cloned() {
+					self.expect(&actual, &returns, || format!("return value of {}()", name));
+				}
+			}
+			Statement::If { condition, others_conditions, otherwise } => {
+				self.infer(&condition.expression);
+				self.check_block(&condition.then);
+
+				for condition in others_conditions.iter().flatten() {
+					self.infer(&condition.expression);
+					self.check_block(&condition.then);
+				}
+
+				if let Some(otherwise) = otherwise {
+					self.check_block(otherwise);
+				}
+			}
+			Statement::For { iterable, value, index, then } => {
+				self.infer(iterable);
+				self.define(value, any(), false);
+
+				if let Some(index) = index {
+					self.define(index, named("number"), false);
+				}
+
+				self.check_block(then);
+			}
+			Statement::While { condition } => {
+				self.infer(&condition.expression);
+				self.check_block(&condition.then);
+			}
+			Statement::Loop { body } => self.check_block(body),
+			Statement::Try { body, name, handler } => {
+				self.check_block(body);
+
+				if let Some(name) = name {
+					self.define(name, named("string"), false);
+				}
+
+				self.check_block(handler);
+			}
+			Statement::Expression { expression } => {
+				self.infer(expression);
+			}
+			Statement::InterfaceDeclaration { .. } | Statement::Break | Statement::Continue => (),
+		}
+	}
+
+	fn check_arguments(&mut self, name: &str, arguments: &CallArguments) {
+		let params = match self.functions.get(name) {
+			Some((params, ..)) => params.clone(),
+			None => {
+				for argument in arguments.get_arguments() {
+					self.infer(argument.get_expression());
+				}
+
+				return;
+			}
+		};
+
+		let mut positional = params.iter().filter(|param| !arguments.get_arguments().iter().any(|a| a.get_name().as_ref() == Some(&param.name)));
+
+		for argument in arguments.get_arguments() {
+			let actual = self.infer(argument.get_expression());
+
+			let param = match argument.get_name() {
+				Some(named) => params.iter().find(|param| param.name == *named),
+				None => positional.next(),
+			};
+
+			if let Some(param) = param {
+				self.expect(&actual, &param.kind, || format!("argument {} of {}()", param.name, name));
+			}
+		}
+	}
+
+	fn infer(&mut self, expression: &Expression) -> Type {
+		match expression {
+			Expression::Number(..) | Expression::BigInt(..) | Expression::Rational(..) => named("number"),
+			Expression::String(..) => named("string"),
+			Expression::Bool(..) => named("bool"),
+			Expression::Null => named("null"),
+			Expression::Range { start, end, step, .. } => {
+				self.infer(start);
+				self.infer(end);
+
+				if let Some(step) = step {
+					self.infer(step);
+				}
+
+				named("range")
+			}
+			Expression::List(items) => {
+				let items = items.iter().map(|item| self.infer(item)).collect::<Vec<Type>>();
+
+				match items.first() {
+					Some(first) if items.iter().all(|item| item == first) => Type::List(Box::new(first.clone())),
+					_ => named("list"),
+				}
+			}
+			Expression::Identifier(name) => match self.lookup(name) {
+				Some(variable) => variable.kind.clone(),
+				None if self.functions.contains_key(name) => named("function"),
+				None => any(),
+			},
+			Expression::Closure(params, body, return_type, _) => {
+				self.check_function("Closure", params, body, return_type);
+				named("function")
+			}
+			Expression::Call(callable, arguments) => match &**callable {
+				Expression::Identifier(name) if self.lookup(name).is_none() => {
+					self.check_arguments(name, arguments);
+
+					match self.functions.get(name) {
+						Some((_, _, true)) => named("task"),
+						Some((_, returns, _)) => returns.clone().unwrap_or_else(any),
+						None => any(),
+					}
+				}
+				callable => {
+					self.infer(callable);
+					self.check_arguments("", arguments);
+					any()
+				}
+			},
+			Expression::MethodCall(target, _, arguments) => {
+				self.infer(target);
+				self.check_arguments("", arguments);
+				any()
+			}
+			Expression::Struct(definition, fields) => {
+				let name = match &**definition {
+					Expression::Identifier(name) => name.clone(),
+					_ => return any(),
+				};
+
+				let declared = self.structs.get(&name).map(|(_, fields)| fields.clone()).unwrap_or_default();
+
+				for (field, value) in fields {
+					let actual = self.infer(value);
+					let kind = declared.iter().find(|f| f.name == *field).and_then(|f| f.kind.clone());
+
+					self.expect(&actual, &kind, || format!("field {} of {}", field, name));
+				}
+
+				named(&name)
+			}
+			Expression::Assign(target, value) => {
+				let actual = self.infer(value);
+
+				match &**target {
+					Expression::Identifier(name) => {
+						let declared = self.lookup(name).filter(|variable| variable.annotated).map(|variable| variable.kind.clone());
+						self.expect(&actual, &declared, || format!("assignment to {}", name));
+					}
+					target => {
+						self.infer(target);
+					}
+				}
+
+				actual
+			}
+			Expression::Infix(left, op, right) => {
+				let (left, right) = (self.infer(left), self.infer(right));
+				let number = named("number");
+
+				match op {
+					Op::Add if left == named("string") || right == named("string") => named("string"),
+					Op::Add | Op::Subtract | Op::Multiply | Op::Divide | Op::Modulo | Op::Pow if left == number && right == number => number,
+					Op::Equals
+					| Op::NotEquals
+					| Op::LessThan
+					| Op::GreaterThan
+					| Op::LessThanOrEquals
+					| Op::GreaterThanOrEquals
+					| Op::And
+					| Op::Or
+					| Op::In
+					| Op::NotIn => named("bool"),
+					_ => any(),
+				}
+			}
+			Expression::Prefix(op, right) => {
+				let right = self.infer(right);
+
+				match op {
+					Op::Bang => named("bool"),
+					_ if right == named("number") => right,
+					_ => any(),
+				}
+			}
+			Expression::MathAssign(target, _, value) => {
+				self.infer(target);
+				self.infer(value);
+				any()
+			}
+			Expression::GetProperty(target, _) => {
+				self.infer(target);
+				any()
+			}
+			Expression::SetProperty(target, _, value) => {
+				self.infer(target);
+				self.infer(value);
+				any()
+			}
+			Expression::Index(target, index) => {
+				self.infer(target);
+
+				if let Some(index) = index {
+					self.infer(index);
+				}
+
+				any()
+			}
+			// Awaiting an async function call gives back its declared return type.
+			Expression::Await(awaited) => match (self.infer(awaited), &**awaited) {
+				(_, Expression::Call(callable, _)) => match &**callable {
+					Expression::Identifier(name) if self.lookup(name).is_none() => match self.functions.get(name) {
+						Some((_, returns, true)) => returns.clone().unwrap_or_else(any),
+						_ => any(),
+					},
+					_ => any(),
+				},
+				_ => any(),
+			},
+			Expression::Spawn(spawned) => {
+				self.infer(spawned);
+				named("task")
+			}
+			Expression::Match(subject, arms) => {
+				self.infer(subject);
+
+				for arm in arms {
+					if let Some(guard) = &arm.guard {
+						self.infer(guard);
+					}
+
+					self.check_block(&arm.body);
+				}
+
+				self.check_exhaustive(subject, arms);
+
+				any()
+			}
+		}
+	}
+
+	// Without an unguarded catch-all arm a match has to cover both booleans, or every variant of the enum its arms match.
+	fn check_exhaustive(&mut self, subject: &Expression, arms: &[MatchArm]) {
+		let catch_all = arms.iter().any(|arm| arm.guard.is_none() && arm.pattern.is_irrefutable());
+		let both_bools = [true, false].iter().all(|b| arms.iter().any(|arm| arm.guard.is_none() && arm.pattern == Pattern::Literal(Expression::Bool(*b))));
+
+		if catch_all || both_bools {
+			return;
+		}
+
+		let subject = match subject {
+			Expression::Identifier(name) => format!("`{}`", name),
+			_ => "value".to_string(),
+		};
+
+		match self.missing_variants(arms) {
+			Some(missing) if missing.is_empty() => (),
+			Some(missing) => self.warnings.push(format!("Warning: match on {} is not exhaustive, missing {}.", subject, missing.join(", "))),
+			None => self.warnings.push(format!("Warning: match on {} is not exhaustive, add a `_` arm.", subject)),
+		}
+	}
+
+	// When every arm matches a variant of the same known enum, lists the variants no unguarded arm fully covers.
+	fn missing_variants(&self, arms: &[MatchArm]) -> Option<Vec<String>> {
+		let enum_name = match arms.first().map(|arm| &arm.pattern) {
+			Some(Pattern::Variant { name, .. }) => name,
+			_ => return None,
+		};
+
+		let variants = self.enums.get(enum_name)?;
+		let mut covered: Vec<&Identifier> = Vec::new();
+
+		for arm in arms {
+			match &arm.pattern {
+				Pattern::Variant { name, variant, fields } if name == enum_name => {
+					let irrefutable = fields.as_ref().is_none_or(|fields| fields.iter().all(Pattern::is_irrefutable));
+
+					if arm.guard.is_none() && irrefutable {
+						covered.push(variant);
+					}
+				}
+				_ => return None,
+			}
+		}
+
+		Some(variants.iter().filter(|v| !covered.contains(v)).map(|v| format!("{}.{}", enum_name, v)).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{parser, token};
+
+	fn warnings(source: &str) -> Vec<String> {
+		check(&parser::parse(token::generate(source)).unwrap()).warnings
+	}
+
+	#[test]
+	fn it_warns_about_matches_that_are_not_exhaustive() {
+		assert_eq!(warnings("create n = 1\nmatch n {\n\t1 => \"one\",\n}"), vec!["Warning: match on `n` is not exhaustive, add a `_` arm."]);
+		assert!(warnings("create n = 1\nmatch n {\n\t1 => \"one\",\n\t_ => \"other\",\n}").is_empty());
+		assert!(warnings("match true {\n\ttrue => 1,\n\tfalse => 0,\n}").is_empty());
+
+		let source = "enum Shape { Circle(radius), Square(side), Empty }\ncreate shape = Shape.Empty\nmatch shape {\n\tShape.Circle(r) => r,\n\tShape.Empty => 0,\n}";
+
+		assert_eq!(warnings(source), vec!["Warning: match on `shape` is not exhaustive, missing Shape.Square."]);
+	}
+
+	#[test]
+	fn it_reports_annotation_mismatches_before_running() {
+		let errors = |source: &str| check(&parser::parse(token::generate(source)).unwrap()).errors;
+
+		assert_eq!(errors("fn f() -> number {\n\treturn \"x\"\n}"), vec!["TypeError: return value of f() expects number, got string."]);
+		assert_eq!(errors("create n: string = 1"), vec!["TypeError: create n expects string, got number."]);
+		assert!(errors("fn area(w: number) -> number {\n\treturn w\n}\narea(2)").is_empty());
+	}
+}
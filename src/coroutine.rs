@@ -0,0 +1,332 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+	ast::*,
+	environment::{Environment, Value},
+	interpreter::{Interpreter, InterpreterResult},
+	iterator::ValueIterator,
+};
+
+// A function body that can stop part way and be resumed: generators stop at `yield`, async functions at `await`.
+// Instead of recursing through `run_statement`, the blocks that contain one of those are walked with an explicit
+// stack of frames, so the body can pick up from the same place later. Other statements run through the interpreter
+// as usual.
+pub struct Coroutine {
+	environment: Rc<RefCell<Environment>>,
+	frames: Vec<Frame>,
+	// Statement waiting on an `await`, it reads the awaited value from `$await` once resumed.
+	pending: Option<Statement>,
+}
+
+pub enum Suspension {
+	Yield(Value),
+	Await(Value),
+	Return(Value),
+}
+
+enum Frame {
+	Block { body: Block, position: usize },
+	Try { body: Block, position: usize, name: Option<Identifier>, handler: Block },
+	// Body of the match arm that was picked, `shadowed` keeps what its bindings replaced in the environment.
+	Arm { body: Block, position: usize, shadowed: Vec<(Identifier, Option<Value>)> },
+	Loop { body: Block, position: usize },
+	While { condition: Expression, body: Block, position: usize },
+	For { iterator: ValueIterator, value: Identifier, index: Option<Identifier>, count: usize, body: Block, position: usize },
+}
+
+impl Frame {
+	fn is_loop(&self) -> bool {
+		matches!(self, Frame::Loop { .. } | Frame::While { .. } | Frame::For { .. })
+	}
+}
+
+impl Coroutine {
+	pub fn new(environment: Rc<RefCell<Environment>>, body: Block) -> Self {
+		Self { environment, frames: vec![Frame::Block { body, position: 0 }], pending: None }
+	}
+
+	// Generator side, the next yielded item or `None` once the body finishes or returns.
+	pub fn next(&mut self, interpreter: &mut Interpreter) -> Result<Option<Value>, InterpreterResult> {
+		match self.resume(interpreter, Ok(Value::Null))? {
+			Suspension::Yield(value) => Ok(Some(value)),
+			Suspension::Return(..) => Ok(None),
+			Suspension::Await(..) => Err(InterpreterResult::Error("Cannot await inside a generator, only inside async functions.".to_string())),
+		}
+	}
+
+	// Runs until the next suspension point, `sent` is the result of the `await` the coroutine stopped at.
+	pub fn resume(&mut self, interpreter: &mut Interpreter, sent: Result<Value, InterpreterResult>) -> Result<Suspension, InterpreterResult> {
+		let environment = Rc::clone(&self.environment);
+
+		match interpreter.with_environment(environment, |interpreter| self.step(interpreter, sent)) {
+			result @ Ok(Suspension::Yield(..) | Suspension::Await(..)) => result,
+			result => {
+				self.frames.clear();
+				self.pending = None;
+				result
+			}
+		}
+	}
+
+	fn step(&mut self, interpreter: &mut Interpreter, sent: Result<Value, InterpreterResult>) -> Result<Suspension, InterpreterResult> {
+		let mut outcome = match self.pending.take() {
+			Some(statement) => self.settle(interpreter, statement, sent),
+			None => self.advance(interpreter),
+		};
+
+		// An error inside a `try` block moves on to its handler, the body carries on from there.
+		loop {
+			match outcome {
+				Err(error) if error.is_error() => {
+					self.catch(error)?;
+					outcome = self.advance(interpreter);
+				}
+				outcome => return outcome,
+			}
+		}
+	}
+
+	// Finishes the statement that stopped at an `await`, a failed task raises its error in its place.
+	fn settle(&mut self, interpreter: &mut Interpreter, statement: Statement, sent: Result<Value, InterpreterResult>) -> Result<Suspension, InterpreterResult> {
+		let result = sent.and_then(|value| {
+			self.environment.borrow_mut().set("$await", value);
+
+			let result = interpreter.run_statement(statement);
+
+			self.environment.borrow_mut().drop("$await");
+
+			result
+		});
+
+		match self.finish(result)? {
+			Some(suspension) => Ok(suspension),
+			None => self.advance(interpreter),
+		}
+	}
+
+	fn advance(&mut self, interpreter: &mut Interpreter) -> Result<Suspension, InterpreterResult> {
+		loop {
+			let statement = match self.frames.last_mut() {
+				None => return Ok(Suspension::Return(Value::Null)),
+				Some(Frame::Block { body, position } | Frame::Try { body, position, .. } | Frame::Arm { body, position, .. }) => match body.get(*position) {
+					Some(statement) => {
+						*position += 1;
+						statement.clone()
+					}
+					None => {
+						self.leave();
+						continue;
+					}
+				},
+				Some(Frame::Loop { body, position }) => match body.get(*position) {
+					Some(statement) => {
+						*position += 1;
+						statement.clone()
+					}
+					None => {
+						*position = 0;
+						continue;
+					}
+				},
+				Some(Frame::While { condition, body, position }) => match body.get(*position) {
+					Some(statement) => {
+						*position += 1;
+						statement.clone()
+					}
+					None => {
+						if interpreter.run_expression(condition.clone())?.to_bool() {
+							*position = 0;
+						} else {
+							self.frames.pop();
+						}
+
+						continue;
+					}
+				},
+				Some(Frame::For { iterator, value, index, count, body, position }) => match body.get(*position) {
+					Some(statement) => {
+						*position += 1;
+						statement.clone()
+					}
+					None => {
+						match iterator.next(interpreter)? {
+							Some(item) => {
+								self.environment.borrow_mut().set(value.clone(), item);
+
+								if let Some(index) = index {
+									self.environment.borrow_mut().set(index.clone(), Value::Number(*count as f64));
+								}
+
+								*count += 1;
+								*position = 0;
+							}
+							None => self.leave(),
+						}
+
+						continue;
+					}
+				},
+			};
+
+			if !statement.suspends() {
+				match self.finish(interpreter.run_statement(statement))? {
+					Some(suspension) => return Ok(suspension),
+					None => continue,
+				}
+			}
+
+			if let Some((awaited, statement)) = statement.split_await() {
+				let awaited = interpreter.run_expression(awaited)?;
+				self.pending = Some(statement);
+
+				return Ok(Suspension::Await(awaited));
+			}
+
+			match statement {
+				Statement::Yield { value } => return Ok(Suspension::Yield(interpreter.run_expression(value)?)),
+				Statement::If { condition, others_conditions, otherwise } => {
+					let mut branch = None;
+
+					for condition in std::iter::once(condition).chain(others_conditions.into_iter().flatten()) {
+						if interpreter.run_expression(condition.expression)?.to_bool() {
+							branch = Some(condition.then);
+							break;
+						}
+					}
+
+					if let Some(body) = branch.or(otherwise) {
+						self.frames.push(Frame::Block { body, position: 0 });
+					}
+				}
+				Statement::While { condition } => {
+					let position = condition.then.len();
+					self.frames.push(Frame::While { condition: condition.expression, body: condition.then, position });
+				}
+				Statement::Expression { expression: Expression::Match(subject, arms) } => {
+					let (bindings, body) = interpreter.select_match_arm(*subject, arms)?;
+					let mut shadowed = Vec::new();
+
+					for (name, value) in bindings {
+						shadowed.push((name.clone(), self.environment.borrow().get(name.clone())));
+						self.environment.borrow_mut().set(name, value);
+					}
+
+					self.frames.push(Frame::Arm { body, position: 0, shadowed });
+				}
+				Statement::Loop { body } => self.frames.push(Frame::Loop { body, position: 0 }),
+				Statement::Try { body, name, handler } => self.frames.push(Frame::Try { body, position: 0, name, handler }),
+				Statement::For { iterable, value, index, then } => {
+					let iterable = interpreter.run_expression(iterable)?;
+					let iterator = interpreter.iterate(iterable)?;
+					let position = then.len();
+
+					self.frames.push(Frame::For { iterator, value, index, count: 0, body: then, position });
+				}
+				_ => unreachable!(),
+			}
+		}
+	}
+
+	// How a statement run by the interpreter ended, `Some` when the coroutine stops there.
+	fn finish(&mut self, result: Result<(), InterpreterResult>) -> Result<Option<Suspension>, InterpreterResult> {
+		match result {
+			Ok(_) => (),
+			Err(InterpreterResult::Break) => self.unwind(true),
+			Err(InterpreterResult::Continue) => self.unwind(false),
+			Err(InterpreterResult::Return(value)) => return Ok(Some(Suspension::Return(value))),
+			Err(err) => return Err(err),
+		}
+
+		Ok(None)
+	}
+
+	// Leaves every block up to the innermost `try` and continues with its handler, errors outside of one go on up.
+	fn catch(&mut self, error: InterpreterResult) -> Result<(), InterpreterResult> {
+		let index = match self.frames.iter().rposition(|frame| matches!(frame, Frame::Try { .. })) {
+			Some(index) => index,
+			None => return Err(error),
+		};
+
+		while self.frames.len() > index + 1 {
+			self.leave();
+		}
+
+		if let Some(Frame::Try { name, handler, .. }) = self.frames.pop() {
+			if let Some(name) = name {
+				self.environment.borrow_mut().set(name, Value::String(error.to_string()));
+			}
+
+			self.frames.push(Frame::Block { body: handler, position: 0 });
+		}
+
+		Ok(())
+	}
+
+	// `break` and `continue` leave every block up to the innermost loop, `continue` then starts its next iteration.
+	fn unwind(&mut self, exit: bool) {
+		while let Some(frame) = self.frames.last_mut() {
+			if frame.is_loop() {
+				break;
+			}
+
+			self.leave();
+		}
+
+		match self.frames.last_mut() {
+			Some(_) if exit => self.leave(),
+			Some(Frame::Loop { body, position } | Frame::While { body, position, .. } | Frame::For { body, position, .. }) => *position = body.len(),
+			_ => (),
+		}
+	}
+
+	// Pops the innermost frame, a `for` drops its variables and a match arm puts back what its bindings shadowed.
+	fn leave(&mut self) {
+		match self.frames.pop() {
+			Some(Frame::For { value, index, .. }) => {
+				self.environment.borrow_mut().drop(value);
+
+				if let Some(index) = index {
+					self.environment.borrow_mut().drop(index);
+				}
+			}
+			Some(Frame::Arm { shadowed, .. }) => {
+				for (name, previous) in shadowed {
+					match previous {
+						Some(value) => self.environment.borrow_mut().set(name, value),
+						None => self.environment.borrow_mut().drop(name),
+					}
+				}
+			}
+			_ => (),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::{caught, evaluate};
+
+	const NATURALS: &str = "fn naturals(from = 0) {\n\tcreate n = from\n\tloop {\n\t\tyield n\n\t\tn += 1\n\t}\n}";
+
+	#[test]
+	fn it_runs_generator_bodies_only_as_items_are_requested() {
+		assert_eq!(evaluate(&format!("{}\ncreate numbers = naturals(1)\nnumbers.next!()\nnumbers.next!()", NATURALS)).unwrap().to_string(), "2");
+		assert_eq!(evaluate(&format!("{}\nnaturals(5).take!(3).collect!()", NATURALS)).unwrap().to_string(), "[5, 6, 7]");
+		assert_eq!(evaluate("fn two() {\n\tyield 1\n\tyield 2\n}\ncreate items = two()\nitems.next!()\nitems.next!()\nitems.next!() == null").unwrap().to_string(), "true");
+
+		let source = format!("{}\ncreate total = 0\nfor n in naturals() {{\n\tif n > 4 {{\n\t\tbreak\n\t}}\n\ttotal += n\n}}\ntotal", NATURALS);
+
+		assert_eq!(evaluate(&source).unwrap().to_string(), "10");
+	}
+
+	#[test]
+	fn it_suspends_inside_match_arms() {
+		let source = "fn labels(items) {\n\tfor item in items {\n\t\tmatch item {\n\t\t\t0 => {\n\t\t\t\tyield \"zero\"\n\t\t\t}\n\t\t\tn if n > 10 => {\n\t\t\t\tyield \"big\"\n\t\t\t\tyield n\n\t\t\t}\n\t\t\t_ => null,\n\t\t}\n\t}\n}";
+
+		assert_eq!(evaluate(&format!("{}\nlabels([0, 5, 20]).collect!()", source)).unwrap().to_string(), "[zero, big, 20]");
+		// The names an arm binds don't outlive it.
+		assert_eq!(evaluate("fn read() {\n\tcreate n = \"outer\"\n\tmatch 20 {\n\t\tn => {\n\t\t\tyield n\n\t\t}\n\t}\n\tyield n\n}\nread().collect!()").unwrap().to_string(), "[20, outer]");
+		assert_eq!(evaluate("async fn double(n) {\n\treturn n * 2\n}\nasync fn pick(n) {\n\tmatch n {\n\t\t1 => {\n\t\t\tcreate d = await double(n)\n\t\t\treturn d\n\t\t}\n\t\t_ => {\n\t\t\treturn 0\n\t\t}\n\t}\n}\nawait pick(1)").unwrap().to_string(), "2");
+		assert_eq!(caught("fn never() {\n\tmatch 3 {\n\t\t1 => {\n\t\t\tyield 1\n\t\t}\n\t}\n}\nnever().collect!()"), "No match arm matched value 3.");
+	}
+}
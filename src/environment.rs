@@ -1,18 +1,31 @@
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, LocalResult, TimeZone, Utc};
 use hashbrown::HashMap;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive};
+use regex::Regex;
 use std::{
 	cell::RefCell,
+	cmp::Ordering,
 	fmt::{Debug, Formatter, Result as FmtResult},
 	iter::Iterator,
 	rc::Rc,
 };
 
 use crate::{
-	ast::{ArgumentValues, Block, Expression, Parameter},
+	ast::{ArgumentValues, Block, Expression, Parameter, Type, Variant},
+	event_loop::Task,
+	gc,
 	interpreter::{Interpreter, InterpreterResult},
+	iterator::ValueIterator,
+	map::Map,
+	numeric,
+	range::Range,
+	worker::WorkerHandle,
+	zone::{self, Zone},
 };
 
-pub type NativeFunctionCallback = fn(&mut Interpreter, ArgumentValues) -> Value;
+pub type NativeFunctionCallback = fn(&mut Interpreter, ArgumentValues) -> Result<Value, InterpreterResult>;
 pub type NativeMethodCallback = fn(&mut Interpreter, Value, ArgumentValues) -> Result<Value, InterpreterResult>;
 
 #[derive(Debug, Clone)]
@@ -30,17 +43,26 @@ impl Environment {
 	}
 
 	pub fn get(&self, name: impl Into<String>) -> Option<Value> {
-		if let Some(value) = self.values.get(&name.into()) {
-			Some(value.clone())
-		} else {
-			None
-		}
+		self.values.get(&name.into()).cloned()
 	}
 
 	pub fn drop(&mut self, name: impl Into<String>) {
 		self.values.remove(&name.into());
 	}
 
+	pub fn values(&self) -> impl Iterator<Item = &Value> {
+		self.values.values()
+	}
+
+	// Empties the scope and hands back what it held, used by the cycle collector.
+	pub fn clear(&mut self) -> Vec<Value> {
+		self.values.drain().map(|(_, value)| value).collect()
+	}
+
+	pub fn entries(&self) -> Vec<(String, Value)> {
+		self.values.iter().map(|(name, value)| (name.clone(), value.clone())).collect()
+	}
+
 	pub fn dump(&self) {
 		dbg!(self.values.clone());
 	}
@@ -49,14 +71,26 @@ impl Environment {
 #[derive(Clone)]
 pub enum Value {
 	Number(f64),
+	BigInt(BigInt),
+	Rational(BigRational),
 	String(String),
 	Null,
 	Bool(bool),
-	DateTime(DateTime<Utc>),
-	Struct { name: String, fields: Vec<Parameter>, methods: Rc<RefCell<HashMap<String, Value>>>, propreties: Option<Vec<Value>> },
+	DateTime(DateTime<Zone>),
+	Duration(Duration),
+	Regex(Regex),
+	Struct { name: String, fields: Vec<Parameter>, methods: Rc<RefCell<HashMap<String, Value>>>, parent: Option<Box<Value>>, interfaces: Vec<String> },
+	Interface { name: String, methods: Vec<(String, Vec<Parameter>)> },
 	StructInstance { environment: Rc<RefCell<Environment>>, definition: Box<Value> },
+	Enum { name: String, variants: Vec<Variant>, methods: Rc<RefCell<HashMap<String, Value>>> },
+	EnumVariant { definition: Box<Value>, variant: String, values: Vec<Value> },
 	List(Rc<RefCell<Vec<Value>>>),
-	Function { name: String, params: Vec<Parameter>, body: Block, environment: Option<Environment>, context: Option<Expression> },
+	Map(Rc<RefCell<Map>>),
+	Iterator(Rc<RefCell<ValueIterator>>),
+	Task(Rc<RefCell<Task>>),
+	Worker(Rc<WorkerHandle>),
+	Range(Range),
+	Function { name: String, params: Vec<Parameter>, body: Block, return_type: Option<Type>, is_async: bool, environment: Option<Environment>, context: Option<Box<Expression>> },
 	NativeFunction { name: String, callback: NativeFunctionCallback },
 	NativeMethod { name: String, callback: NativeMethodCallback, context: Expression },
 	Constant(Box<Value>),
@@ -69,13 +103,25 @@ impl Debug for Value {
 			"{}",
 			match self {
 				Value::Constant(v) => format!("{:?}", v),
-				Value::Number(n) => n.to_string(),
+				Value::Number(n) => numeric::format(*n),
+				Value::BigInt(n) => n.to_string(),
+				Value::Rational(r) => r.to_string(),
 				Value::String(s) => s.to_string(),
 				Value::DateTime(dt) => dt.to_string(),
+				Value::Duration(duration) => crate::stdlib::duration::format(duration),
+				Value::Regex(regex) => format!("/{}/", regex.as_str()),
 				Value::Null => "null".to_string(),
+				Value::Iterator(..) => "<iterator>".to_string(),
+				Value::Task(..) => "<task>".to_string(),
+				Value::Worker(..) => "<worker>".to_string(),
+				Value::Range(range) => range.to_string(),
 				Value::NativeFunction { name, .. } | Value::NativeMethod { name, .. } => format!("<{}>", name),
 				Value::Function { name, params, .. } =>
-					format!("<{}>({})", name, params.into_iter().map(|p| p.name.clone()).collect::<Vec<String>>().join(", ")),
+					format!("<{}>({})", name, params.iter().map(|p| p.name.clone()).collect::<Vec<String>>().join(", ")),
+				Value::Enum { name, .. } => format!("<enum:{}>", name),
+				Value::Interface { name, .. } => format!("<interface:{}>", name),
+				Value::EnumVariant { values, .. } if values.is_empty() => self.clone().typestring(),
+				Value::EnumVariant { .. } => self.nested_string(true, &mut Vec::new()),
 				Value::StructInstance { definition, .. } => {
 					let name = match *definition.clone() {
 						Value::Struct { name, .. } => name,
@@ -86,11 +132,10 @@ impl Debug for Value {
 				}
 				Value::Struct { name, methods, fields, .. } => {
 					let name = format!("<struct:{}>", name);
-					let mut fields = fields.into_iter().map(|p| p.name.clone()).collect::<Vec<String>>();
+					let mut fields = fields.iter().map(|p| p.name.clone()).collect::<Vec<String>>();
 					let mut methods = methods
 						.borrow()
 						.keys()
-						.into_iter()
 						.map(|p| {
 							let mut p = p.clone();
 							p.push_str("()");
@@ -104,30 +149,63 @@ impl Debug for Value {
 
 					fields
 				}
-				Value::List(items) => {
-					let mut buffer = String::from("[");
-					let items = items.borrow();
-
-					for (i, item) in items.iter().enumerate() {
-						buffer.push_str(&item.clone().to_string());
-
-						if i != items.len() - 1 {
-							buffer.push_str(", ");
-						}
-					}
-
-					buffer.push_str("]");
-					buffer
-				}
+				Value::List(..) | Value::Map(..) => self.nested_string(true, &mut Vec::new()),
 				Value::Bool(true) => "true".to_string(),
 				Value::Bool(false) => "false".to_string(),
-				_ => todo!(),
 			}
 		)
 	}
 }
 
 impl Value {
+	// Lists and maps are allocated through the cycle collector, so one that ends up containing itself can still be freed.
+	pub fn list(items: Vec<Value>) -> Value {
+		Value::List(gc::list(items))
+	}
+
+	pub fn map(map: Map) -> Value {
+		Value::Map(gc::map(map))
+	}
+
+	// Text of a value inside a list, map or enum payload. Lists and maps already being printed further up, which
+	// contain themselves, are shown as `[...]` and `{...}`. List and map items print like `to_string`, payloads like `Debug`.
+	fn nested_string(&self, debug: bool, visiting: &mut Vec<*const ()>) -> String {
+		match self {
+			Value::Constant(v) => v.nested_string(debug, visiting),
+			Value::List(items) => {
+				let pointer = Rc::as_ptr(items) as *const ();
+
+				if visiting.contains(&pointer) {
+					return "[...]".to_string();
+				}
+
+				visiting.push(pointer);
+				let items = items.borrow().iter().map(|item| item.nested_string(false, visiting)).collect::<Vec<String>>();
+				visiting.pop();
+
+				format!("[{}]", items.join(", "))
+			}
+			Value::Map(map) => {
+				let pointer = Rc::as_ptr(map) as *const ();
+
+				if visiting.contains(&pointer) {
+					return "{...}".to_string();
+				}
+
+				visiting.push(pointer);
+				let entries = map.borrow().iter().map(|(key, value)| format!("{}: {}", key, value.nested_string(false, visiting))).collect::<Vec<String>>();
+				visiting.pop();
+
+				format!("{{{}}}", entries.join(", "))
+			}
+			Value::EnumVariant { values, .. } if !values.is_empty() => {
+				format!("{}({})", self.clone().typestring(), values.iter().map(|v| v.nested_string(true, visiting)).collect::<Vec<String>>().join(", "))
+			}
+			value if debug => format!("{:?}", value),
+			value => value.clone().to_string(),
+		}
+	}
+
 	pub fn to_vec(self) -> Rc<RefCell<Vec<Value>>> {
 		match self {
 			Value::List(list) => list,
@@ -136,24 +214,42 @@ impl Value {
 		}
 	}
 
-	pub fn to_number(self) -> f64 {
+	pub fn to_map(self) -> Rc<RefCell<Map>> {
 		match self {
+			Value::Map(map) => map,
+			Value::Constant(v) => v.to_map(),
+			_ => unreachable!(),
+		}
+	}
+
+	// `None` for values that have no numeric meaning, like lists or structs.
+	pub fn to_number(self) -> Option<f64> {
+		Some(match self {
 			Value::Number(n) => n,
+			Value::BigInt(n) => n.to_f64().unwrap_or(f64::NAN),
+			Value::Rational(r) => r.to_f64().unwrap_or(f64::NAN),
 			Value::Bool(true) => 1.0,
 			Value::Null | Value::Bool(false) => 0.0,
-			Value::String(s) => match s.trim().parse::<f64>() {
-				Ok(f) => f,
-				Err(_) => 0.0,
-			},
-			Value::Constant(v) => v.to_number(),
-			_ => unreachable!(),
+			Value::String(s) => s.trim().parse::<f64>().unwrap_or(0.0),
+			Value::Constant(v) => return v.to_number(),
+			_ => return None,
+		})
+	}
+
+	// Like `to_number`, a catchable type error naming `context` when the value is not a number.
+	pub fn expect_number(self, context: &str) -> Result<f64, InterpreterResult> {
+		match self.clone().to_number() {
+			Some(n) => Ok(n),
+			None => Err(InterpreterResult::TypeMismatch(context.to_string(), "number".to_string(), self.typestring())),
 		}
 	}
 
 	pub fn to_string(self) -> String {
 		match self {
 			Value::String(s) => s,
-			Value::Number(n) => n.to_string(),
+			Value::Number(n) => numeric::format(n),
+			Value::BigInt(n) => n.to_string(),
+			Value::Rational(r) => r.to_string(),
 			Value::Bool(_) => {
 				if self.to_bool() {
 					"true".to_string()
@@ -162,17 +258,28 @@ impl Value {
 				}
 			}
 			Value::DateTime(dt) => dt.to_rfc3339(),
+			Value::Duration(duration) => crate::stdlib::duration::format(&duration),
+			Value::Regex(regex) => format!("/{}/", regex.as_str()),
 			Value::Null => "".to_string(),
-			v @ Value::Function { .. } | v @ Value::StructInstance { .. } | v @ Value::List(..) => format!("{:?}", v),
+			Value::Iterator(..) => "<iterator>".to_string(),
+			Value::Task(..) => "<task>".to_string(),
+			Value::Worker(..) => "<worker>".to_string(),
+			Value::Range(range) => range.to_string(),
+			v @ Value::Function { .. }
+			| v @ Value::StructInstance { .. }
+			| v @ Value::List(..)
+			| v @ Value::Map(..)
+			| v @ Value::Enum { .. }
+			| v @ Value::Interface { .. }
+			| v @ Value::EnumVariant { .. } => format!("{:?}", v),
 			Value::Constant(v) => v.to_string(),
 			Value::NativeFunction { name, .. } | Value::NativeMethod { name, .. } => format!("<{}>", name),
 			Value::Struct { name, methods, fields, .. } => {
 				let name = format!("<struct:{}>", name);
-				let mut fields = fields.into_iter().map(|p| p.name.clone()).collect::<Vec<String>>();
+				let mut fields = fields.iter().map(|p| p.name.clone()).collect::<Vec<String>>();
 				let mut methods = methods
 					.borrow()
 					.keys()
-					.into_iter()
 					.map(|p| {
 						let mut p = p.clone();
 						p.push_str("()");
@@ -186,63 +293,201 @@ impl Value {
 
 				fields
 			}
-			_ => todo!(),
 		}
 	}
 
 	pub fn to_bool(self) -> bool {
 		match self {
-			Value::Bool(true) | Value::Function { .. } => true,
+			Value::Bool(true) | Value::Function { .. } | Value::Iterator(..) | Value::Task(..) | Value::Worker(..) | Value::EnumVariant { .. } => true,
 			Value::String(s) => !s.is_empty(),
 			Value::Number(n) => n > 0.0,
+			Value::BigInt(n) => n.is_positive(),
+			Value::Rational(r) => r.is_positive(),
+			Value::Range(range) => range.len() > 0,
 			Value::Constant(v) => v.to_bool(),
 			_ => false,
 		}
 	}
 
-	pub fn to_datetime(self) -> DateTime<Utc> {
+	// Strings are parsed as ISO 8601 or RFC 2822 (an empty string is now) and numbers are Unix timestamps in seconds.
+	pub fn to_datetime(self) -> Result<DateTime<Zone>, InterpreterResult> {
 		match self {
-			Value::String(s) => {
-				let s = s.trim();
+			Value::String(s) if s.trim().is_empty() => Ok(zone::now()),
+			Value::String(s) => zone::parse(&s).map_err(InterpreterResult::Error),
+			Value::Number(n) => match Utc.timestamp_millis_opt((n * 1000.0) as i64) {
+				LocalResult::Single(datetime) if n.is_finite() => Ok(datetime.with_timezone(&Zone::utc())),
+				_ => Err(InterpreterResult::Error(format!("Timestamp {} is out of range.", numeric::format(n)))),
+			},
+			Value::Constant(v) => v.to_datetime(),
+			Value::DateTime(dt) => Ok(dt),
+			v => Err(InterpreterResult::Error(format!("Cannot convert {} to a datetime.", v.typestring()))),
+		}
+	}
+
+	// Equality used by `==`, `!=`, `in` and `not in`. Values of different types are never equal (no coercion),
+	// except numbers, which compare by value across floats, big integers and rationals. Lists compare item by
+	// item and struct instances compare their definition and every non-method field. Functions are equal when
+	// they share name, parameters and body, and struct definitions when they share a name. Identity can be
+	// checked explicitly with the `same?` global function.
+	pub fn equals(&self, other: &Value) -> bool {
+		self.equals_visiting(other, &mut Vec::new())
+	}
+
+	// `visiting` holds the pairs of containers being compared further up, meeting one again means both sides
+	// contain themselves at the same place, which is treated as equal instead of recursing forever.
+	fn equals_visiting(&self, other: &Value, visiting: &mut Vec<(*const (), *const ())>) -> bool {
+		match (self, other) {
+			(Value::Constant(l), r) => l.equals_visiting(r, visiting),
+			(l, Value::Constant(r)) => l.equals_visiting(r, visiting),
+			(l, r) if numeric::is_numeric(l) && numeric::is_numeric(r) => numeric::equals(l, r),
+			(Value::String(l), Value::String(r)) => l == r,
+			(Value::Bool(l), Value::Bool(r)) => l == r,
+			(Value::DateTime(l), Value::DateTime(r)) => l == r,
+			(Value::Duration(l), Value::Duration(r)) => l == r,
+			(Value::Regex(l), Value::Regex(r)) => l.as_str() == r.as_str(),
+			(Value::Null, Value::Null) => true,
+			(Value::List(l), Value::List(r)) => {
+				visit(l, r, visiting, |visiting| {
+					let (l, r) = (l.borrow(), r.borrow());
 
-				if s.len() == 0 {
-					return Utc::now();
+					l.len() == r.len() && l.iter().zip(r.iter()).all(|(l, r)| l.equals_visiting(r, visiting))
+				})
+			}
+			// Maps are equal when they hold the same keys with equal values, whatever order they were inserted in.
+			(Value::Map(l), Value::Map(r)) => {
+				visit(l, r, visiting, |visiting| {
+					let (l, r) = (l.borrow(), r.borrow());
+
+					l.len() == r.len() && l.iter().all(|(key, value)| r.get(key).is_some_and(|other| value.equals_visiting(other, visiting)))
+				})
+			}
+			(Value::StructInstance { environment: l, definition: l_definition }, Value::StructInstance { environment: r, definition: r_definition }) => {
+				if !l_definition.equals(r_definition) {
+					return false;
 				}
 
-				let mut parts = s.split(' ');
+				visit(l, r, visiting, |visiting| {
+					let (l, r) = (l.borrow(), r.borrow());
+					let fields = |environment: &Environment| environment.values.iter().filter(|(_, v)| !v.is_function()).count();
 
-				let date = parts.next().unwrap();
-				let time = parts.next().unwrap();
+					fields(&l) == fields(&r)
+						&& l.values.iter().filter(|(_, v)| !v.is_function()).all(|(name, v)| r.get(name.clone()).is_some_and(|other| v.equals_visiting(&other, visiting)))
+				})
+			}
+			(Value::Struct { name: l, .. }, Value::Struct { name: r, .. }) => l == r,
+			(Value::Enum { name: l, .. }, Value::Enum { name: r, .. }) => l == r,
+			(Value::Interface { name: l, .. }, Value::Interface { name: r, .. }) => l == r,
+			(Value::EnumVariant { values: l_values, .. }, Value::EnumVariant { values: r_values, .. }) => {
+				self.clone().typestring() == other.clone().typestring()
+					&& l_values.len() == r_values.len()
+					&& l_values.iter().zip(r_values.iter()).all(|(l, r)| l.equals_visiting(r, visiting))
+			}
+			(Value::Iterator(l), Value::Iterator(r)) => Rc::ptr_eq(l, r),
+			(Value::Task(l), Value::Task(r)) => Rc::ptr_eq(l, r),
+			(Value::Worker(l), Value::Worker(r)) => Rc::ptr_eq(l, r),
+			(Value::Range(l), Value::Range(r)) => l == r,
+			(
+				Value::Function { name: l, params: l_params, body: l_body, return_type: l_returns, is_async: l_async, .. },
+				Value::Function { name: r, params: r_params, body: r_body, return_type: r_returns, is_async: r_async, .. },
+			) => l == r && l_params == r_params && l_body == r_body && l_returns == r_returns && l_async == r_async,
+			(Value::NativeFunction { name: l, .. }, Value::NativeFunction { name: r, .. }) => l == r,
+			_ => false,
+		}
+	}
+
+	// Identity: the same list, map or struct instance in memory, plain equality for everything else.
+	pub fn same(&self, other: &Value) -> bool {
+		match (self, other) {
+			(Value::Constant(l), r) => l.same(r),
+			(l, Value::Constant(r)) => l.same(r),
+			(Value::List(l), Value::List(r)) => Rc::ptr_eq(l, r),
+			(Value::Map(l), Value::Map(r)) => Rc::ptr_eq(l, r),
+			(Value::StructInstance { environment: l, .. }, Value::StructInstance { environment: r, .. }) => Rc::ptr_eq(l, r),
+			(l, r) => l.equals(r),
+		}
+	}
+
+	// Ordering used by `<`, `>`, `<=` and `>=`. Defined for numbers, strings (lexicographic), datetimes, durations and lists
+	// (lexicographic by item, then by length). `None` means the two values cannot be ordered.
+	pub fn compare(&self, other: &Value) -> Option<Ordering> {
+		self.compare_visiting(other, &mut Vec::new())
+	}
 
-				let date = date.split('-').collect::<Vec<&str>>();
-				let time = time.split(':').collect::<Vec<&str>>();
+	fn compare_visiting(&self, other: &Value, visiting: &mut Vec<(*const (), *const ())>) -> Option<Ordering> {
+		match (self, other) {
+			(Value::Constant(l), r) => l.compare_visiting(r, visiting),
+			(l, Value::Constant(r)) => l.compare_visiting(r, visiting),
+			(l, r) if numeric::is_numeric(l) && numeric::is_numeric(r) => numeric::compare(l, r),
+			(Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+			(Value::DateTime(l), Value::DateTime(r)) => Some(l.cmp(r)),
+			(Value::Duration(l), Value::Duration(r)) => Some(l.cmp(r)),
+			(Value::List(l), Value::List(r)) => {
+				let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+
+				if pair.0 == pair.1 || visiting.contains(&pair) {
+					return Some(Ordering::Equal);
+				}
+
+				visiting.push(pair);
 
-				let year = date[0].parse::<i32>().unwrap();
-				let month = date[1].parse::<u32>().unwrap();
-				let day = date[2].parse::<u32>().unwrap();
+				let ordering = (|| {
+					let (l, r) = (l.borrow(), r.borrow());
 
-				let hour = time[0].parse::<u32>().unwrap();
-				let minute = time[1].parse::<u32>().unwrap();
-				let second = time[2].parse::<u32>().unwrap();
+					for (l, r) in l.iter().zip(r.iter()) {
+						match l.compare_visiting(r, visiting)? {
+							Ordering::Equal => continue,
+							ordering => return Some(ordering),
+						}
+					}
 
-				Utc.ymd(year, month, day).and_hms(hour, minute, second)
+					Some(l.len().cmp(&r.len()))
+				})();
+
+				visiting.pop();
+				ordering
 			}
-			Value::Number(n) => Utc.timestamp(n as i64, 0),
-			Value::Constant(v) => v.to_datetime(),
-			Value::DateTime(dt) => dt,
-			_ => unreachable!(),
+			_ => None,
 		}
 	}
 
-	pub fn is(self, other: Value) -> bool {
-		match (self, other.clone()) {
-			(Value::String(l), r) => l == r.to_string(),
-			(Value::Number(n), r) => n == r.to_number(),
-			(Value::Bool(true), r) => r.to_bool() == true,
-			(Value::Bool(false), r) => r.to_bool() == false,
-			(Value::DateTime(dt), r) => dt == r.to_datetime(),
-			(Value::Null, Value::Null) => true,
-			(Value::Constant(v), _) => v.is(other),
+	pub fn unwrap_constant(self) -> Value {
+		match self {
+			Value::Constant(v) => v.unwrap_constant(),
+			v => v,
+		}
+	}
+
+	pub fn is_temporal(&self) -> bool {
+		matches!(self, Value::DateTime(..) | Value::Duration(..))
+	}
+
+	pub fn is_function(&self) -> bool {
+		match self {
+			Value::Function { .. } | Value::NativeFunction { .. } | Value::NativeMethod { .. } => true,
+			Value::Constant(v) => v.is_function(),
+			_ => false,
+		}
+	}
+
+	// Whether a value is an instance of a struct (or one of its descendants), implements an interface or is a variant of an enum.
+	pub fn is_instance_of(&self, kind: &Value) -> bool {
+		match (self, kind) {
+			(Value::Constant(v), kind) => v.is_instance_of(kind),
+			(value, Value::Constant(kind)) => value.is_instance_of(kind),
+			(Value::StructInstance { definition, .. }, kind) => {
+				let mut current = Some(&**definition);
+
+				while let Some(Value::Struct { name, parent, interfaces, .. }) = current {
+					match kind {
+						Value::Struct { name: kind, .. } if kind == name => return true,
+						Value::Interface { name: kind, .. } if interfaces.contains(kind) => return true,
+						_ => current = parent.as_deref(),
+					}
+				}
+
+				false
+			}
+			(Value::EnumVariant { definition, .. }, Value::Enum { .. }) => definition.equals(kind),
 			_ => false,
 		}
 	}
@@ -250,9 +495,11 @@ impl Value {
 	pub fn typestring(self) -> String {
 		match self {
 			Value::String(..) => "string".into(),
-			Value::Number(..) => "number".into(),
+			Value::Number(..) | Value::BigInt(..) | Value::Rational(..) => "number".into(),
 			Value::Bool(..) => "bool".into(),
 			Value::DateTime(..) => "datetime".into(),
+			Value::Duration(..) => "duration".into(),
+			Value::Regex(..) => "regex".into(),
 			Value::Null => "null".into(),
 			Value::Function { .. } | Value::NativeFunction { .. } | Value::NativeMethod { .. } => "function".into(),
 			Value::StructInstance { definition, .. } => match *definition.clone() {
@@ -260,9 +507,112 @@ impl Value {
 				_ => unreachable!(),
 			},
 			Value::Struct { .. } => "struct".into(),
+			Value::Enum { .. } => "enum".into(),
+			Value::Interface { .. } => "interface".into(),
+			Value::EnumVariant { definition, variant, .. } => match *definition {
+				Value::Enum { name, .. } => format!("{}.{}", name, variant),
+				_ => unreachable!(),
+			},
 			Value::List(..) => "list".into(),
+			Value::Map(..) => "map".into(),
+			Value::Iterator(..) => "iterator".into(),
+			Value::Task(..) => "task".into(),
+			Value::Worker(..) => "worker".into(),
+			Value::Range(..) => "range".into(),
 			Value::Constant(v) => v.typestring(),
-			_ => unreachable!(),
 		}
 	}
 }
+
+// Runs `compare` on a pair of containers unless it's the same object or a pair already being compared further up.
+fn visit<T>(l: &Rc<T>, r: &Rc<T>, visiting: &mut Vec<(*const (), *const ())>, compare: impl FnOnce(&mut Vec<(*const (), *const ())>) -> bool) -> bool {
+	let pair = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+
+	if pair.0 == pair.1 || visiting.contains(&pair) {
+		return true;
+	}
+
+	visiting.push(pair);
+	let equal = compare(visiting);
+	visiting.pop();
+
+	equal
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::evaluate;
+
+	fn is_true(source: &str) -> bool {
+		evaluate(source).unwrap().to_bool()
+	}
+
+	#[test]
+	fn it_compares_values_structurally() {
+		assert!(is_true("[1, [2, 3]] == [1, [2, 3]]"));
+		assert!(is_true("1 == 1n"));
+		assert!(is_true("1/2r == 0.5"));
+		assert!(is_true(r#""1" != 1"#));
+		assert!(is_true("null != false"));
+		assert!(is_true(
+			r#"
+			create a = Json.parse!("{}")
+			a.x = 1
+			a.y = 2
+			create b = Json.parse!("{}")
+			b.y = 2
+			b.x = 1
+			a == b
+			"#
+		));
+		assert!(is_true(
+			r#"
+			struct Point { x, y }
+			create a = Point { x: 1, y: 2 }
+			create b = Point { x: 1, y: 2 }
+			create c = Point { x: 1, y: 3 }
+			a == b && a != c
+			"#
+		));
+	}
+
+	#[test]
+	fn it_orders_values() {
+		assert!(is_true(r#""apple" < "banana""#));
+		assert!(is_true("[1, 2] < [1, 3]"));
+		assert!(is_true("[1, 2] < [1, 2, 0]"));
+		assert!(evaluate(r#"[1] < "a""#).is_err());
+	}
+
+	#[test]
+	fn it_compares_values_that_contain_themselves() {
+		assert!(is_true("create a = []\na.push!(a)\ncreate b = []\nb.push!(b)\na == b"));
+		assert!(is_true("create a = [1]\na.push!(a)\ncreate b = [1]\nb.push!(b)\na <= b"));
+		assert!(is_true("create a = [1]\na.push!(a)\ncreate b = [2]\nb.push!(b)\na != b"));
+		assert!(is_true(
+			r#"
+			struct Node { next }
+			create a = Node { next: null }
+			a.next = a
+			create b = Node { next: null }
+			b.next = b
+			a == b
+			"#
+		));
+	}
+
+	#[test]
+	fn it_prints_values_that_contain_themselves() {
+		assert_eq!(evaluate("create a = [1]\na.push!(a)\na").unwrap().to_string(), "[1, [...]]");
+		assert_eq!(evaluate("create a = [1]\na.push!(a)\n\"{}\".format!([a])").unwrap().to_string(), "[[1, [...]]]");
+
+		let source = r#"
+			create m = Json.parse!("{}")
+			m.self = m
+			m.items = [m]
+			"{}".format!(m)
+		"#;
+
+		assert_eq!(evaluate(source).unwrap().to_string(), "{self: {...}, items: [{...}]}");
+	}
+}
@@ -0,0 +1,374 @@
+use std::{
+	cell::RefCell,
+	fmt::{Debug, Formatter, Result as FmtResult},
+	io::{Read, Write},
+	net::{Shutdown, TcpListener},
+	rc::Rc,
+	sync::mpsc::{channel, Receiver, Sender, TryRecvError},
+	time::{Duration, Instant},
+};
+
+use crate::{
+	ast::{ArgumentValued, ArgumentValues, Identifier, Type},
+	coroutine::{Coroutine, Suspension},
+	environment::Value,
+	interpreter::{Interpreter, InterpreterResult},
+};
+
+// Result of native work done on a helper thread, only plain data crosses threads.
+pub type Outcome = Result<Option<String>, String>;
+
+type Request = Result<(String, Sender<String>), String>;
+
+// Single-threaded cooperative scheduler. Async function calls, timers and native I/O are tasks, the loop polls every
+// scheduled task in turn until the one being awaited is done. Blocking I/O runs on helper threads and is only
+// observed here through channels, so Lugli values never leave the interpreter thread.
+#[derive(Clone, Default)]
+pub struct EventLoop {
+	tasks: Vec<Rc<RefCell<Task>>>,
+	// Finished tasks that failed before anything awaited them, reported once the script is done.
+	unhandled: Vec<Rc<RefCell<Task>>>,
+	// Number of async function bodies currently running, `Time.sleep!` only hands control to the loop inside one.
+	depth: usize,
+}
+
+impl Debug for EventLoop {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		write!(f, "EventLoop {{ tasks: {} }}", self.tasks.len())
+	}
+}
+
+pub struct Task {
+	kind: TaskKind,
+	state: TaskState,
+	scheduled: bool,
+	awaited: bool,
+}
+
+enum TaskState {
+	Pending,
+	Done(Value),
+	Failed(String),
+}
+
+enum TaskKind {
+	// `waiting` is the task the coroutine's current `await` is on.
+	Coroutine { coroutine: Coroutine, name: Identifier, returns: Option<Type>, waiting: Option<Rc<RefCell<Task>>> },
+	Timer(Instant),
+	Thread(Receiver<Outcome>),
+	Serve { requests: Receiver<Request>, handler: Value, remaining: usize },
+	Ready,
+}
+
+impl Task {
+	fn new(kind: TaskKind) -> Self {
+		Self { kind, state: TaskState::Pending, scheduled: false, awaited: false }
+	}
+
+	fn shared(self) -> Value {
+		Value::Task(Rc::new(RefCell::new(self)))
+	}
+
+	fn is_pending(&self) -> bool {
+		matches!(self.state, TaskState::Pending)
+	}
+
+	// Timers, threads and servers finish on their own, coroutines only move when what they await does.
+	fn is_io(&self) -> bool {
+		self.is_pending() && matches!(self.kind, TaskKind::Timer(..) | TaskKind::Thread(..) | TaskKind::Serve { .. })
+	}
+}
+
+pub fn coroutine(coroutine: Coroutine, name: Identifier, returns: Option<Type>) -> Value {
+	Task::new(TaskKind::Coroutine { coroutine, name, returns, waiting: None }).shared()
+}
+
+pub fn timer(milliseconds: f64) -> Value {
+	Task::new(TaskKind::Timer(Instant::now() + Duration::from_millis(milliseconds.max(0.0) as u64))).shared()
+}
+
+// Starts `work` right away on a helper thread, the task completes with the string it returns.
+pub fn thread(work: impl FnOnce() -> Outcome + Send + 'static) -> Value {
+	let (sender, receiver) = channel();
+
+	std::thread::spawn(move || sender.send(work()));
+
+	Task::new(TaskKind::Thread(receiver)).shared()
+}
+
+// Answers `connections` requests on `listener`, each is read until the client stops writing and answered with what
+// `handler` returns for it.
+pub fn serve(listener: TcpListener, handler: Value, connections: usize) -> Value {
+	let (sender, receiver) = channel::<Request>();
+
+	std::thread::spawn(move || {
+		for _ in 0..connections {
+			let mut stream = match listener.accept() {
+				Ok((stream, _)) => stream,
+				Err(e) => return sender.send(Err(format!("Cannot accept connection: {}.", e))),
+			};
+
+			let mut request = String::new();
+
+			if let Err(e) = stream.read_to_string(&mut request) {
+				return sender.send(Err(format!("Cannot read request: {}.", e)));
+			}
+
+			let (reply_sender, reply_receiver) = channel();
+
+			sender.send(Ok((request, reply_sender))).ok();
+
+			if let Ok(reply) = reply_receiver.recv() {
+				stream.write_all(reply.as_bytes()).ok();
+			}
+
+			stream.shutdown(Shutdown::Both).ok();
+		}
+
+		Ok(())
+	});
+
+	Task::new(TaskKind::Serve { requests: receiver, handler, remaining: connections }).shared()
+}
+
+pub fn in_task(interpreter: &Interpreter) -> bool {
+	interpreter.event_loop.depth > 0
+}
+
+pub fn schedule(interpreter: &mut Interpreter, task: &Rc<RefCell<Task>>) {
+	let mut borrowed = task.borrow_mut();
+
+	if !borrowed.scheduled {
+		borrowed.scheduled = true;
+		interpreter.event_loop.tasks.push(Rc::clone(task));
+	}
+}
+
+// A statement that drops a timer nobody scheduled, like a bare `Time.sleep!()` inside an async function, still sleeps:
+// it blocks until the deadline like it does outside of one.
+pub fn discard(task: &Rc<RefCell<Task>>) {
+	let mut task = match task.try_borrow_mut() {
+		Ok(task) => task,
+		Err(..) => return,
+	};
+
+	if let (TaskKind::Timer(deadline), false) = (&task.kind, task.scheduled) {
+		std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+		task.state = TaskState::Done(Value::Null);
+	}
+}
+
+// Runs the loop until `task` finishes. Used by `await` outside of a suspendable statement, which includes the top
+// level of a script.
+pub fn block_on(interpreter: &mut Interpreter, task: &Rc<RefCell<Task>>) -> Result<Value, InterpreterResult> {
+	schedule(interpreter, task);
+	task.borrow_mut().awaited = true;
+
+	loop {
+		if let Ok(task) = task.try_borrow() {
+			match &task.state {
+				TaskState::Pending => (),
+				TaskState::Done(value) => return Ok(value.clone()),
+				TaskState::Failed(message) => return Err(InterpreterResult::Error(message.clone())),
+			}
+		}
+
+		turn(interpreter)?;
+	}
+}
+
+// Drains every scheduled task once the script is done, failures nobody awaited are reported here.
+pub fn run_until_idle(interpreter: &mut Interpreter) -> Result<(), InterpreterResult> {
+	while interpreter.event_loop.tasks.iter().any(|task| task.borrow().is_pending()) {
+		turn(interpreter)?;
+	}
+
+	interpreter.event_loop.tasks.clear();
+
+	for task in std::mem::take(&mut interpreter.event_loop.unhandled) {
+		let task = task.borrow();
+
+		if let TaskState::Failed(message) = &task.state {
+			if !task.awaited {
+				return Err(InterpreterResult::Error(message.clone()));
+			}
+		}
+	}
+
+	Ok(())
+}
+
+// Polls every scheduled task once. Tasks already running further down the stack are skipped, when nothing moved and
+// nothing is left to wait for the awaited tasks can never finish.
+fn turn(interpreter: &mut Interpreter) -> Result<(), InterpreterResult> {
+	let mut progress = false;
+
+	for task in interpreter.event_loop.tasks.clone() {
+		if let Ok(mut task) = task.try_borrow_mut() {
+			if task.is_pending() {
+				progress |= poll(&mut task, interpreter);
+			}
+		}
+	}
+
+	// Finished tasks leave the loop whether or not they were awaited, whoever holds them still sees their result.
+	let mut unhandled = Vec::new();
+
+	interpreter.event_loop.tasks.retain(|task| match task.try_borrow() {
+		Ok(finished) if !finished.is_pending() => {
+			if matches!(finished.state, TaskState::Failed(..)) && !finished.awaited {
+				unhandled.push(Rc::clone(task));
+			}
+
+			false
+		}
+		_ => true,
+	});
+
+	interpreter.event_loop.unhandled.extend(unhandled);
+
+	if !progress {
+		if !interpreter.event_loop.tasks.iter().any(|task| task.try_borrow().is_ok_and(|task| task.is_io())) {
+			return Err(InterpreterResult::Error("Deadlock: the awaited tasks are waiting on each other.".to_string()));
+		}
+
+		std::thread::sleep(Duration::from_millis(1));
+	}
+
+	Ok(())
+}
+
+fn poll(task: &mut Task, interpreter: &mut Interpreter) -> bool {
+	let state = match &mut task.kind {
+		TaskKind::Ready => return false,
+		TaskKind::Timer(deadline) if Instant::now() < *deadline => return false,
+		TaskKind::Timer(..) => TaskState::Done(Value::Null),
+		TaskKind::Thread(receiver) => match receiver.try_recv() {
+			Ok(Ok(output)) => TaskState::Done(output.map_or(Value::Null, Value::String)),
+			Ok(Err(message)) => TaskState::Failed(message),
+			Err(TryRecvError::Empty) => return false,
+			Err(TryRecvError::Disconnected) => TaskState::Failed("Native task stopped without a result.".to_string()),
+		},
+		TaskKind::Serve { requests, handler, remaining } => match requests.try_recv() {
+			Ok(Ok((request, reply))) => match answer(interpreter, handler.clone(), request) {
+				Ok(response) => {
+					reply.send(response).ok();
+					*remaining -= 1;
+
+					if *remaining > 0 {
+						return true;
+					}
+
+					TaskState::Done(Value::Null)
+				}
+				Err(e) => TaskState::Failed(e.to_string()),
+			},
+			Ok(Err(message)) => TaskState::Failed(message),
+			Err(TryRecvError::Empty) => return false,
+			Err(TryRecvError::Disconnected) => TaskState::Failed("Server stopped before answering every connection.".to_string()),
+		},
+		TaskKind::Coroutine { coroutine, name, returns, waiting } => {
+			// A failed task is raised at the `await`, where a surrounding `try` can catch it.
+			let sent = match waiting.as_ref().map(|waiting| waiting.try_borrow()) {
+				None => Ok(Value::Null),
+				Some(Err(..)) => return false,
+				Some(Ok(awaited)) => match &awaited.state {
+					TaskState::Pending => return false,
+					TaskState::Done(value) => Ok(value.clone()),
+					TaskState::Failed(message) => Err(InterpreterResult::Error(message.clone())),
+				},
+			};
+
+			*waiting = None;
+
+			interpreter.event_loop.depth += 1;
+			let suspension = coroutine.resume(interpreter, sent);
+			interpreter.event_loop.depth -= 1;
+
+			match suspension {
+				Ok(Suspension::Await(Value::Task(awaited))) => {
+					schedule(interpreter, &awaited);
+					awaited.borrow_mut().awaited = true;
+					*waiting = Some(awaited);
+					return true;
+				}
+				// Awaiting a plain value hands it straight back on the next poll.
+				Ok(Suspension::Await(value)) => {
+					*waiting = Some(Rc::new(RefCell::new(Task { kind: TaskKind::Ready, state: TaskState::Done(value), scheduled: false, awaited: true })));
+					return true;
+				}
+				Ok(Suspension::Yield(..)) => TaskState::Failed("Cannot yield inside an async function.".to_string()),
+				Ok(Suspension::Return(value)) => match interpreter.check_type(&value, returns, || format!("return value of {}()", name)) {
+					Ok(_) => TaskState::Done(value),
+					Err(e) => TaskState::Failed(e.to_string()),
+				},
+				Err(e) => TaskState::Failed(e.to_string()),
+			}
+		}
+	};
+
+	task.state = state;
+
+	true
+}
+
+fn answer(interpreter: &mut Interpreter, handler: Value, request: String) -> Result<String, InterpreterResult> {
+	let mut arguments = ArgumentValues::new();
+	arguments.push(ArgumentValued::new(None, Value::String(request)));
+
+	let response = match interpreter.call(handler, arguments)? {
+		Value::Task(task) => block_on(interpreter, &task)?,
+		response => response,
+	};
+
+	interpreter.stringify(response)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Instant;
+
+	use crate::interpreter::{caught, evaluate, evaluate_in, register_global_functions, register_global_structs, Interpreter};
+
+	#[test]
+	fn it_drops_tasks_once_they_are_done() {
+		let mut interpreter = Interpreter::new([].iter(), std::env::temp_dir().join("test.lg"));
+
+		register_global_functions(&mut interpreter);
+		register_global_structs(&mut interpreter);
+
+		let source = "async fn work(n) {\n\treturn n * 2\n}\nfor i in 0..100 {\n\tspawn work(i)\n}\ncreate last = spawn work(100)\nawait last";
+
+		assert_eq!(evaluate_in(&mut interpreter, source).unwrap().to_number(), Some(200.0));
+		assert!(interpreter.event_loop.tasks.is_empty());
+	}
+
+	#[test]
+	fn it_reports_failures_nobody_awaited() {
+		let error = evaluate("async fn fail() {\n\treturn [1][5]\n}\nspawn fail()\nawait Time.sleep!(5)").unwrap_err();
+
+		assert_eq!(error.to_string(), "Undefined index: 5.");
+	}
+
+	#[test]
+	fn it_sleeps_on_a_timer_that_is_not_awaited() {
+		let started = Instant::now();
+
+		evaluate("async fn nap() {\n\tTime.sleep!(50)\n\treturn 1\n}\nawait nap()").unwrap();
+
+		assert!(started.elapsed().as_millis() >= 50);
+	}
+
+	#[test]
+	fn it_runs_subprocesses_as_tasks() {
+		assert_eq!(evaluate("await Async.run!(\"echo\", [\"from\", 1])").unwrap().to_string(), "from 1\n");
+		assert_eq!(caught("Async.run!(\"echo\", \"hi\")"), "run! expects a list of arguments, got string.");
+	}
+
+	#[test]
+	fn it_returns_tasks_from_async_closures_and_methods() {
+		assert_eq!(evaluate("create double = async fn(n) {\n\treturn n * 2\n}\ncreate task = double(2)\ntype?(task)").unwrap().to_string(), "task");
+		assert_eq!(evaluate("create double = async fn(n) -> number {\n\treturn n * 2\n}\nawait double(2)").unwrap().to_string(), "4");
+		assert_eq!(evaluate("struct Job {\n\tn,\n\tasync fn run(this) {\n\t\treturn this.n + 1\n\t}\n}\ncreate job = Job { n: 1 }\nawait job.run()").unwrap().to_string(), "2");
+	}
+}
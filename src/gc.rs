@@ -0,0 +1,267 @@
+use hashbrown::HashMap;
+use std::{
+	cell::RefCell,
+	rc::{Rc, Weak},
+};
+
+use crate::{
+	environment::{Environment, Value},
+	map::Map,
+};
+
+// Cycle collector for the values reference counting can't free on its own: a list or map that contains itself, struct
+// instances pointing at each other, or a closure stored on the instance it captured. Lists, maps and instances are
+// registered when they are allocated. A collection works out, from the strong counts, which of them are only
+// referenced by each other, and empties those so their reference counts drop to zero.
+thread_local! {
+	static HEAP: RefCell<Heap> = RefCell::new(Heap::default());
+}
+
+// Allocations between automatic collections, raised when a collection finds most objects still alive.
+const THRESHOLD: usize = 10_000;
+
+#[derive(Default)]
+struct Heap {
+	lists: Vec<Weak<RefCell<Vec<Value>>>>,
+	maps: Vec<Weak<RefCell<Map>>>,
+	instances: Vec<Weak<RefCell<Environment>>>,
+	allocations: usize,
+	threshold: usize,
+	collections: usize,
+	collected: usize,
+}
+
+pub struct Stats {
+	pub lists: usize,
+	pub maps: usize,
+	pub instances: usize,
+	pub collections: usize,
+	pub collected: usize,
+}
+
+pub fn list(items: Vec<Value>) -> Rc<RefCell<Vec<Value>>> {
+	let list = Rc::new(RefCell::new(items));
+
+	track(|heap| heap.lists.push(Rc::downgrade(&list)));
+
+	list
+}
+
+pub fn map(map: Map) -> Rc<RefCell<Map>> {
+	let map = Rc::new(RefCell::new(map));
+
+	track(|heap| heap.maps.push(Rc::downgrade(&map)));
+
+	map
+}
+
+pub fn instance(environment: Environment) -> Rc<RefCell<Environment>> {
+	let instance = Rc::new(RefCell::new(environment));
+
+	track(|heap| heap.instances.push(Rc::downgrade(&instance)));
+
+	instance
+}
+
+fn track(register: impl FnOnce(&mut Heap)) {
+	let due = HEAP.with(|heap| {
+		let mut heap = heap.borrow_mut();
+
+		register(&mut heap);
+		heap.allocations += 1;
+
+		heap.allocations >= heap.threshold.max(THRESHOLD)
+	});
+
+	if due {
+		collect();
+	}
+}
+
+pub fn stats() -> Stats {
+	HEAP.with(|heap| {
+		let heap = heap.borrow();
+
+		Stats {
+			lists: heap.lists.iter().filter(|list| list.strong_count() > 0).count(),
+			maps: heap.maps.iter().filter(|map| map.strong_count() > 0).count(),
+			instances: heap.instances.iter().filter(|instance| instance.strong_count() > 0).count(),
+			collections: heap.collections,
+			collected: heap.collected,
+		}
+	})
+}
+
+enum Node {
+	List(Rc<RefCell<Vec<Value>>>),
+	Map(Rc<RefCell<Map>>),
+	Instance(Rc<RefCell<Environment>>),
+}
+
+impl Node {
+	fn address(&self) -> usize {
+		match self {
+			Node::List(list) => Rc::as_ptr(list) as *const () as usize,
+			Node::Map(map) => Rc::as_ptr(map) as *const () as usize,
+			Node::Instance(instance) => Rc::as_ptr(instance) as *const () as usize,
+		}
+	}
+
+	fn strong_count(&self) -> usize {
+		match self {
+			Node::List(list) => Rc::strong_count(list),
+			Node::Map(map) => Rc::strong_count(map),
+			Node::Instance(instance) => Rc::strong_count(instance),
+		}
+	}
+
+	// Addresses of the tracked objects this one holds a strong reference to, `None` while it is borrowed.
+	fn edges(&self) -> Option<Vec<usize>> {
+		let mut edges = Vec::new();
+
+		match self {
+			Node::List(list) => list.try_borrow().ok()?.iter().for_each(|value| references(value, &mut edges)),
+			Node::Map(map) => map.try_borrow().ok()?.values().for_each(|value| references(value, &mut edges)),
+			Node::Instance(instance) => instance.try_borrow().ok()?.values().for_each(|value| references(value, &mut edges)),
+		}
+
+		Some(edges)
+	}
+
+	fn clear(&self) -> Vec<Value> {
+		match self {
+			Node::List(list) => std::mem::take(&mut *list.borrow_mut()),
+			Node::Map(map) => std::mem::take(&mut *map.borrow_mut()).into_values(),
+			Node::Instance(instance) => instance.borrow_mut().clear(),
+		}
+	}
+}
+
+// Follows values held by value (closure environments, constants, enum payloads) down to the tracked objects they
+// reference. Untracked shared objects, like struct method tables, count as references from outside.
+fn references(value: &Value, edges: &mut Vec<usize>) {
+	match value {
+		Value::List(list) => edges.push(Rc::as_ptr(list) as *const () as usize),
+		Value::Map(map) => edges.push(Rc::as_ptr(map) as *const () as usize),
+		Value::StructInstance { environment, .. } => edges.push(Rc::as_ptr(environment) as *const () as usize),
+		Value::Function { environment: Some(environment), .. } => environment.values().for_each(|value| references(value, edges)),
+		Value::EnumVariant { values, .. } => values.iter().for_each(|value| references(value, edges)),
+		Value::Constant(value) => references(value, edges),
+		_ => (),
+	}
+}
+
+// Frees every tracked object that is only reachable from other tracked objects, returns how many were freed.
+pub fn collect() -> usize {
+	let nodes = HEAP.with(|heap| {
+		let mut heap = heap.borrow_mut();
+
+		heap.lists.retain(|list| list.strong_count() > 0);
+		heap.maps.retain(|map| map.strong_count() > 0);
+		heap.instances.retain(|instance| instance.strong_count() > 0);
+		heap.allocations = 0;
+
+		let lists = heap.lists.iter().filter_map(Weak::upgrade).map(Node::List);
+		let maps = heap.maps.iter().filter_map(Weak::upgrade).map(Node::Map);
+
+		lists.chain(maps).chain(heap.instances.iter().filter_map(Weak::upgrade).map(Node::Instance)).collect::<Vec<Node>>()
+	});
+
+	let index = nodes.iter().enumerate().map(|(i, node)| (node.address(), i)).collect::<HashMap<usize, usize>>();
+
+	// A borrowed object can't be inspected, so nothing is collected until it is released.
+	let edges = match nodes.iter().map(Node::edges).collect::<Option<Vec<Vec<usize>>>>() {
+		Some(edges) => edges,
+		None => return 0,
+	};
+
+	// References from outside the tracked objects: the strong count minus the one held by `nodes` and the ones
+	// coming from other tracked objects.
+	let mut external = nodes.iter().map(|node| node.strong_count() as isize - 1).collect::<Vec<isize>>();
+
+	for target in edges.iter().flatten() {
+		if let Some(&i) = index.get(target) {
+			external[i] -= 1;
+		}
+	}
+
+	let mut reachable = external.iter().map(|count| *count > 0).collect::<Vec<bool>>();
+	let mut pending = (0..nodes.len()).filter(|&i| reachable[i]).collect::<Vec<usize>>();
+
+	while let Some(i) = pending.pop() {
+		for target in &edges[i] {
+			if let Some(&j) = index.get(target) {
+				if !reachable[j] {
+					reachable[j] = true;
+					pending.push(j);
+				}
+			}
+		}
+	}
+
+	let garbage = nodes.iter().zip(reachable.iter()).filter(|(_, reachable)| !**reachable).map(|(node, _)| node).collect::<Vec<&Node>>();
+	let collected = garbage.len();
+
+	// Contents are dropped only after every cycle is broken, so nothing is freed while it's still being emptied.
+	let contents = garbage.into_iter().map(Node::clear).collect::<Vec<Vec<Value>>>();
+
+	drop(nodes);
+	drop(contents);
+
+	HEAP.with(|heap| {
+		let mut heap = heap.borrow_mut();
+		let live = (heap.lists.len() + heap.maps.len() + heap.instances.len()).saturating_sub(collected);
+
+		heap.collections += 1;
+		heap.collected += collected;
+		heap.threshold = live * 2;
+	});
+
+	collected
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::evaluate;
+
+	#[test]
+	fn it_collects_cycles() {
+		let source = r#"
+			struct Node { value, next = null }
+
+			fn leak() {
+				create a = Node { value: 1 }
+				create b = Node { value: 2, next: a }
+				a.next = b
+
+				create items = [1]
+				items.push!(items)
+
+				create map = Json.parse!("{}")
+				map.self = map
+				map.items = [map]
+			}
+
+			leak()
+			Runtime.gc!()
+		"#;
+
+		// Two instances, the list that contains itself, the map and the list inside it.
+		assert_eq!(evaluate(source).unwrap().to_number(), Some(5.0));
+	}
+
+	#[test]
+	fn it_keeps_cycles_that_are_still_referenced() {
+		let source = r#"
+			create map = Json.parse!("{}")
+			map.self = map
+			Runtime.gc!()
+			create memory = Runtime.memory?()
+			create inner = map.self.self
+			create result = [memory.maps, inner == map]
+			result
+		"#;
+
+		assert_eq!(evaluate(source).unwrap().to_string(), "[1, true]");
+	}
+}
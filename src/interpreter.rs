@@ -2,6 +2,7 @@ use colored::*;
 use hashbrown::HashMap;
 use std::{
 	cell::{Ref, RefCell, RefMut},
+	cmp::Ordering,
 	fs::canonicalize,
 	path::PathBuf,
 	rc::Rc,
@@ -12,6 +13,13 @@ use thiserror::Error;
 use crate::{
 	ast::*,
 	environment::{self, *},
+	event_loop::{self, EventLoop},
+	coroutine::Coroutine,
+	iterator::ValueIterator,
+	numeric,
+	random::Random,
+	range::Range,
+	worker::WorkerChannels,
 };
 
 pub fn register_global_functions(interpreter: &mut Interpreter) {
@@ -26,13 +34,54 @@ pub fn register_global_structs(interpreter: &mut Interpreter) {
 	}
 }
 
-pub fn interpret(ast: Program, path: PathBuf) -> Result<(), InterpreterResult> {
+// Stack of the threads scripts and workers run on. Debug builds use large frames for `run_expression`, the default
+// stacks only fit a few dozen nested calls.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+pub fn interpret(ast: Program, path: PathBuf, args: Vec<String>) -> Result<(), InterpreterResult> {
 	let mut interpreter = Interpreter::new(ast.iter(), canonicalize(path).unwrap());
+	interpreter.args = args;
+
+	register_global_functions(&mut interpreter);
+	register_global_structs(&mut interpreter);
+
+	interpreter.run()?;
+
+	event_loop::run_until_idle(&mut interpreter)
+}
+
+// Runs a script with the global functions and structs registered and returns the value of its last statement, once
+// the event loop is idle. Used by the tests of every module.
+#[cfg(test)]
+pub fn evaluate(source: &str) -> Result<Value, InterpreterResult> {
+	let mut interpreter = Interpreter::new([].iter(), std::env::temp_dir().join("test.lg"));
 
 	register_global_functions(&mut interpreter);
 	register_global_structs(&mut interpreter);
 
-	interpreter.run()
+	let value = evaluate_in(&mut interpreter, source)?;
+
+	event_loop::run_until_idle(&mut interpreter)?;
+
+	Ok(value)
+}
+
+// Runs `source` inside a `try` and returns the message its `catch` receives, for tests of catchable errors.
+#[cfg(test)]
+pub fn caught(source: &str) -> String {
+	let body = source.lines().map(|line| format!("\t{}", line)).collect::<Vec<String>>().join("\n");
+
+	match evaluate(&format!("create caught = null\ntry {{\n{}\n}} catch error {{\n\tcaught = error\n}}\ncaught", body)) {
+		Ok(Value::Null) => panic!("No error was raised by:\n{}", source),
+		Ok(error) => error.to_string(),
+		Err(error) => panic!("Uncaught error {} raised by:\n{}", error, source),
+	}
+}
+
+// Like `evaluate` on an interpreter set up by the caller, the event loop is left as the script leaves it.
+#[cfg(test)]
+pub fn evaluate_in(interpreter: &mut Interpreter, source: &str) -> Result<Value, InterpreterResult> {
+	interpreter.run_block_value(crate::parser::parse(crate::token::generate(source)).unwrap())
 }
 
 #[derive(Error, Debug)]
@@ -53,7 +102,10 @@ pub enum InterpreterResult {
 	UndefinedVariable(String),
 
 	#[error("Undefined index: {0}.")]
-	UndefinedIndex(usize),
+	UndefinedIndex(i64),
+
+	#[error("Undefined key: {0}.")]
+	UndefinedKey(String),
 
 	#[error("Undefined field: {0}.{1}")]
 	UndefinedField(String, String),
@@ -75,10 +127,33 @@ pub enum InterpreterResult {
 
 	#[error("Cannot assign value to constant.")]
 	CannotAssignValueToConstant,
+
+	#[error("TypeError: {0} expects {1}, got {2}.")]
+	TypeMismatch(String, String, String),
+
+	#[error("Missing fields for struct {0}: {1}.")]
+	MissingFields(String, String),
+
+	#[error("Struct {0} does not implement {2} required by interface {1}.")]
+	MissingInterfaceMethod(String, String, String),
+
+	#[error("Cannot destructure value {0}, it does not fit the pattern.")]
+	CannotDestructure(String),
+
+	#[error("No match arm matched value {0}.")]
+	NoMatchingArm(String),
+
+	#[error("TypeError: unsupported operand types for {0}: {1} and {2}.")]
+	TypeError(String, String, String),
 }
 
 impl InterpreterResult {
-	pub fn print(self) {
+	// Errors raised by the program, as opposed to `return`, `break` and `continue` unwinding through it.
+	pub fn is_error(&self) -> bool {
+		!matches!(self, InterpreterResult::Return(..) | InterpreterResult::Break | InterpreterResult::Continue)
+	}
+
+	pub fn print(self) -> ! {
 		eprintln!("{}", format!("{}", self).red().bold());
 		std::process::exit(1);
 	}
@@ -89,49 +164,97 @@ pub struct Interpreter<'i> {
 	ast: Iter<'i, Statement>,
 	environment: Rc<RefCell<Environment>>,
 	pub globals: HashMap<String, Value>,
+	pub event_loop: EventLoop,
+	// Set on interpreters running inside a `Worker`, connects them to the interpreter that started them.
+	pub worker: Option<Rc<WorkerChannels>>,
+	pub random: Random,
+	// Command line arguments given after the script path.
+	pub args: Vec<String>,
+	// Variables set with `Env.set!`, read before the process environment and passed to the programs the script runs.
+	pub env: HashMap<String, String>,
 	path: PathBuf,
 }
 
 #[allow(unreachable_patterns)]
 impl<'i> Interpreter<'i> {
 	pub fn new(ast: Iter<'i, Statement>, path: PathBuf) -> Self {
-		Self { ast, environment: Rc::new(RefCell::new(Environment::new())), globals: HashMap::new(), path }
+		Self { ast, environment: Rc::new(RefCell::new(Environment::new())), globals: HashMap::new(), event_loop: EventLoop::default(), worker: None, random: Random::from_time(), args: Vec::new(), env: HashMap::new(), path }
 	}
 
-	fn run_statement(&mut self, statement: Statement) -> Result<(), InterpreterResult> {
+	pub fn run_statement(&mut self, statement: Statement) -> Result<(), InterpreterResult> {
 		Ok(match statement {
-			Statement::CreateDeclaration { name, initial } => {
-				if initial.is_none() {
-					self.env_mut().set(name, Value::Null)
-				} else {
-					let initial = initial.unwrap();
-					let value = self.run_expression(initial)?;
+			Statement::CreateDeclaration { name, kind, initial } => {
+				let value = match initial {
+					Some(initial) => self.run_expression(initial)?,
+					None => Value::Null,
+				};
 
-					self.env_mut().set(name, value)
-				}
+				self.check_type(&value, &kind, || format!("create {}", name))?;
+				self.env_mut().set(name, value)
 			}
-			Statement::ConstDeclaration { name, initial } => {
-				let value = Value::Constant(Box::new(self.run_expression(initial)?));
+			Statement::ConstDeclaration { name, kind, initial } => {
+				let value = self.run_expression(initial)?;
 
-				self.env_mut().set(name, value)
+				self.check_type(&value, &kind, || format!("const {}", name))?;
+				self.env_mut().set(name, Value::Constant(Box::new(value)))
 			}
-			Statement::FunctionDeclaration { name, params, body } => {
-				self.globals.insert(name.clone(), Value::Function { name, params, body, environment: None, context: None });
+			Statement::DestructuringDeclaration { pattern, initial, constant } => {
+				let value = self.run_expression(initial)?;
+				let mut bindings: Vec<(Identifier, Value)> = Vec::new();
+
+				if !self.match_pattern(&pattern, &value, &mut bindings)? {
+					return Err(InterpreterResult::CannotDestructure(format!("{:?}", value)));
+				}
+
+				for (name, value) in bindings {
+					self.env_mut().set(name, if constant { Value::Constant(Box::new(value)) } else { value });
+				}
 			}
-			Statement::StructDeclaration { name, fields } => {
-				let methods: Rc<RefCell<hashbrown::HashMap<String, environment::Value>>> = Rc::new(RefCell::new(hashbrown::HashMap::new()));
-				let mut fields_filtred: Vec<Parameter> = Vec::new();
+			Statement::FunctionDeclaration { name, params, body, return_type, is_async } => {
+				self.globals.insert(name.clone(), Value::Function { name, params, body, return_type, is_async, environment: None, context: None });
+			}
+			Statement::StructDeclaration { name, extends, fields } => {
+				let mut parent: Option<Value> = None;
+				let mut implements: Vec<Value> = Vec::new();
+
+				for supertype in extends {
+					match self.globals.get(&supertype).cloned() {
+						Some(definition @ Value::Struct { .. }) if parent.is_none() => parent = Some(definition),
+						Some(Value::Struct { .. }) => return Err(InterpreterResult::Error(format!("Struct {} can only extend one struct.", name))),
+						Some(interface @ Value::Interface { .. }) => implements.push(interface),
+						_ => return Err(InterpreterResult::UndefinedVariable(supertype)),
+					}
+				}
+
+				// Inherited fields and methods come first, the struct's own declarations override them.
+				let (inherited_fields, inherited_methods, mut interfaces) = match &parent {
+					Some(Value::Struct { fields, methods, interfaces, .. }) => (fields.clone(), methods.borrow().clone(), interfaces.clone()),
+					_ => (Vec::new(), hashbrown::HashMap::new(), Vec::new()),
+				};
+
+				let methods: Rc<RefCell<hashbrown::HashMap<String, environment::Value>>> = Rc::new(RefCell::new(inherited_methods));
+				let mut fields_filtred: Vec<Parameter> = inherited_fields.into_iter().filter(|f| !fields.iter().any(|own| own.name == f.name)).collect();
+
+				// Methods see the parent struct as `super`.
+				let mut method_environment = self.environment.borrow().clone();
+
+				if let Some(parent) = &parent {
+					method_environment.set("super", parent.clone());
+				}
+
 				for field in fields.clone() {
 					match field.clone().initial {
 						Some(e) => match e {
-							Expression::Closure(params, body) => {
+							Expression::Closure(params, body, return_type, is_async) => {
 								methods.borrow_mut().insert(
 									field.name.clone(),
 									Value::Function {
 										name: field.name.clone(),
 										params,
 										body,
-										environment: Some(self.environment.borrow().clone()),
+										return_type,
+										is_async,
+										environment: Some(method_environment.clone()),
 										context: None,
 									},
 								);
@@ -144,31 +267,66 @@ impl<'i> Interpreter<'i> {
 					}
 				}
 
-				self.globals.insert(name.clone(), Value::Struct { name, fields: fields_filtred, methods, propreties: None });
-			}
-			Statement::For { iterable, value, index, then } => {
-				let iterable = self.run_expression(iterable)?;
+				for interface in implements {
+					let (interface_name, required) = match interface {
+						Value::Interface { name, methods } => (name, methods),
+						_ => unreachable!(),
+					};
 
-				let items = match iterable {
-					Value::List(items) => items,
-					_ => return Err(InterpreterResult::InvalidIterable(iterable.typestring())),
-				};
+					for (method, params) in required {
+						let implemented = match methods.borrow().get(&method) {
+							Some(Value::Function { params: implementation, .. }) => implementation.len() == params.len(),
+							_ => false,
+						};
+
+						if !implemented {
+							let signature = format!("{}({})", method, params.iter().map(|p| p.name.clone()).collect::<Vec<String>>().join(", "));
+
+							return Err(InterpreterResult::MissingInterfaceMethod(name, interface_name, signature));
+						}
+					}
+
+					interfaces.push(interface_name);
+				}
+
+				self.globals.insert(
+					name.clone(),
+					Value::Struct { name, fields: fields_filtred, methods, parent: parent.map(Box::new), interfaces },
+				);
+			}
+			Statement::InterfaceDeclaration { name, methods } => {
+				self.globals.insert(name.clone(), Value::Interface { name, methods });
+			}
+			Statement::EnumDeclaration { name, variants, methods: declarations } => {
+				let methods: Rc<RefCell<hashbrown::HashMap<String, environment::Value>>> = Rc::new(RefCell::new(hashbrown::HashMap::new()));
 
-				// If there aren't any items in the list, we can leave this execution
-				// cycle early.
-				if items.borrow().is_empty() {
-					return Ok(());
+				for method in declarations {
+					if let Some(Expression::Closure(params, body, return_type, is_async)) = method.initial {
+						methods.borrow_mut().insert(
+							method.name.clone(),
+							Value::Function { name: method.name, params, body, return_type, is_async, environment: Some(self.environment.borrow().clone()), context: None },
+						);
+					}
 				}
 
+				self.globals.insert(name.clone(), Value::Enum { name, variants, methods });
+			}
+			Statement::For { iterable, value, index, then } => {
+				let iterable = self.run_expression(iterable)?;
+				let mut items = self.iterate(iterable)?;
+
 				let set_index: bool = index.is_some();
+				let mut i = 0;
 
-				'outer_for: for (i, item) in items.borrow().iter().enumerate() {
-					self.env_mut().set(value.clone(), item.clone());
+				'outer_for: while let Some(item) = items.next(self)? {
+					self.env_mut().set(value.clone(), item);
 
 					if set_index {
 						self.env_mut().set(index.clone().unwrap(), Value::Number(i as f64));
 					}
 
+					i += 1;
+
 					for statement in then.clone() {
 						match self.run_statement(statement) {
 							Err(InterpreterResult::Break) => break 'outer_for,
@@ -210,6 +368,25 @@ impl<'i> Interpreter<'i> {
 				}
 			},
 
+			Statement::Try { body, name, handler } => {
+				let error = match body.into_iter().try_for_each(|statement| self.run_statement(statement)) {
+					Err(error) if error.is_error() => error,
+					result => return result,
+				};
+
+				if let Some(name) = &name {
+					self.env_mut().set(name.clone(), Value::String(error.to_string()));
+				}
+
+				for statement in handler {
+					self.run_statement(statement)?;
+				}
+
+				if let Some(name) = name {
+					self.env_mut().drop(name);
+				}
+			}
+
 			Statement::If { condition, others_conditions, otherwise } => {
 				let expression = self.run_expression(condition.expression)?;
 				let mut satisfied = false;
@@ -236,108 +413,597 @@ impl<'i> Interpreter<'i> {
 					}
 				}
 
-				if otherwise.is_some() && !satisfied {
-					for statement in otherwise.unwrap() {
-						self.run_statement(statement)?;
+				if let Some(otherwise) = otherwise {
+					if !satisfied {
+						for statement in otherwise {
+							self.run_statement(statement)?;
+						}
 					}
 				}
 			}
 
 			Statement::Expression { expression } => {
-				self.run_expression(expression)?;
+				if let Value::Task(task) = self.run_expression(expression)? {
+					event_loop::discard(&task);
+				}
 			}
 			Statement::Return { value } => {
 				return Err(InterpreterResult::Return(self.run_expression(value)?));
 			}
+			Statement::Yield { .. } => {
+				return Err(InterpreterResult::Error("Cannot yield outside of a generator function body.".to_string()));
+			}
 			Statement::Break => {
 				return Err(InterpreterResult::Break);
 			}
 			Statement::Continue => {
 				return Err(InterpreterResult::Continue);
 			}
-			_ => todo!("{:?}", statement),
+			statement => return Err(InterpreterResult::Error(format!("Unsupported statement: {:?}.", statement))),
 		})
 	}
 
 	pub fn call(&mut self, callable: Value, arguments: ArgumentValues) -> Result<Value, InterpreterResult> {
 		Ok(match callable {
 			Value::Constant(v) => self.call(*v, arguments)?,
-			Value::NativeFunction { callback, .. } => callback(self, arguments),
+			Value::NativeFunction { callback, .. } => callback(self, arguments)?,
 			Value::NativeMethod { callback, context, .. } => {
 				let context = self.run_expression(context)?;
 
 				callback(self, context, arguments)?
 			}
-			Value::Function { name, mut params, body, environment, context } => {
-				let old_environment = Rc::clone(&self.environment);
+			Value::Function { name, params, body, return_type, is_async, environment, context } => {
+				let this = match context {
+					Some(context) if params.first().is_some_and(Parameter::is_this) => Some(self.run_expression(*context)?),
+					_ => None,
+				};
 
-				let new_environment =
-					if environment.is_some() { Rc::new(RefCell::new(environment.unwrap())) } else { Rc::new(RefCell::new(Environment::new())) };
+				self.call_function(name, params, body, return_type, is_async, environment, this, arguments)?
+			}
+			Value::EnumVariant { definition, variant, .. } => self.construct_variant(*definition, variant, arguments)?,
+			value => return Err(InterpreterResult::TypeMismatch("call".to_string(), "function".to_string(), value.typestring())),
+		})
+	}
 
-				if context.is_some() && params.first() == Some(&Parameter { name: "this".to_string(), initial: None }) {
-					let context = self.run_expression(context.unwrap())?;
-					new_environment.borrow_mut().set("this", context);
-					params = params.iter().filter(|p| p.name != "this").cloned().collect();
-				}
+	// `target += value`, only numbers are updated in place.
+	fn math_assign(&mut self, target: Expression, op: Op, value: Expression) -> Result<Value, InterpreterResult> {
+		let current = self.run_expression(target.clone())?;
+		let value = self.run_expression(value)?;
+
+		if !matches!(current, Value::Number(..) | Value::BigInt(..) | Value::Rational(..)) {
+			return Err(InterpreterResult::TypeError(format!("{}=", op.symbol()), current.typestring(), value.typestring()));
+		}
+
+		let result = numeric::arithmetic(current, op, value)?;
+
+		match target {
+			Expression::Identifier(i) => self.env_mut().set(i, result.clone()),
+			Expression::GetProperty(object, field) => match self.run_expression(*object)?.unwrap_constant() {
+				Value::StructInstance { environment, .. } => environment.borrow_mut().set(field, result.clone()),
+				Value::Map(map) => map.borrow_mut().set(field, result.clone()),
+				object => return Err(InterpreterResult::UndefinedField(object.typestring(), field)),
+			},
+			_ => return Err(InterpreterResult::Error("Invalid assignment target.".to_string())),
+		}
+
+		Ok(result)
+	}
+
+	// `Shape.Circle(2)` builds a payload variant, arguments are matched to the declared fields by position or name.
+	fn construct_variant(&mut self, definition: Value, variant: String, arguments: ArgumentValues) -> Result<Value, InterpreterResult> {
+		let typestring = Value::EnumVariant { definition: Box::new(definition.clone()), variant: variant.clone(), values: vec![] }.typestring();
+
+		let fields = match &definition {
+			Value::Enum { variants, .. } => variants.iter().find(|v| v.name == variant).and_then(|v| v.fields.clone()),
+			_ => unreachable!(),
+		};
+
+		let fields = match fields {
+			Some(fields) => fields,
+			None => return Err(InterpreterResult::Error(format!("Unit variant {} cannot be called.", typestring))),
+		};
+
+		if arguments.len() < fields.len() {
+			return Err(InterpreterResult::TooFewArguments(typestring, arguments.len(), fields.len()));
+		}
+
+		if arguments.len() > fields.len() {
+			return Err(InterpreterResult::Error(format!("Too many arguments to variant {}(), {} passed in, {} expected.", typestring, arguments.len(), fields.len())));
+		}
+
+		let mut positional = arguments.clone().filter(|argument| argument.get_name().is_none());
+		let mut values: Vec<Value> = Vec::new();
+
+		for field in fields.iter() {
+			let named = arguments.clone().find(|argument| argument.get_name().as_ref() == Some(field));
+
+			match named.or_else(|| positional.next()) {
+				Some(argument) => values.push(argument.get_value()),
+				None => return Err(InterpreterResult::TooFewArguments(typestring, values.len(), fields.len())),
+			}
+		}
+
+		Ok(Value::EnumVariant { definition: Box::new(definition), variant, values })
+	}
+
+	// Calls a method of a struct instance with `this` bound to it, `None` when the value has no such method.
+	pub fn call_method(&mut self, instance: Value, method: &str, arguments: Vec<Value>) -> Result<Option<Value>, InterpreterResult> {
+		let function = match &instance {
+			Value::StructInstance { environment, .. } => environment.borrow().get(method),
+			Value::EnumVariant { definition, .. } => match &**definition {
+				Value::Enum { methods, .. } => methods.borrow().get(method).cloned(),
+				_ => None,
+			},
+			Value::Constant(v) => return self.call_method(*v.clone(), method, arguments),
+			_ => None,
+		};
+
+		let (name, params, body, return_type, is_async, environment) = match function {
+			Some(Value::Function { name, params, body, return_type, is_async, environment, .. }) => (name, params, body, return_type, is_async, environment),
+			_ => return Ok(None),
+		};
+
+		let mut arguments_value = ArgumentValues::new();
+
+		for argument in arguments {
+			arguments_value.push(ArgumentValued::new(None, argument));
+		}
+
+		Ok(Some(self.call_function(name, params, body, return_type, is_async, environment, Some(instance), arguments_value)?))
+	}
+
+	// Struct instances can overload operators through `__add`, `__sub`, `__mul`, `__div`, `__mod`, `__pow`,
+	// `__eq`, `__ne`, `__lt`, `__gt`, `__le` and `__ge`. Missing comparisons are derived from `__eq` and `__lt`.
+	fn run_overloaded_operator(&mut self, left: &Value, op: &Op, right: &Value) -> Result<Option<Value>, InterpreterResult> {
+		let method = match op {
+			Op::Add => "__add",
+			Op::Subtract => "__sub",
+			Op::Multiply => "__mul",
+			Op::Divide => "__div",
+			Op::Modulo => "__mod",
+			Op::Pow => "__pow",
+			Op::Equals => "__eq",
+			Op::NotEquals => "__ne",
+			Op::LessThan => "__lt",
+			Op::GreaterThan => "__gt",
+			Op::LessThanOrEquals => "__le",
+			Op::GreaterThanOrEquals => "__ge",
+			_ => return Ok(None),
+		};
+
+		if let Some(result) = self.call_method(left.clone(), method, vec![right.clone()])? {
+			return Ok(Some(result));
+		}
+
+		let derived = match op {
+			Op::NotEquals => self.call_method(left.clone(), "__eq", vec![right.clone()])?.map(|v| Value::Bool(!v.to_bool())),
+			Op::GreaterThan => self.call_method(right.clone(), "__lt", vec![left.clone()])?.map(|v| Value::Bool(v.to_bool())),
+			Op::LessThanOrEquals => self.call_method(right.clone(), "__lt", vec![left.clone()])?.map(|v| Value::Bool(!v.to_bool())),
+			Op::GreaterThanOrEquals => self.call_method(left.clone(), "__lt", vec![right.clone()])?.map(|v| Value::Bool(!v.to_bool())),
+			Op::Add => match (left, right) {
+				(Value::String(l), r) if self.has_method(r, "__str") => Some(Value::String(format!("{}{}", l, self.stringify(r.clone())?))),
+				(l, Value::String(r)) if self.has_method(l, "__str") => Some(Value::String(format!("{}{}", self.stringify(l.clone())?, r))),
+				_ => None,
+			},
+			_ => None,
+		};
+
+		Ok(derived)
+	}
+
+	fn has_method(&self, value: &Value, method: &str) -> bool {
+		match value {
+			Value::StructInstance { environment, .. } => matches!(environment.borrow().get(method), Some(Value::Function { .. })),
+			Value::EnumVariant { definition, .. } => matches!(&**definition, Value::Enum { methods, .. } if methods.borrow().contains_key(method)),
+			Value::Constant(v) => self.has_method(v, method),
+			_ => false,
+		}
+	}
 
-				let mut params_to_satisfy = params.clone();
+	// Checks a value against a `match` pattern, collecting the names it binds.
+	fn match_pattern(&mut self, pattern: &Pattern, value: &Value, bindings: &mut Vec<(Identifier, Value)>) -> Result<bool, InterpreterResult> {
+		Ok(match (pattern, value.clone().unwrap_constant()) {
+			(Pattern::Wildcard, _) => true,
+			(Pattern::Binding(name), value) => {
+				bindings.push((name.clone(), value));
+				true
+			}
+			(Pattern::Literal(literal), value) => self.run_expression(literal.clone())?.equals(&value),
+			(Pattern::Range(range), value) => match self.run_expression(range.clone())? {
+				Value::Range(range) => numeric::to_float(&value).is_some_and(|n| range.contains(n)),
+				_ => unreachable!(),
+			},
+			(Pattern::Default(pattern, default), Value::Null) => {
+				let default = self.run_expression(default.clone())?;
+				self.match_pattern(pattern, &default, bindings)?
+			}
+			(Pattern::Default(pattern, _), value) => self.match_pattern(pattern, &value, bindings)?,
+			(Pattern::List { items, rest }, Value::List(values)) => {
+				let values = values.borrow().clone();
+				let required = items.iter().filter(|pattern| !matches!(pattern, Pattern::Default(..))).count();
+
+				if values.len() < required || (rest.is_none() && values.len() > items.len()) {
+					return Ok(false);
+				}
 
-				for argument in arguments.clone() {
-					if argument.get_name().is_some() && params_to_satisfy.iter().any(|param| param.get_name() == argument.get_name().unwrap()) {
-						params_to_satisfy.retain(|param| param.get_name() != argument.get_name().unwrap());
+				// Items missing at the end of the list are null, so only patterns with a default can match them.
+				for (i, pattern) in items.iter().enumerate() {
+					if !self.match_pattern(pattern, values.get(i).unwrap_or(&Value::Null), bindings)? {
+						return Ok(false);
 					}
 				}
 
-				let params_without_value = params_to_satisfy.iter().filter(|param| !param.has_initial()).count();
+				if let Some(Some(name)) = rest {
+					let rest = values.get(items.len()..).unwrap_or_default().to_vec();
+					bindings.push((name.clone(), Value::list(rest)));
+				}
 
-				if params_without_value > arguments.len() {
-					return Err(InterpreterResult::TooFewArguments(name.clone(), arguments.len(), params_without_value));
+				true
+			}
+			(Pattern::Variant { name, variant, fields }, value @ Value::EnumVariant { .. }) => {
+				let values = match value.clone() {
+					Value::EnumVariant { values, .. } => values,
+					_ => unreachable!(),
+				};
+
+				if value.typestring() != format!("{}.{}", name, variant) {
+					return Ok(false);
 				}
 
-				for params_with_initial in params.iter().filter(|param| param.has_initial()) {
-					let initial = self.run_expression(params_with_initial.get_initial().unwrap())?;
-					new_environment.borrow_mut().set(params_with_initial.get_name(), initial);
+				match fields {
+					Some(fields) if fields.len() != values.len() => false,
+					Some(fields) => {
+						for (pattern, value) in fields.iter().zip(values.iter()) {
+							if !self.match_pattern(pattern, value, bindings)? {
+								return Ok(false);
+							}
+						}
+
+						true
+					}
+					None => true,
 				}
+			}
+			(Pattern::Struct { name, fields }, Value::StructInstance { environment, definition }) => {
+				let (definition_name, definition_fields) = match *definition {
+					Value::Struct { name, fields, .. } => (name, fields),
+					_ => unreachable!(),
+				};
 
-				for argument in arguments.clone().filter(|arg| arg.get_name().is_some()) {
-					new_environment.borrow_mut().set(argument.get_name().unwrap(), argument.get_value());
+				if name.as_ref().is_some_and(|name| *name != definition_name) {
+					return Ok(false);
 				}
-				for (param, ArgumentValued { value, .. }) in params_to_satisfy.iter().zip(arguments.filter(|param| param.get_name().is_none())) {
-					new_environment.borrow_mut().set(param.get_name(), value);
+
+				for (field, pattern) in fields {
+					// Fields left out of the struct literal fall back to their declared default.
+					let stored = environment.borrow().get(field.clone());
+
+					let value = match (stored, definition_fields.iter().find(|p| p.name == *field)) {
+						(Some(value), _) => value,
+						(None, Some(declared)) => match declared.get_initial() {
+							Some(initial) => self.run_expression(initial)?,
+							None => Value::Null,
+						},
+						// A field the struct doesn't declare only matches a pattern with a default.
+						(None, None) if matches!(pattern, Pattern::Default(..)) => Value::Null,
+						(None, None) => return Ok(false),
+					};
+
+					if !self.match_pattern(pattern, &value, bindings)? {
+						return Ok(false);
+					}
 				}
 
-				self.environment = new_environment;
+				true
+			}
+			_ => false,
+		})
+	}
 
-				let mut return_value: Option<Value> = None;
+	// Runs the guard and body of a match arm in a child of the current environment, so the names the pattern binds
+	// never replace variables of the enclosing scope. Any other variable the arm assigns is written back to it. `None`
+	// when the guard rejects the arm.
+	fn run_match_arm(&mut self, bindings: Vec<(Identifier, Value)>, guard: Option<Expression>, body: Block) -> Result<Option<Value>, InterpreterResult> {
+		let mut scope = self.env().clone();
 
-				for statement in body {
-					match self.run_statement(statement) {
-						Err(InterpreterResult::Return(value)) => {
-							return_value = Some(value);
-							break;
+		for (name, value) in bindings.iter() {
+			scope.set(name.clone(), value.clone());
+		}
+
+		let scope = Rc::new(RefCell::new(scope));
+
+		let result = self.with_environment(Rc::clone(&scope), |interpreter| {
+			let satisfied = match guard {
+				Some(guard) => interpreter.run_expression(guard)?.to_bool(),
+				None => true,
+			};
+
+			if satisfied {
+				interpreter.run_block_value(body).map(Some)
+			} else {
+				Ok(None)
+			}
+		});
+
+		// Assignments to variables of the enclosing scope are kept, variables created inside the arm are dropped with it.
+		for (name, value) in scope.borrow().entries() {
+			if !bindings.iter().any(|(bound, _)| *bound == name) && self.env().get(name.clone()).is_some() {
+				self.env_mut().set(name, value);
+			}
+		}
+
+		result
+	}
+
+	// The bindings and body of the first arm whose pattern and guard accept the subject, for coroutines that step
+	// through the arm instead of running it at once.
+	pub fn select_match_arm(&mut self, subject: Expression, arms: Vec<MatchArm>) -> Result<(Vec<(Identifier, Value)>, Block), InterpreterResult> {
+		let subject = self.run_expression(subject)?.unwrap_constant();
+
+		for arm in arms {
+			let mut bindings: Vec<(Identifier, Value)> = Vec::new();
+
+			if !self.match_pattern(&arm.pattern, &subject, &mut bindings)? {
+				continue;
+			}
+
+			let satisfied = match arm.guard {
+				Some(guard) => {
+					let mut scope = self.env().clone();
+
+					for (name, value) in bindings.iter() {
+						scope.set(name.clone(), value.clone());
+					}
+
+					self.with_environment(Rc::new(RefCell::new(scope)), |interpreter| interpreter.run_expression(guard))?.to_bool()
+				}
+				None => true,
+			};
+
+			if satisfied {
+				return Ok((bindings, arm.body));
+			}
+		}
+
+		Err(InterpreterResult::NoMatchingArm(format!("{:?}", subject)))
+	}
+
+	// Runs the statements of a block, the value of a trailing expression statement is the value of the block.
+	fn run_block_value(&mut self, mut block: Block) -> Result<Value, InterpreterResult> {
+		let last = block.pop();
+
+		for statement in block {
+			self.run_statement(statement)?;
+		}
+
+		Ok(match last {
+			Some(Statement::Expression { expression }) => self.run_expression(expression)?,
+			Some(statement) => {
+				self.run_statement(statement)?;
+				Value::Null
+			}
+			None => Value::Null,
+		})
+	}
+
+	// Turns any iterable value into an iterator: lists, strings (by character), maps (as `[key, value]` pairs), native
+	// iterators and struct instances that either return an iterable from `__iter`/`iter` or produce items from `next` until it returns null.
+	pub fn iterate(&mut self, value: Value) -> Result<ValueIterator, InterpreterResult> {
+		Ok(match value.unwrap_constant() {
+			Value::List(items) => ValueIterator::List { items, index: 0 },
+			Value::String(s) => ValueIterator::Chars { chars: s.chars().collect(), index: 0 },
+			Value::Iterator(iterator) => ValueIterator::Shared(iterator),
+			Value::Range(range) => range.iter(),
+			Value::Map(map) => {
+				let pairs = map.borrow().iter().map(|(key, value)| Value::list(vec![Value::String(key.clone()), value.clone()])).collect();
+
+				ValueIterator::List { items: crate::gc::list(pairs), index: 0 }
+			}
+			instance @ Value::StructInstance { .. } => {
+				for method in ["__iter", "iter"].iter() {
+					if let Some(iterable) = self.call_method(instance.clone(), method, vec![])? {
+						if iterable.same(&instance) {
+							return Ok(ValueIterator::Instance(instance));
 						}
-						Err(err) => return Err(err),
-						_ => (),
-					};
+
+						return self.iterate(iterable);
+					}
 				}
 
-				self.environment = old_environment;
+				if !self.has_method(&instance, "next") {
+					return Err(InterpreterResult::InvalidIterable(instance.typestring()));
+				}
 
-				if return_value.is_some() {
-					return_value.unwrap()
-				} else {
-					Value::Null
+				ValueIterator::Instance(instance)
+			}
+			value => return Err(InterpreterResult::InvalidIterable(value.typestring())),
+		})
+	}
+
+	// String conversion that honours `__str` on struct instances, also inside lists. Lists and maps that contain
+	// themselves print as `[...]` and `{...}` where they repeat.
+	pub fn stringify(&mut self, value: Value) -> Result<String, InterpreterResult> {
+		self.stringify_visiting(value, &mut Vec::new())
+	}
+
+	fn stringify_visiting(&mut self, value: Value, visiting: &mut Vec<*const ()>) -> Result<String, InterpreterResult> {
+		Ok(match value.unwrap_constant() {
+			Value::List(items) => {
+				let pointer = Rc::as_ptr(&items) as *const ();
+
+				if visiting.contains(&pointer) {
+					return Ok("[...]".to_string());
 				}
+
+				visiting.push(pointer);
+				let mut buffer = Vec::new();
+
+				for item in items.borrow().clone() {
+					buffer.push(self.stringify_visiting(item, visiting)?);
+				}
+
+				visiting.pop();
+				format!("[{}]", buffer.join(", "))
 			}
-			_ => todo!(),
+			Value::Map(map) => {
+				let pointer = Rc::as_ptr(&map) as *const ();
+
+				if visiting.contains(&pointer) {
+					return Ok("{...}".to_string());
+				}
+
+				visiting.push(pointer);
+				let mut buffer = Vec::new();
+
+				for (key, value) in map.borrow().clone().iter() {
+					buffer.push(format!("{}: {}", key, self.stringify_visiting(value.clone(), visiting)?));
+				}
+
+				visiting.pop();
+				format!("{{{}}}", buffer.join(", "))
+			}
+			instance @ Value::StructInstance { .. } => match self.call_method(instance.clone(), "__str", vec![])? {
+				Some(value) => value.to_string(),
+				None => instance.to_string(),
+			},
+			value => value.to_string(),
 		})
 	}
 
-	fn run_expression(&mut self, expression: Expression) -> Result<Value, InterpreterResult> {
+	#[allow(clippy::too_many_arguments)]
+	fn call_function(
+		&mut self,
+		name: String,
+		mut params: Vec<Parameter>,
+		body: Block,
+		returns: Option<Type>,
+		is_async: bool,
+		environment: Option<Environment>,
+		this: Option<Value>,
+		arguments: ArgumentValues,
+	) -> Result<Value, InterpreterResult> {
+		let old_environment = Rc::clone(&self.environment);
+
+		let new_environment = Rc::new(RefCell::new(environment.unwrap_or_else(Environment::new)));
+
+		if let Some(this) = this {
+			if params.first().is_some_and(Parameter::is_this) {
+				new_environment.borrow_mut().set("this", this);
+				params = params.iter().filter(|p| p.name != "this").cloned().collect();
+			}
+		}
+
+		let mut params_to_satisfy = params.clone();
+
+		for argument in arguments.clone() {
+			if argument.get_name().is_some() && params_to_satisfy.iter().any(|param| param.get_name() == argument.get_name().unwrap()) {
+				params_to_satisfy.retain(|param| param.get_name() != argument.get_name().unwrap());
+			}
+		}
+
+		let params_without_value = params_to_satisfy.iter().filter(|param| !param.has_initial()).count();
+
+		if params_without_value > arguments.len() {
+			return Err(InterpreterResult::TooFewArguments(name.clone(), arguments.len(), params_without_value));
+		}
+
+		for params_with_initial in params.iter().filter(|param| param.has_initial()) {
+			let initial = self.run_expression(params_with_initial.get_initial().unwrap())?;
+			new_environment.borrow_mut().set(params_with_initial.get_name(), initial);
+		}
+
+		for argument in arguments.clone().filter(|arg| arg.get_name().is_some()) {
+			new_environment.borrow_mut().set(argument.get_name().unwrap(), argument.get_value());
+		}
+		for (param, ArgumentValued { value, .. }) in params_to_satisfy.iter().zip(arguments.filter(|param| param.get_name().is_none())) {
+			new_environment.borrow_mut().set(param.get_name(), value);
+		}
+
+		for param in params.iter() {
+			let value = new_environment.borrow().get(param.get_name()).unwrap_or(Value::Null);
+
+			self.check_type(&value, &param.kind, || format!("argument {} of {}()", param.name, name))?;
+		}
+
+		// Async functions become a task, their body runs on the event loop once the task is awaited or spawned.
+		if is_async {
+			return Ok(event_loop::coroutine(Coroutine::new(new_environment, body), name, returns));
+		}
+
+		// Calling a generator function only sets up its environment, the body runs as items are requested.
+		if body.iter().any(Statement::yields) {
+			let generator = ValueIterator::Generator(Box::new(Coroutine::new(new_environment, body))).shared();
+
+			self.check_type(&generator, &returns, || format!("return value of {}()", name))?;
+
+			return Ok(generator);
+		}
+
+		self.environment = new_environment;
+
+		let mut return_value: Option<Value> = None;
+
+		for statement in body {
+			match self.run_statement(statement) {
+				Err(InterpreterResult::Return(value)) => {
+					return_value = Some(value);
+					break;
+				}
+				Err(err) => {
+					self.environment = old_environment;
+					return Err(err);
+				}
+				_ => (),
+			};
+		}
+
+		self.environment = old_environment;
+
+		let return_value = return_value.unwrap_or(Value::Null);
+
+		self.check_type(&return_value, &returns, || format!("return value of {}()", name))?;
+
+		Ok(return_value)
+	}
+
+	// Runtime side of the gradual type checker, unannotated values are never checked.
+	pub fn check_type(&self, value: &Value, kind: &Option<Type>, context: impl FnOnce() -> String) -> Result<(), InterpreterResult> {
+		match kind {
+			Some(kind) if !self.matches_type(value, kind) => Err(InterpreterResult::TypeMismatch(context(), kind.to_string(), value.clone().typestring())),
+			_ => Ok(()),
+		}
+	}
+
+	pub fn matches_type(&self, value: &Value, kind: &Type) -> bool {
+		let value = value.clone().unwrap_constant();
+
+		match kind {
+			Type::Union(types) => types.iter().any(|kind| self.matches_type(&value, kind)),
+			Type::List(item) => match &value {
+				Value::List(items) => items.borrow().iter().all(|value| self.matches_type(value, item)),
+				_ => false,
+			},
+			Type::Named(name) => match name.as_str() {
+				"any" => true,
+				"number" => numeric::is_numeric(&value),
+				"function" => value.is_function(),
+				builtin @ ("string" | "bool" | "null" | "datetime" | "duration" | "list" | "map" | "regex" | "range" | "iterator" | "task" | "worker") => value.typestring() == builtin,
+				name => match self.globals.get(name) {
+					Some(kind @ (Value::Struct { .. } | Value::Interface { .. } | Value::Enum { .. })) => value.is_instance_of(kind),
+					_ => value.typestring() == name,
+				},
+			},
+		}
+	}
+
+	pub fn run_expression(&mut self, expression: Expression) -> Result<Value, InterpreterResult> {
 		Ok(match expression.clone() {
 			Expression::Number(n) => Value::Number(n),
+			Expression::BigInt(n) => Value::BigInt(n),
+			Expression::Rational(r) => Value::Rational(r),
 			Expression::String(s) => Value::String(s),
 			Expression::Bool(b) => Value::Bool(b),
+			Expression::Null => Value::Null,
 			Expression::Identifier(n) => {
 				if self.globals.contains_key(&n) {
 					self.globals[&n].clone()
@@ -350,15 +1016,38 @@ impl<'i> Interpreter<'i> {
 				}
 			}
 			Expression::Index(target, index) => {
-				let instance = self.run_expression(*target)?;
-				let index = self.run_expression(*index.expect("Expected index."))?.to_number() as usize;
+				let instance = self.run_expression(*target)?.unwrap_constant();
+				let index = self.run_expression(*index.expect("Expected index."))?;
+
+				match (instance, numeric::to_float(&index), index.unwrap_constant()) {
+					(Value::List(items), _, Value::Range(range)) => Value::list(range.slice(&items.borrow())),
+					(Value::String(s), _, Value::Range(range)) => Value::String(range.slice(&s.chars().collect::<Vec<char>>()).into_iter().collect()),
+					// Negative indexes count from the end.
+					(Value::List(items), Some(index), _) => {
+						let items = items.borrow();
+
+						match crate::stdlib::position(index, items.len()) {
+							Some(position) => items[position].clone(),
+							None => return Err(InterpreterResult::UndefinedIndex(index as i64)),
+						}
+					}
+					// Strings are indexed by character.
+					(Value::String(s), Some(index), _) => {
+						let chars = s.chars().collect::<Vec<char>>();
 
-				match instance {
-					Value::List(items) => match items.borrow().get(index) {
-						Some(v) => v.clone(),
-						None => return Err(InterpreterResult::UndefinedIndex(index)),
+						match crate::stdlib::position(index, chars.len()) {
+							Some(position) => Value::String(chars[position].to_string()),
+							None => return Err(InterpreterResult::UndefinedIndex(index as i64)),
+						}
+					}
+					(Value::Map(map), _, Value::String(key)) => match map.borrow().get(&key) {
+						Some(value) => value.clone(),
+						None => return Err(InterpreterResult::UndefinedKey(key)),
+					},
+					(instance, _, index) => match self.call_method(instance.clone(), "__index", vec![index.clone()])? {
+						Some(value) => value,
+						None => return Err(InterpreterResult::TypeError("[]".to_string(), instance.typestring(), index.typestring())),
 					},
-					_ => unreachable!(),
 				}
 			}
 			Expression::MethodCall(target, field, arguments) => {
@@ -368,8 +1057,7 @@ impl<'i> Interpreter<'i> {
 				let mut arguments_value = ArgumentValues::new();
 
 				for argument in arguments.get_arguments().clone() {
-					arguments_value
-						.push_back(ArgumentValued::new(argument.get_name().clone(), self.run_expression(argument.get_expression().clone())?));
+					arguments_value.push(ArgumentValued::new(argument.get_name().clone(), self.run_expression(argument.get_expression().clone())?));
 				}
 				self.call(callable, arguments_value)?
 			}
@@ -392,6 +1080,7 @@ impl<'i> Interpreter<'i> {
 				) -> Result<(), InterpreterResult> {
 					Ok(match instance.clone() {
 						Value::StructInstance { environment, .. } => environment.borrow_mut().set(field, value.clone()),
+						Value::Map(map) => map.borrow_mut().set(field, value.clone()),
 						Value::Struct { methods, .. } => {
 							if !matches!(value.clone(), Value::Function { .. }) {
 								return Err(InterpreterResult::InvalidMethodAssignmentTarget(instance.typestring()));
@@ -403,12 +1092,12 @@ impl<'i> Interpreter<'i> {
 						_ => {
 							let callback = interpreter.get_property(instance.clone(), field.clone(), target.clone(), expression.clone())?;
 							let mut args = ArgumentValues::new();
-							args.push(ArgumentValued::new(Some(field), value));
+							args.push(ArgumentValued::new(Some(field.clone()), value));
 
 							let result = interpreter.call(callback, args)?;
 							match target.clone() {
 								Expression::Identifier(i) => interpreter.env_mut().set(i, result),
-								_ => unimplemented!(),
+								_ => return Err(InterpreterResult::Error(format!("Cannot assign property {} of {}.", field, instance.typestring()))),
 							}
 						}
 					})
@@ -421,17 +1110,40 @@ impl<'i> Interpreter<'i> {
 				let left = self.run_expression(*left)?;
 				let right = self.run_expression(*right)?;
 
-				match (left, op, right) {
-					(Value::Number(l), Op::Add, Value::Number(r)) => Value::Number(l + r),
-					(Value::Number(l), Op::Multiply, Value::Number(r)) => Value::Number(l * r),
-					(Value::Number(l), Op::Divide, Value::Number(r)) => Value::Number(l / r),
-					(Value::Number(l), Op::Subtract, Value::Number(r)) => Value::Number(l - r),
-					(Value::Number(l), Op::Add, Value::String(r)) => {
+				if let Some(result) = self.run_overloaded_operator(&left, &op, &right)? {
+					return Ok(result);
+				}
+
+				match (left.unwrap_constant(), op, right.unwrap_constant()) {
+					(l, op @ (Op::Add | Op::Subtract | Op::Multiply | Op::Divide | Op::Modulo | Op::Pow), r)
+						if numeric::is_numeric(&l) && numeric::is_numeric(&r) =>
+					{
+						numeric::arithmetic(l, op, r)?
+					}
+					(l, op @ (Op::Add | Op::Subtract | Op::Multiply | Op::Divide), r) if l.is_temporal() || r.is_temporal() => {
+						crate::stdlib::duration::temporal_arithmetic(l, op, r)?
+					}
+					(l, Op::Equals, r) => Value::Bool(l.equals(&r)),
+					(l, Op::NotEquals, r) => Value::Bool(!l.equals(&r)),
+					(l, op @ (Op::LessThan | Op::GreaterThan | Op::LessThanOrEquals | Op::GreaterThanOrEquals), r) => {
+						let ordering = match l.compare(&r) {
+							Some(ordering) => ordering,
+							None => return Err(InterpreterResult::TypeError(op.symbol().to_string(), l.typestring(), r.typestring())),
+						};
+
+						Value::Bool(match op {
+							Op::LessThan => ordering == Ordering::Less,
+							Op::GreaterThan => ordering == Ordering::Greater,
+							Op::LessThanOrEquals => ordering != Ordering::Greater,
+							_ => ordering != Ordering::Less,
+						})
+					}
+					(l, Op::Add, Value::String(r)) if numeric::is_numeric(&l) => {
 						let mut l = l.to_string();
 						l.push_str(r.as_str());
 						Value::String(l)
 					}
-					(Value::String(l), Op::Add, Value::Number(r)) => {
+					(Value::String(l), Op::Add, r) if numeric::is_numeric(&r) => {
 						let mut l = l;
 						l.push_str(r.to_string().as_str());
 						Value::String(l)
@@ -441,33 +1153,17 @@ impl<'i> Interpreter<'i> {
 						l.push_str(r.as_str());
 						Value::String(l)
 					}
-					(Value::Number(l), Op::Modulo, Value::Number(r)) => Value::Number(l % r),
-					(Value::String(l), Op::Equals, Value::String(r)) => Value::Bool(l == r),
-					(Value::Number(l), Op::Equals, Value::Number(r)) => Value::Bool(l == r),
-					(Value::Bool(l), Op::Equals, Value::Bool(r)) => Value::Bool(l == r),
-					(Value::String(l), Op::NotEquals, Value::String(r)) => Value::Bool(l != r),
-					(Value::Number(l), Op::NotEquals, Value::Number(r)) => Value::Bool(l != r),
-					(Value::Bool(l), Op::NotEquals, Value::Bool(r)) => Value::Bool(l != r),
-					(Value::Number(l), Op::LessThan, Value::Number(r)) => Value::Bool(l < r),
-					(Value::Number(l), Op::GreaterThan, Value::Number(r)) => Value::Bool(l > r),
-					(Value::Number(l), Op::LessThanOrEquals, Value::Number(r)) => Value::Bool(l <= r),
-					(Value::Number(l), Op::GreaterThanOrEquals, Value::Number(r)) => Value::Bool(l >= r),
 					(l, Op::And, r) => Value::Bool(l.to_bool() && r.to_bool()),
 					(l, Op::Or, r) => Value::Bool(l.to_bool() || r.to_bool()),
-					(Value::Number(l), Op::Pow, Value::Number(r)) => Value::Number(l.powf(r)),
-					(l, Op::In, Value::List(r)) => {
-						let filtered: Vec<Value> = r.borrow().clone().into_iter().filter(|v| v.clone().is(l.clone())).collect();
-
-						Value::Bool(!filtered.is_empty())
-					}
+					(l, Op::In, Value::Range(r)) => Value::Bool(numeric::to_float(&l).is_some_and(|n| r.contains(n))),
+					(l, Op::NotIn, Value::Range(r)) => Value::Bool(!numeric::to_float(&l).is_some_and(|n| r.contains(n))),
+					(l, Op::In, Value::List(r)) => Value::Bool(r.borrow().iter().any(|v| v.equals(&l))),
+					(Value::String(l), Op::In, Value::Map(r)) => Value::Bool(r.borrow().contains(&l)),
+					(Value::String(l), Op::NotIn, Value::Map(r)) => Value::Bool(!r.borrow().contains(&l)),
 					(Value::String(l), Op::In, Value::String(r)) => Value::Bool(r.contains(l.as_str())),
-					(l, Op::NotIn, Value::List(r)) => {
-						let filtered: Vec<Value> = r.borrow().clone().into_iter().filter(|v| v.clone().is(l.clone())).collect();
-
-						Value::Bool(filtered.is_empty())
-					}
+					(l, Op::NotIn, Value::List(r)) => Value::Bool(!r.borrow().iter().any(|v| v.equals(&l))),
 					(Value::String(l), Op::NotIn, Value::String(r)) => Value::Bool(!r.contains(l.as_str())),
-					_ => todo!(),
+					(l, op, r) => return Err(InterpreterResult::TypeError(op.symbol().to_string(), l.typestring(), r.typestring())),
 				}
 			}
 			Expression::List(items) => {
@@ -477,59 +1173,131 @@ impl<'i> Interpreter<'i> {
 					values.push(self.run_expression(item)?);
 				}
 
-				Value::List(Rc::new(RefCell::new(values)))
+				Value::list(values)
+			}
+			Expression::Range { start, end, step, inclusive } => {
+				let operator = if inclusive { "..=" } else { ".." };
+				let start = self.run_expression(*start)?.unwrap_constant();
+				let end = self.run_expression(*end)?.unwrap_constant();
+
+				let (from, to) = match (numeric::to_float(&start), numeric::to_float(&end)) {
+					(Some(from), Some(to)) => (from, to),
+					_ => return Err(InterpreterResult::TypeError(operator.to_string(), start.typestring(), end.typestring())),
+				};
+
+				let step = match step {
+					Some(step) => {
+						let step = self.run_expression(*step)?;
+
+						match numeric::to_float(&step) {
+							Some(step) => step,
+							None => return Err(InterpreterResult::TypeError("step".to_string(), "range".to_string(), step.typestring())),
+						}
+					}
+					None => 1.0,
+				};
+
+				if step == 0.0 {
+					return Err(InterpreterResult::Error("Range step cannot be zero.".to_string()));
+				}
+
+				Value::Range(Range::new(from, to, step, inclusive))
+			}
+			// Awaits that can't suspend the enclosing statement, like the top level of a script, run the event loop
+			// until the task is done.
+			Expression::Await(awaited) => match self.run_expression(*awaited)?.unwrap_constant() {
+				Value::Task(task) => event_loop::block_on(self, &task)?,
+				value => value,
+			},
+			Expression::Spawn(spawned) => match self.run_expression(*spawned)?.unwrap_constant() {
+				Value::Task(task) => {
+					event_loop::schedule(self, &task);
+					Value::Task(task)
+				}
+				value => return Err(InterpreterResult::TypeMismatch("spawn".to_string(), "task".to_string(), value.typestring())),
+			},
+			Expression::Match(subject, arms) => {
+				let subject = self.run_expression(*subject)?.unwrap_constant();
+
+				for arm in arms {
+					let mut bindings: Vec<(Identifier, Value)> = Vec::new();
+
+					if !self.match_pattern(&arm.pattern, &subject, &mut bindings)? {
+						continue;
+					}
+
+					if let Some(value) = self.run_match_arm(bindings, arm.guard, arm.body)? {
+						return Ok(value);
+					}
+				}
+
+				return Err(InterpreterResult::NoMatchingArm(format!("{:?}", subject)));
 			}
-			Expression::Closure(params, body) => {
-				Value::Function { name: String::from("Closure"), params, body, environment: Some(self.environment.borrow().clone()), context: None }
+			Expression::Closure(params, body, return_type, is_async) => {
+				Value::Function { name: String::from("Closure"), params, body, return_type, is_async, environment: Some(self.environment.borrow().clone()), context: None }
 			}
 			Expression::Struct(definition, fields) => {
 				let definition = self.run_expression(*definition)?;
 
 				let (name, field_definitions, methods) = match definition.clone() {
 					Value::Struct { name, fields, methods, .. } => (name, fields, methods),
-					_ => unreachable!(),
+					definition => return Err(InterpreterResult::Error(format!("Value of type {} is not a struct.", definition.typestring()))),
 				};
 
-				let mut environment = Environment::new();
+				if let Some(field) = fields.keys().find(|field| !field_definitions.iter().any(|f| f.name == **field)) {
+					return Err(InterpreterResult::UndefinedField(name, field.clone()));
+				}
 
-				for parameter in field_definitions.iter().find(|param| param.has_initial()) {
-					let value = self.run_expression(parameter.get_initial().unwrap())?;
+				let missing = field_definitions
+					.iter()
+					.filter(|f| !f.has_initial() && !fields.contains_key(&f.name))
+					.map(|f| f.name.clone())
+					.collect::<Vec<String>>();
 
-					environment.set(parameter.get_name(), value);
+				if !missing.is_empty() {
+					return Err(InterpreterResult::MissingFields(name, missing.join(", ")));
 				}
 
-				for (field, value) in fields {
-					if !field_definitions.iter().any(|f| f.name == field) {
-						return Err(InterpreterResult::UndefinedField(name, field.clone()));
-					}
+				let mut environment = Environment::new();
 
-					let value = self.run_expression(value)?;
+				// Defaults are evaluated for every instance, so a `[]` default is never shared between two instances.
+				// Field values are stored as they are: lists and nested instances are shared by reference, like any assignment.
+				for field in field_definitions.iter() {
+					let value = match fields.get(&field.name) {
+						Some(value) => self.run_expression(value.clone())?,
+						None => self.run_expression(field.get_initial().unwrap())?,
+					};
 
-					environment.set(
-						field,
-						match value {
-							Value::StructInstance { environment, definition } => {
-								let environment = environment.borrow().clone();
+					self.check_type(&value, &field.kind, || format!("field {} of {}", field.name, name))?;
 
-								Value::StructInstance { definition, environment: Rc::new(RefCell::new(environment)) }
-							}
-							_ => value,
-						},
-					);
+					environment.set(field.get_name(), value);
 				}
 
-				let environment = Rc::new(RefCell::new(environment));
+				let environment = crate::gc::instance(environment);
 
 				for (name, method) in methods.borrow().clone() {
 					let method = match method {
-						Value::Function { name, body, params, .. } => Value::Function { name, params, body, environment: None, context: None },
+						Value::Function { name, body, params, return_type, is_async, environment: declaration, .. } => {
+							// Only `super` is kept from the declaring environment, inherited methods still see their own parent.
+							let environment = declaration.and_then(|declaration| declaration.get("super")).map(|parent| {
+								let mut environment = Environment::new();
+								environment.set("super", parent);
+								environment
+							});
+
+							Value::Function { name, params, body, return_type, is_async, environment, context: None }
+						}
 						_ => unreachable!(),
 					};
 
 					environment.borrow_mut().set(name, method);
 				}
 
-				Value::StructInstance { environment, definition: Box::new(definition) }
+				let instance = Value::StructInstance { environment, definition: Box::new(definition) };
+
+				self.call_method(instance.clone(), "init", vec![])?;
+
+				instance
 			}
 			Expression::Call(callable, arguments) => {
 				let callable = self.run_expression(*callable)?;
@@ -547,38 +1315,13 @@ impl<'i> Interpreter<'i> {
 
 				match op {
 					Op::Bang => Value::Bool(!right.to_bool()),
-					Op::Subtract => Value::Number(-right.to_number()),
+					Op::Subtract if numeric::is_numeric(&right) => numeric::negate(right),
+					Op::Subtract => return Err(InterpreterResult::TypeError("-".to_string(), "number".to_string(), right.typestring())),
 					_ => unreachable!(),
 				}
 			}
 
-			Expression::MathAssign(target, op, value) => {
-				let target_expr = self.run_expression(*target.clone())?;
-				let value = self.run_expression(*value)?;
-
-				match target_expr.clone() {
-					Value::Number(n) => {
-						match *target.clone() {
-							Expression::Identifier(i) => {
-								self.env_mut().set(
-									i,
-									Value::Number(match op {
-										Op::Add => n + value.clone().to_number(),
-										Op::Subtract => n - value.clone().to_number(),
-										Op::Multiply => n * value.clone().to_number(),
-										Op::Divide => n / value.clone().to_number(),
-										_ => unreachable!(),
-									}),
-								);
-							}
-							_ => unimplemented!(),
-						}
-
-						Value::Number(n + value.to_number())
-					}
-					_ => unreachable!(),
-				}
-			}
+			Expression::MathAssign(target, op, value) => self.math_assign(*target, op, *value)?,
 
 			Expression::Assign(target, value) => {
 				let value = self.run_expression(*value)?;
@@ -592,13 +1335,29 @@ impl<'i> Interpreter<'i> {
 					Ok(match instance {
 						Value::List(items) => match index {
 							Some(i) => {
-								let index = interpreter.run_expression(*i)?.to_number();
-								items.borrow_mut()[index as usize] = value.clone();
+								let index = interpreter.run_expression(*i)?;
+								let index = match numeric::to_float(&index) {
+									Some(index) => index,
+									None => return Err(InterpreterResult::TypeError("[]".to_string(), "list".to_string(), index.typestring())),
+								};
+								let mut items = items.borrow_mut();
+
+								match crate::stdlib::position(index, items.len()) {
+									Some(position) => items[position] = value.clone(),
+									None => return Err(InterpreterResult::UndefinedIndex(index as i64)),
+								}
 							}
 							None => {
 								items.borrow_mut().push(value.clone());
 							}
 						},
+						Value::Map(map) => match index {
+							Some(key) => match interpreter.run_expression(*key)?.unwrap_constant() {
+								Value::String(key) => map.borrow_mut().set(key, value.clone()),
+								key => return Err(InterpreterResult::TypeError("[]".to_string(), "map".to_string(), key.typestring())),
+							},
+							None => return Err(InterpreterResult::InvalidAppendTarget("map".to_string())),
+						},
 						_ => return Err(InterpreterResult::InvalidAppendTarget(instance.typestring())),
 					})
 				}
@@ -611,23 +1370,22 @@ impl<'i> Interpreter<'i> {
 					}
 
 					_ => {
-						match self.run_expression(*target.clone())? {
-							Value::Constant(_) => return Err(InterpreterResult::CannotAssignValueToConstant),
-							_ => (),
-						};
+						if let Value::Constant(_) = self.run_expression(*target.clone())? {
+							return Err(InterpreterResult::CannotAssignValueToConstant);
+						}
 
 						match *target.clone() {
 							Expression::Identifier(i) => {
 								self.env_mut().set(i, value.clone());
 							}
-							_ => todo!(),
+							_ => return Err(InterpreterResult::Error("Invalid assignment target.".to_string())),
 						}
 					}
 				};
 
 				value
 			}
-			_ => todo!("{:?}", expression),
+			expression => return Err(InterpreterResult::Error(format!("Unsupported expression: {:?}.", expression))),
 		})
 	}
 
@@ -646,15 +1404,25 @@ impl<'i> Interpreter<'i> {
 
 		self.globals.insert(
 			struct_name.clone(),
-			Value::Struct { name: struct_name, methods: Rc::new(RefCell::new(methods)), fields: vec![], propreties: None },
+			Value::Struct { name: struct_name, methods: Rc::new(RefCell::new(methods)), fields: vec![], parent: None, interfaces: vec![] },
 		);
 	}
 
-	fn env(&self) -> Ref<Environment> {
+	// Runs `callback` with `environment` as the current scope, used to resume generators inside their own call frame.
+	pub fn with_environment<T>(&mut self, environment: Rc<RefCell<Environment>>, callback: impl FnOnce(&mut Self) -> T) -> T {
+		let old_environment = std::mem::replace(&mut self.environment, environment);
+		let result = callback(self);
+
+		self.environment = old_environment;
+
+		result
+	}
+
+	fn env(&self) -> Ref<'_, Environment> {
 		RefCell::borrow(&self.environment)
 	}
 
-	fn env_mut(&mut self) -> RefMut<Environment> {
+	fn env_mut(&mut self) -> RefMut<'_, Environment> {
 		RefCell::borrow_mut(&self.environment)
 	}
 
@@ -663,8 +1431,8 @@ impl<'i> Interpreter<'i> {
 			Value::StructInstance { environment, definition, .. } => {
 				if let Some(value) = environment.borrow().get(field.clone()) {
 					match value {
-						Value::Function { name, params, body, environment, .. } => match expression.clone() {
-							Expression::MethodCall(..) => Value::Function { name, params, body, environment, context: Some(target) },
+						Value::Function { name, params, body, return_type, is_async, environment, .. } => match expression.clone() {
+							Expression::MethodCall(..) => Value::Function { name, params, body, return_type, is_async, environment, context: Some(Box::new(target)) },
 							_ => {
 								if let Expression::Identifier(i) = target {
 									return Err(InterpreterResult::UndefinedField(i, field));
@@ -690,8 +1458,8 @@ impl<'i> Interpreter<'i> {
 				} {
 					let initial = self.run_expression(value)?;
 					match initial {
-						Value::Function { name, params, body, environment, .. } => {
-							Value::Function { name, params, body, environment, context: Some(target) }
+						Value::Function { name, params, body, return_type, is_async, environment, .. } => {
+							Value::Function { name, params, body, return_type, is_async, environment, context: Some(Box::new(target)) }
 						}
 						_ => initial,
 					}
@@ -706,13 +1474,19 @@ impl<'i> Interpreter<'i> {
 			}
 			Value::Struct { name, methods, fields, .. } => {
 				if let Some(value) = methods.borrow().get(&field.clone()) {
-					value.clone()
+					match (value.clone(), target) {
+						// `super.method()` runs the parent's method on the current `this`.
+						(Value::Function { name, params, body, return_type, is_async, environment, .. }, Expression::Identifier(target)) if target == "super" => {
+							Value::Function { name, params, body, return_type, is_async, environment, context: Some(Box::new(Expression::Identifier("this".to_string()))) }
+						}
+						(value, _) => value,
+					}
 				} else if let Some(value) = fields.iter().find(|p| p.name == field).map(|p| p.get_initial().clone()).unwrap_or_else(|| None) {
 					let initial = self.run_expression(value)?;
 
 					match initial {
-						Value::Function { name, params, body, environment, .. } => {
-							Value::Function { name, params, body, environment, context: Some(target) }
+						Value::Function { name, params, body, return_type, is_async, environment, .. } => {
+							Value::Function { name, params, body, return_type, is_async, environment, context: Some(Box::new(target)) }
 						}
 						_ => initial,
 					}
@@ -720,46 +1494,124 @@ impl<'i> Interpreter<'i> {
 					return Err(InterpreterResult::UndefinedMethod(name, field));
 				}
 			}
-			Value::String(..) => match expression {
-				Expression::MethodCall(..) => {
-					Value::NativeMethod { name: field.clone(), callback: crate::stdlib::StringObject::get(field), context: target }
+			Value::Enum { name, variants, methods } => {
+				if variants.iter().any(|v| v.name == field) {
+					Value::EnumVariant { definition: Box::new(Value::Enum { name, variants, methods }), variant: field, values: vec![] }
+				} else if let Some(method) = methods.borrow().get(&field) {
+					method.clone()
+				} else {
+					return Err(InterpreterResult::UndefinedField(name, field));
 				}
-				_ => todo!(),
-			},
-			Value::Number(..) => match expression {
-				Expression::MethodCall(..) => {
-					Value::NativeMethod { name: field.clone(), callback: crate::stdlib::NumberObject::get(field), context: target }
+			}
+			Value::EnumVariant { definition, variant, values } => {
+				let (name, variants, methods) = match *definition {
+					Value::Enum { name, variants, methods } => (name, variants, methods),
+					_ => unreachable!(),
+				};
+
+				let fields = variants.into_iter().find(|v| v.name == variant).and_then(|v| v.fields).unwrap_or_default();
+
+				let method = methods.borrow().get(&field).cloned();
+
+				match (expression, fields.iter().position(|f| *f == field), method) {
+					(Expression::MethodCall(..), _, Some(Value::Function { name, params, body, return_type, is_async, environment, .. })) => {
+						Value::Function { name, params, body, return_type, is_async, environment, context: Some(Box::new(target)) }
+					}
+					(Expression::GetProperty(..), Some(index), _) => values[index].clone(),
+					_ => return Err(InterpreterResult::UndefinedField(format!("{}.{}", name, variant), field)),
 				}
-				_ => todo!(),
+			}
+			Value::String(..) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::StringObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("String".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("String".to_string(), field)),
+			},
+			Value::Number(..) | Value::BigInt(..) | Value::Rational(..) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::NumberObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("Number".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("Number".to_string(), field)),
 			},
 			Value::List(..) => match expression {
-				Expression::MethodCall(..) => {
-					Value::NativeMethod { name: field.clone(), callback: crate::stdlib::ListObject::get(field), context: target }
-				}
-				_ => todo!(),
+				Expression::MethodCall(..) => match crate::stdlib::ListObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("List".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("List".to_string(), field)),
+			},
+			// `map.key` reads an entry, methods are looked up only for calls.
+			Value::Map(map) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::MapObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("Map".to_string(), field)),
+				},
+				_ => match map.borrow().get(&field) {
+					Some(value) => value.clone(),
+					None => return Err(InterpreterResult::UndefinedKey(field)),
+				},
+			},
+			Value::Duration(..) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::DurationObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("Duration".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("Duration".to_string(), field)),
+			},
+			Value::Regex(..) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::RegexObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("Regex".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("Regex".to_string(), field)),
+			},
+			Value::Iterator(..) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::IteratorObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("Iterator".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("Iterator".to_string(), field)),
+			},
+			Value::Range(..) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::RangeObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("Range".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("Range".to_string(), field)),
+			},
+			Value::Worker(..) => match expression {
+				Expression::MethodCall(..) => match crate::stdlib::WorkerObject::get(&field) {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedMethod("Worker".to_string(), field)),
+				},
+				_ => return Err(InterpreterResult::UndefinedField("Worker".to_string(), field)),
 			},
 			Value::Constant(v) => self.get_property(*v, field, target, expression)?,
-			Value::DateTime(..) => match expression {
+			Value::DateTime(..) => {
 				// TODO: Remake origin of stdlib objects
-				Expression::GetProperty(..) => {
-					Value::NativeMethod { name: field.clone(), callback: crate::stdlib::DateTimeObject::getter_property(field), context: target }
-				}
-				Expression::SetProperty(..) => {
-					Value::NativeMethod { name: field.clone(), callback: crate::stdlib::DateTimeObject::setter_property(field), context: target }
-				}
-				Expression::MethodCall(..) => {
-					Value::NativeMethod { name: field.clone(), callback: crate::stdlib::DateTimeObject::get_method(field), context: target }
+				let callback = match expression {
+					Expression::GetProperty(..) => crate::stdlib::DateTimeObject::getter_property(&field),
+					Expression::SetProperty(..) => crate::stdlib::DateTimeObject::setter_property(&field),
+					Expression::MethodCall(..) => match crate::stdlib::DateTimeObject::get_method(&field) {
+						Some(callback) => Some(callback),
+						None => return Err(InterpreterResult::UndefinedMethod("DateTime".to_string(), field)),
+					},
+					_ => None,
+				};
+
+				match callback {
+					Some(callback) => Value::NativeMethod { name: field, callback, context: target },
+					None => return Err(InterpreterResult::UndefinedField("DateTime".to_string(), field)),
 				}
-				_ => todo!(),
-			},
-			_ => todo!(),
+			}
+			value => return Err(InterpreterResult::UndefinedField(value.typestring(), field)),
 		})
 	}
 
 	pub fn exec(&mut self, ast: Program) -> Result<(), InterpreterResult> {
-		let mut ast = ast.into_iter();
-
-		while let Some(statement) = ast.next() {
+		for statement in ast {
 			self.run_statement(statement)?;
 		}
 
@@ -779,3 +1631,271 @@ impl<'i> Interpreter<'i> {
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{caught, evaluate, evaluate_in, register_global_functions, register_global_structs, Interpreter};
+
+	// The value of `source` run after the declarations in `prelude`, printed the way `Console.println!` prints it.
+	fn run(prelude: &str, source: &str) -> String {
+		let mut interpreter = Interpreter::new([].iter(), std::env::temp_dir().join("test.lg"));
+
+		register_global_functions(&mut interpreter);
+		register_global_structs(&mut interpreter);
+
+		let value = evaluate_in(&mut interpreter, &format!("{}\n{}", prelude, source)).unwrap();
+
+		interpreter.stringify(value).unwrap()
+	}
+
+	const VECTOR: &str = "struct Vector {
+	x, y
+
+	fn __add(this, other) {
+		return Vector { x: this.x + other.x, y: this.y + other.y }
+	}
+
+	fn __eq(this, other) {
+		return this.x == other.x && this.y == other.y
+	}
+
+	fn __lt(this, other) {
+		return this.x < other.x
+	}
+
+	fn __index(this, i) {
+		if i == 0 {
+			return this.x
+		}
+		return this.y
+	}
+
+	fn __len(this) {
+		return 2
+	}
+
+	fn __iter(this) {
+		return [this.x, this.y]
+	}
+
+	fn __str(this) {
+		return \"Vector(\" + this.x + \", \" + this.y + \")\"
+	}
+}
+
+create a = Vector { x: 1, y: 2 }
+create b = Vector { x: 3, y: 4 }
+create c = Vector { x: 1, y: 2 }
+";
+
+	#[test]
+	fn it_dispatches_operators_to_magic_methods() {
+		assert_eq!(run(VECTOR, "a + b"), "Vector(4, 6)");
+		assert_eq!(run(VECTOR, "a == c"), "true");
+		assert_eq!(run(VECTOR, "a != b"), "true");
+		// `>` and `<=` are derived from `__lt`.
+		assert_eq!(run(VECTOR, "a < b"), "true");
+		assert_eq!(run(VECTOR, "a > b"), "false");
+		assert_eq!(run(VECTOR, "b <= a"), "false");
+		assert_eq!(run(VECTOR, "b[1]"), "4");
+		assert_eq!(run(VECTOR, "len?(a)"), "2");
+		assert_eq!(run(VECTOR, "\"a = \" + a"), "a = Vector(1, 2)");
+		assert_eq!(run(VECTOR, "create total = 0\nfor component in b {\n\ttotal = total + component\n}\ntotal"), "7");
+		assert_eq!(run(VECTOR, "create result = [a, b]\nresult"), "[Vector(1, 2), Vector(3, 4)]");
+	}
+
+	const DESCRIBE: &str = "struct Person {
+	name, age
+}
+
+fn describe(value) {
+	return match value {
+		0 => \"zero\",
+		1..10 => \"small number\",
+		\"hello\" => \"a greeting\",
+		[] => \"an empty list\",
+		[first, ..rest] => \"a list starting with \" + first + \" and \" + len?(rest) + \" more\",
+		Person { name, age } if age >= 18 => name + \" is an adult\",
+		Person { name } => name + \" is a minor\",
+		n if n < 0 => \"negative \" + n,
+		_ => \"something else\",
+	}
+}
+";
+
+	#[test]
+	fn it_runs_the_first_match_arm_whose_pattern_and_guard_match() {
+		assert_eq!(run(DESCRIBE, "describe(0)"), "zero");
+		assert_eq!(run(DESCRIBE, "describe(7)"), "small number");
+		assert_eq!(run(DESCRIBE, "describe(-3)"), "negative -3");
+		assert_eq!(run(DESCRIBE, "describe(\"hello\")"), "a greeting");
+		assert_eq!(run(DESCRIBE, "describe([])"), "an empty list");
+		assert_eq!(run(DESCRIBE, "describe([1, 2, 3])"), "a list starting with 1 and 2 more");
+		assert_eq!(run(DESCRIBE, "describe(Person { name: \"Ana\", age: 30 })"), "Ana is an adult");
+		// A failing guard falls through to the next arm.
+		assert_eq!(run(DESCRIBE, "describe(Person { name: \"Bruno\", age: 12 })"), "Bruno is a minor");
+		assert_eq!(run(DESCRIBE, "describe(100)"), "something else");
+		assert_eq!(evaluate("match true {\n\ttrue => {\n\t\tcreate greeting = \"yes\"\n\t\tgreeting + \"!\"\n\t}\n\tfalse => \"no\",\n}").unwrap().to_string(), "yes!");
+	}
+
+	#[test]
+	fn it_keeps_match_bindings_inside_their_arm() {
+		assert_eq!(evaluate("create n = \"outer\"\nmatch 5 {\n\tn if n > 10 => \"big\",\n\tn => \"small\",\n}\nn").unwrap().to_string(), "outer");
+		assert_eq!(evaluate("match [1, 2] {\n\t[first, ..rest] => first,\n}\nfirst").unwrap_err().to_string(), "Undefined variable: first.");
+		// Variables from outside the arm can still be assigned.
+		assert_eq!(evaluate("create count = 0\nmatch 1 {\n\t1 => {\n\t\tcount = count + 1\n\t}\n\t_ => null,\n}\ncount").unwrap().to_string(), "1");
+		assert_eq!(evaluate("match 1 {\n\t1 => {\n\t\tcreate inner = 2\n\t}\n\t_ => null,\n}\ninner").unwrap_err().to_string(), "Undefined variable: inner.");
+		assert_eq!(caught("match 3 {\n\t1 => \"one\",\n}"), "No match arm matched value 3.");
+	}
+
+	#[test]
+	fn it_raises_catchable_errors_for_unsupported_operations() {
+		assert_eq!(caught("create s = \"abc\"\ns.length"), "Undefined field: String.length");
+		assert_eq!(caught("create s = \"abc\"\ns.shout!()"), "Undefined method: String.shout!()");
+		assert_eq!(caught("create s = \"abc\"\ns += 1"), "TypeError: unsupported operand types for +=: string and number.");
+		assert_eq!(caught("create n = 1\nn()"), "TypeError: call expects function, got number.");
+		assert_eq!(caught("create b = true\nb.value"), "Undefined field: bool.value");
+		assert_eq!(caught("create r = 1..3\nr.size"), "Undefined field: Range.size");
+	}
+
+	#[test]
+	fn it_updates_map_entries_with_compound_assignment() {
+		assert_eq!(evaluate("create m = Json.parse!(\"{}\")\nm.count = 1\nm.count += 2\nm.count").unwrap().to_string(), "3");
+	}
+
+	#[test]
+	fn it_destructures_lists_and_structs() {
+		assert_eq!(evaluate("create [a, b] = [1, 2]\ncreate result = [b, a]\nresult").unwrap().to_string(), "[2, 1]");
+		assert_eq!(evaluate("create [first, second = 5, third = 0] = [1]\ncreate result = [first, second, third]\nresult").unwrap().to_string(), "[1, 5, 0]");
+		assert_eq!(evaluate("create [head, ..tail] = [1, 2, 3]\ntail").unwrap().to_string(), "[2, 3]");
+		assert_eq!(evaluate("struct User {\n\tname, role = \"member\"\n}\ncreate user = User { name: \"Ana\" }\nconst { name, role: kind } = user\nname + \" \" + kind").unwrap().to_string(), "Ana member");
+		assert_eq!(evaluate("create [[x, y], [z]] = [[1, 2], [3]]\nx + y + z").unwrap().to_string(), "6");
+		assert_eq!(caught("const [a] = [1]\na = 2"), "Cannot assign value to constant.");
+		assert_eq!(caught("create [a, b] = 1"), "Cannot destructure value 1, it does not fit the pattern.");
+	}
+
+	#[test]
+	fn it_destructures_parameters_and_for_heads() {
+		assert_eq!(evaluate("fn sum([a, b]) {\n\treturn a + b\n}\nsum([2, 3])").unwrap().to_string(), "5");
+		assert_eq!(evaluate("fn greet({ name, role = \"guest\" }) {\n\treturn name + \" \" + role\n}\nstruct User {\n\tname\n}\ngreet(User { name: \"Ana\" })").unwrap().to_string(), "Ana guest");
+		assert_eq!(evaluate("create total = 0\nfor [key, value] in [[\"a\", 1], [\"b\", 2]] {\n\ttotal = total + value\n}\ntotal").unwrap().to_string(), "3");
+		assert_eq!(evaluate("create last = null\nfor (i, [x]) in [[7], [8]] {\n\tlast = i + x\n}\nlast").unwrap().to_string(), "9");
+	}
+
+	const SHAPE: &str = "enum Shape {
+	Circle(radius),
+	Rect(width, height),
+	Empty
+
+	fn area(this) {
+		return match this {
+			Shape.Circle(r) => 3 * r * r,
+			Shape.Rect(w, h) => w * h,
+			Shape.Empty => 0,
+		}
+	}
+}";
+
+	#[test]
+	fn it_constructs_compares_and_matches_enum_variants() {
+		assert_eq!(run(SHAPE, "Shape.Rect(width = 3, height = 4)"), "Shape.Rect(3, 4)");
+		assert_eq!(run(SHAPE, "type?(Shape.Circle(2))"), "Shape.Circle");
+		assert_eq!(run(SHAPE, "create rect = Shape.Rect(3, 4)\nrect.height"), "4");
+		assert_eq!(run(SHAPE, "create circle = Shape.Circle(2)\ncircle.area()"), "12");
+		assert_eq!(run(SHAPE, "create empty = Shape.Empty\nempty.area()"), "0");
+		assert_eq!(run(SHAPE, "Shape.Circle(2) == Shape.Circle(2)"), "true");
+		assert_eq!(run(SHAPE, "Shape.Circle(2) == Shape.Circle(3)"), "false");
+		assert_eq!(run(SHAPE, "Shape.Empty == Shape.Empty"), "true");
+		assert_eq!(run(SHAPE, "match Shape.Rect(2, 2) {\n\tShape.Rect(w, h) if w == h => \"square\",\n\t_ => \"other\",\n}"), "square");
+		assert_eq!(caught(&format!("{}\nShape.Empty()", SHAPE)), "Unit variant Shape.Empty cannot be called.");
+		assert_eq!(caught(&format!("{}\nShape.Rect(1)", SHAPE)), "Too few arguments to function Shape.Rect(), 1 passed in, 2 expected.");
+	}
+
+	const USERS: &str = "interface Describable {
+	fn describe(this)
+}
+
+struct User : Describable {
+	name
+
+	fn describe(this) {
+		return this.name
+	}
+
+	fn greet(this) {
+		return \"Hi \" + this.name
+	}
+}
+
+struct Admin : User {
+	level = 1
+
+	fn describe(this) {
+		return super.describe() + \" (admin \" + this.level + \")\"
+	}
+}
+
+struct Root : Admin {
+	level = 9
+}
+
+create user = User { name: \"Ana\" }
+create admin = Admin { name: \"Bruno\", level: 3 }
+create root = Root { name: \"Carla\" }";
+
+	#[test]
+	fn it_inherits_fields_and_methods_from_parent_structs() {
+		assert_eq!(run(USERS, "user.describe()"), "Ana");
+		assert_eq!(run(USERS, "admin.describe()"), "Bruno (admin 3)");
+		assert_eq!(run(USERS, "admin.greet()"), "Hi Bruno");
+		// Defaults and `super` resolve through every level of the chain.
+		assert_eq!(run(USERS, "root.describe()"), "Carla (admin 9)");
+		assert_eq!(run(USERS, "root.greet()"), "Hi Carla");
+	}
+
+	#[test]
+	fn it_checks_types_and_interfaces_across_inheritance() {
+		assert_eq!(run(USERS, "create result = [is?(admin, User), is?(root, User), is?(root, Describable), is?(user, Admin), is?(1, User)]\nresult"), "[true, true, true, false, false]");
+		assert_eq!(caught("interface Printable {\n\tfn print(this, target)\n}\nstruct Report : Printable {\n\ttitle\n}"), "Struct Report does not implement print(this, target) required by interface Printable.");
+	}
+
+	const ACCOUNTS: &str = "struct Address {
+	city
+}
+
+struct Account {
+	name, email, tags = [], active = true, address = null
+
+	fn init(this) {
+		this.email = this.email.lower!()
+	}
+}
+
+create home = Address { city: \"Lisbon\" }
+create ana = Account { name: \"Ana\", email: \"ANA@LUGLI.DEV\", address: home }
+create bruno = Account { name: \"Bruno\", email: \"bruno@lugli.dev\", address: home }";
+
+	#[test]
+	fn it_applies_every_default_and_runs_init_after_struct_literals() {
+		assert_eq!(run(ACCOUNTS, "create result = [ana.email, ana.active, ana.tags, bruno.address.city]\nresult"), "[ana@lugli.dev, true, [], Lisbon]");
+		// Defaults are evaluated for every instance, nested instances are shared.
+		assert_eq!(run(ACCOUNTS, "ana.tags.push!(\"admin\")\nbruno.tags"), "[]");
+		assert_eq!(run(ACCOUNTS, "home.city = \"Porto\"\nana.address.city"), "Porto");
+		assert_eq!(run(ACCOUNTS, "same?(ana.address, bruno.address)"), "true");
+	}
+
+	#[test]
+	fn it_names_the_missing_fields_of_struct_literals() {
+		assert_eq!(caught(&format!("{}\nAccount {{ tags: [] }}", ACCOUNTS)), "Missing fields for struct Account: name, email.");
+		assert_eq!(caught(&format!("{}\nAccount {{ name: \"Carla\" }}", ACCOUNTS)), "Missing fields for struct Account: email.");
+	}
+
+	#[test]
+	fn it_checks_annotated_arguments_and_return_values_when_called() {
+		assert_eq!(evaluate("fn area(w: number, h: number) -> number {\n\treturn w * h\n}\narea(3, 4)").unwrap().to_string(), "12");
+		assert_eq!(caught("fn area(w: number, h: number) -> number {\n\treturn w * h\n}\ncreate side = \"3\"\narea(side, 2)"), "TypeError: argument w of area() expects number, got string.");
+		assert_eq!(caught("create name = fn(n) -> string {\n\treturn n\n}\nname(1)"), "TypeError: return value of Closure() expects string, got number.");
+		assert_eq!(caught("struct Box {\n\tvalue,\n\tfn get(this) -> number {\n\t\treturn this.value\n\t}\n}\ncreate b = Box { value: \"s\" }\nb.get()"), "TypeError: return value of get() expects number, got string.");
+		assert_eq!(evaluate("fn maybe(value: string | null) -> list<number> {\n\treturn [1]\n}\nmaybe(null)").unwrap().to_string(), "[1]");
+	}
+}
@@ -0,0 +1,97 @@
+use std::{
+	cell::RefCell,
+	fs::File,
+	io::{BufReader, Lines},
+	rc::Rc,
+};
+
+use crate::{
+	environment::Value,
+	coroutine::Coroutine,
+	interpreter::{Interpreter, InterpreterResult},
+	range::Range,
+};
+
+// State of a `for ... in` loop or of a `Value::Iterator`. Items are produced one at a time, so ranges, zips and
+// user iterators never build an intermediate list.
+pub enum ValueIterator {
+	List { items: Rc<RefCell<Vec<Value>>>, index: usize },
+	Chars { chars: Vec<char>, index: usize },
+	Range { range: Range, index: usize },
+	Instance(Value),
+	Enumerate { inner: Box<ValueIterator>, index: usize },
+	Zip(Vec<ValueIterator>),
+	Take { inner: Box<ValueIterator>, remaining: usize },
+	Generator(Box<Coroutine>),
+	// Lines of a file, read as they're asked for.
+	File(Lines<BufReader<File>>),
+	Shared(Rc<RefCell<ValueIterator>>),
+}
+
+impl ValueIterator {
+	pub fn shared(self) -> Value {
+		Value::Iterator(Rc::new(RefCell::new(self)))
+	}
+
+	pub fn next(&mut self, interpreter: &mut Interpreter) -> Result<Option<Value>, InterpreterResult> {
+		Ok(match self {
+			ValueIterator::List { items, index } => {
+				let item = items.borrow().get(*index).cloned();
+				*index += 1;
+				item
+			}
+			ValueIterator::Chars { chars, index } => {
+				let item = chars.get(*index).map(|c| Value::String(c.to_string()));
+				*index += 1;
+				item
+			}
+			ValueIterator::Range { range, index } => {
+				if *index < range.len() || range.endless() {
+					let item = Value::Number(range.get(*index));
+					*index += 1;
+					Some(item)
+				} else {
+					None
+				}
+			}
+			ValueIterator::Instance(instance) => match interpreter.call_method(instance.clone(), "next", vec![])? {
+				Some(Value::Null) | None => None,
+				Some(item) => Some(item),
+			},
+			ValueIterator::Enumerate { inner, index } => match inner.next(interpreter)? {
+				Some(item) => {
+					let pair = vec![Value::Number(*index as f64), item];
+					*index += 1;
+					Some(Value::list(pair))
+				}
+				None => None,
+			},
+			ValueIterator::Zip(iterators) => {
+				let mut items = Vec::new();
+
+				for iterator in iterators.iter_mut() {
+					match iterator.next(interpreter)? {
+						Some(item) => items.push(item),
+						None => return Ok(None),
+					}
+				}
+
+				Some(Value::list(items))
+			}
+			ValueIterator::Take { inner, remaining } => match remaining {
+				0 => None,
+				_ => {
+					*remaining -= 1;
+					inner.next(interpreter)?
+				}
+			},
+			ValueIterator::Generator(generator) => generator.next(interpreter)?,
+			ValueIterator::File(lines) => match lines.next() {
+				Some(Ok(line)) => Some(Value::String(line)),
+				Some(Err(e)) => return Err(InterpreterResult::Error(format!("Cannot read line: {}.", e))),
+				None => None,
+			},
+			ValueIterator::Shared(iterator) => iterator.borrow_mut().next(interpreter)?,
+		})
+	}
+}
@@ -0,0 +1,531 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+
+use crate::{
+	environment::{Environment, Value},
+	interpreter::InterpreterResult,
+	map::Map,
+	numeric,
+};
+
+// Nesting deeper than this is rejected instead of overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+// Objects become maps with their keys in document order. Numbers become floats, except integers too large for a float
+// to hold exactly, which become big integers.
+pub fn parse(source: &str) -> Result<Value, InterpreterResult> {
+	let mut parser = Parser { chars: source.chars().collect(), position: 0, line: 1, column: 1, depth: 0 };
+
+	parser.skip_whitespace();
+	let value = parser.value()?;
+	parser.skip_whitespace();
+
+	match parser.peek() {
+		None => Ok(value),
+		Some(c) => Err(parser.error(format!("unexpected '{}' after the end of the document", c))),
+	}
+}
+
+struct Parser {
+	chars: Vec<char>,
+	position: usize,
+	line: usize,
+	column: usize,
+	depth: usize,
+}
+
+impl Parser {
+	fn error(&self, message: impl Into<String>) -> InterpreterResult {
+		InterpreterResult::Error(format!("Invalid JSON at line {}, column {}: {}.", self.line, self.column, message.into()))
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.chars.get(self.position).copied()
+	}
+
+	fn next(&mut self) -> Option<char> {
+		let c = self.peek()?;
+
+		self.position += 1;
+
+		if c == '\n' {
+			self.line += 1;
+			self.column = 1;
+		} else {
+			self.column += 1;
+		}
+
+		Some(c)
+	}
+
+	fn expect(&mut self, expected: char) -> Result<(), InterpreterResult> {
+		match self.peek() {
+			Some(c) if c == expected => {
+				self.next();
+				Ok(())
+			}
+			Some(c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+			None => Err(self.error(format!("expected '{}', found the end of the document", expected))),
+		}
+	}
+
+	fn skip_whitespace(&mut self) {
+		while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+			self.next();
+		}
+	}
+
+	fn value(&mut self) -> Result<Value, InterpreterResult> {
+		match self.peek() {
+			Some('{') => self.nested(Self::object),
+			Some('[') => self.nested(Self::array),
+			Some('"') => Ok(Value::String(self.string()?)),
+			Some('-' | '0'..='9') => self.number(),
+			Some('t') => self.literal("true", Value::Bool(true)),
+			Some('f') => self.literal("false", Value::Bool(false)),
+			Some('n') => self.literal("null", Value::Null),
+			Some(c) => Err(self.error(format!("unexpected '{}'", c))),
+			None => Err(self.error("unexpected end of the document")),
+		}
+	}
+
+	fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, InterpreterResult>) -> Result<Value, InterpreterResult> {
+		if self.depth == MAX_DEPTH {
+			return Err(self.error(format!("nested deeper than {} levels", MAX_DEPTH)));
+		}
+
+		self.depth += 1;
+		let value = parse(self);
+		self.depth -= 1;
+
+		value
+	}
+
+	fn literal(&mut self, word: &str, value: Value) -> Result<Value, InterpreterResult> {
+		for expected in word.chars() {
+			if self.peek() != Some(expected) {
+				return Err(self.error(format!("expected '{}'", word)));
+			}
+
+			self.next();
+		}
+
+		Ok(value)
+	}
+
+	fn object(&mut self) -> Result<Value, InterpreterResult> {
+		let mut map = Map::new();
+
+		self.expect('{')?;
+		self.skip_whitespace();
+
+		if self.peek() == Some('}') {
+			self.next();
+			return Ok(Value::map(map));
+		}
+
+		loop {
+			self.skip_whitespace();
+
+			if self.peek() != Some('"') {
+				return Err(self.error("expected a string key"));
+			}
+
+			let key = self.string()?;
+
+			self.skip_whitespace();
+			self.expect(':')?;
+			self.skip_whitespace();
+
+			let value = self.value()?;
+			map.set(key, value);
+
+			self.skip_whitespace();
+
+			match self.peek() {
+				Some(',') => self.next(),
+				Some('}') => {
+					self.next();
+					return Ok(Value::map(map));
+				}
+				_ => return Err(self.error("expected ',' or '}' after an object entry")),
+			};
+		}
+	}
+
+	fn array(&mut self) -> Result<Value, InterpreterResult> {
+		let mut items = Vec::new();
+
+		self.expect('[')?;
+		self.skip_whitespace();
+
+		if self.peek() == Some(']') {
+			self.next();
+			return Ok(Value::list(items));
+		}
+
+		loop {
+			self.skip_whitespace();
+			items.push(self.value()?);
+			self.skip_whitespace();
+
+			match self.peek() {
+				Some(',') => self.next(),
+				Some(']') => {
+					self.next();
+					return Ok(Value::list(items));
+				}
+				_ => return Err(self.error("expected ',' or ']' after an array item")),
+			};
+		}
+	}
+
+	fn string(&mut self) -> Result<String, InterpreterResult> {
+		let mut string = String::new();
+
+		self.expect('"')?;
+
+		loop {
+			match self.next() {
+				Some('"') => return Ok(string),
+				Some('\\') => match self.next() {
+					Some('"') => string.push('"'),
+					Some('\\') => string.push('\\'),
+					Some('/') => string.push('/'),
+					Some('b') => string.push('\u{8}'),
+					Some('f') => string.push('\u{c}'),
+					Some('n') => string.push('\n'),
+					Some('r') => string.push('\r'),
+					Some('t') => string.push('\t'),
+					Some('u') => string.push(self.unicode_escape()?),
+					_ => return Err(self.error("invalid escape sequence")),
+				},
+				Some(c) if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+				Some(c) => string.push(c),
+				None => return Err(self.error("unterminated string")),
+			}
+		}
+	}
+
+	// `\uXXXX`, characters outside the basic plane come as a surrogate pair of two escapes.
+	fn unicode_escape(&mut self) -> Result<char, InterpreterResult> {
+		let high = self.hex()?;
+
+		if !(0xD800..0xDC00).contains(&high) {
+			return char::from_u32(high).ok_or_else(|| self.error("invalid unicode escape"));
+		}
+
+		if self.next() != Some('\\') || self.next() != Some('u') {
+			return Err(self.error("unpaired surrogate in unicode escape"));
+		}
+
+		let low = self.hex()?;
+
+		if !(0xDC00..0xE000).contains(&low) {
+			return Err(self.error("unpaired surrogate in unicode escape"));
+		}
+
+		char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).ok_or_else(|| self.error("invalid unicode escape"))
+	}
+
+	fn hex(&mut self) -> Result<u32, InterpreterResult> {
+		let mut code = 0;
+
+		for _ in 0..4 {
+			match self.next().and_then(|c| c.to_digit(16)) {
+				Some(digit) => code = code * 16 + digit,
+				None => return Err(self.error("expected four hex digits in unicode escape")),
+			}
+		}
+
+		Ok(code)
+	}
+
+	fn number(&mut self) -> Result<Value, InterpreterResult> {
+		let start = self.position;
+		let digits = |parser: &mut Self| {
+			let mut count = 0;
+
+			while matches!(parser.peek(), Some('0'..='9')) {
+				parser.next();
+				count += 1;
+			}
+
+			count
+		};
+
+		if self.peek() == Some('-') {
+			self.next();
+		}
+
+		match self.peek() {
+			Some('0') => {
+				self.next();
+			}
+			Some('1'..='9') => {
+				digits(self);
+			}
+			_ => return Err(self.error("expected a digit")),
+		}
+
+		let mut integer = true;
+
+		if self.peek() == Some('.') {
+			integer = false;
+			self.next();
+
+			if digits(self) == 0 {
+				return Err(self.error("expected a digit after the decimal point"));
+			}
+		}
+
+		if matches!(self.peek(), Some('e' | 'E')) {
+			integer = false;
+			self.next();
+
+			if matches!(self.peek(), Some('+' | '-')) {
+				self.next();
+			}
+
+			if digits(self) == 0 {
+				return Err(self.error("expected a digit in the exponent"));
+			}
+		}
+
+		let number = self.chars[start..self.position].iter().collect::<String>();
+
+		if integer {
+			return match number.parse::<BigInt>() {
+				Ok(n) => Ok(numeric::integer_value(n, true)),
+				Err(..) => Err(self.error(format!("invalid number {}", number))),
+			};
+		}
+
+		match number.parse::<f64>() {
+			Ok(n) if n.is_finite() => Ok(Value::Number(n)),
+			_ => Err(self.error(format!("number {} is out of range", number))),
+		}
+	}
+}
+
+// Struct instances serialize their fields in declaration order, datetimes as RFC 3339 strings, unit enum variants
+// as their name and variants with values as `{"Variant": [values]}`.
+pub fn stringify(value: &Value, pretty: bool) -> Result<String, InterpreterResult> {
+	let mut serializer = Serializer { output: String::new(), pretty, depth: 0, visiting: Vec::new() };
+
+	serializer.value(value)?;
+
+	Ok(serializer.output)
+}
+
+struct Serializer {
+	output: String,
+	pretty: bool,
+	depth: usize,
+	// Lists, maps and instances being written, meeting one of them again means the value contains itself.
+	visiting: Vec<*const ()>,
+}
+
+impl Serializer {
+	fn enter<T>(&mut self, rc: &Rc<T>) -> Result<(), InterpreterResult> {
+		let pointer = Rc::as_ptr(rc) as *const ();
+
+		if self.visiting.contains(&pointer) {
+			return Err(InterpreterResult::Error("Cannot serialize a value that contains itself to JSON.".to_string()));
+		}
+
+		self.visiting.push(pointer);
+
+		Ok(())
+	}
+
+	fn value(&mut self, value: &Value) -> Result<(), InterpreterResult> {
+		match value {
+			Value::Constant(v) => self.value(v)?,
+			Value::Null => self.output.push_str("null"),
+			Value::Bool(b) => self.output.push_str(&b.to_string()),
+			Value::Number(n) => self.number(*n)?,
+			Value::BigInt(n) => self.output.push_str(&n.to_string()),
+			Value::Rational(..) => self.number(value.clone().expect_number("Json.stringify!")?)?,
+			Value::String(s) => self.string(s),
+			Value::DateTime(dt) => self.string(&dt.to_rfc3339()),
+			// ISO 8601, like `PT5400S`.
+			Value::Duration(duration) => self.string(&duration.to_string()),
+			Value::Range(range) => {
+				self.list(&range.values().into_iter().map(Value::Number).collect::<Vec<Value>>())?;
+			}
+			Value::List(items) => {
+				self.enter(items)?;
+				self.list(&items.borrow())?;
+				self.visiting.pop();
+			}
+			Value::Map(map) => {
+				self.enter(map)?;
+				self.object(map.borrow().iter().map(|(key, value)| (key.clone(), value.clone())).collect())?;
+				self.visiting.pop();
+			}
+			Value::StructInstance { environment, definition } => {
+				self.enter(environment)?;
+				self.object(fields(&environment.borrow(), definition))?;
+				self.visiting.pop();
+			}
+			Value::EnumVariant { variant, values, .. } if values.is_empty() => self.string(variant),
+			Value::EnumVariant { variant, values, .. } => self.object(vec![(variant.clone(), Value::list(values.clone()))])?,
+			Value::Function { .. } | Value::NativeFunction { .. } | Value::NativeMethod { .. } => {
+				return Err(InterpreterResult::Error("Cannot serialize a function to JSON.".to_string()))
+			}
+			_ => return Err(InterpreterResult::Error(format!("Cannot serialize a value of type {} to JSON.", value.clone().typestring()))),
+		}
+
+		Ok(())
+	}
+
+	fn number(&mut self, n: f64) -> Result<(), InterpreterResult> {
+		if !n.is_finite() {
+			return Err(InterpreterResult::Error(format!("Cannot serialize {} to JSON.", n)));
+		}
+
+		self.output.push_str(&n.to_string());
+
+		Ok(())
+	}
+
+	fn string(&mut self, s: &str) {
+		self.output.push('"');
+
+		for c in s.chars() {
+			match c {
+				'"' => self.output.push_str("\\\""),
+				'\\' => self.output.push_str("\\\\"),
+				'\n' => self.output.push_str("\\n"),
+				'\r' => self.output.push_str("\\r"),
+				'\t' => self.output.push_str("\\t"),
+				'\u{8}' => self.output.push_str("\\b"),
+				'\u{c}' => self.output.push_str("\\f"),
+				c if (c as u32) < 0x20 => self.output.push_str(&format!("\\u{:04x}", c as u32)),
+				c => self.output.push(c),
+			}
+		}
+
+		self.output.push('"');
+	}
+
+	fn list(&mut self, items: &[Value]) -> Result<(), InterpreterResult> {
+		self.output.push('[');
+
+		if !items.is_empty() {
+			self.depth += 1;
+
+			for (i, item) in items.iter().enumerate() {
+				self.separator(i);
+				self.value(item)?;
+			}
+
+			self.depth -= 1;
+			self.newline();
+		}
+
+		self.output.push(']');
+
+		Ok(())
+	}
+
+	fn object(&mut self, entries: Vec<(String, Value)>) -> Result<(), InterpreterResult> {
+		self.output.push('{');
+
+		if !entries.is_empty() {
+			self.depth += 1;
+
+			for (i, (key, value)) in entries.iter().enumerate() {
+				self.separator(i);
+				self.string(key);
+				self.output.push_str(if self.pretty { ": " } else { ":" });
+				self.value(value)?;
+			}
+
+			self.depth -= 1;
+			self.newline();
+		}
+
+		self.output.push('}');
+
+		Ok(())
+	}
+
+	fn separator(&mut self, index: usize) {
+		if index > 0 {
+			self.output.push(',');
+		}
+
+		self.newline();
+	}
+
+	fn newline(&mut self) {
+		if self.pretty {
+			self.output.push('\n');
+			self.output.push_str(&"  ".repeat(self.depth));
+		}
+	}
+}
+
+// Declared fields first, then any set on the instance afterwards sorted by name. Methods are left out.
+fn fields(environment: &Environment, definition: &Value) -> Vec<(String, Value)> {
+	let declared = match definition {
+		Value::Struct { fields, .. } => fields.iter().map(|field| field.name.clone()).collect::<Vec<String>>(),
+		_ => Vec::new(),
+	};
+
+	let mut extra = environment.entries().into_iter().filter(|(name, _)| !declared.contains(name)).collect::<Vec<(String, Value)>>();
+	extra.sort_by(|(l, _), (r, _)| l.cmp(r));
+
+	declared
+		.iter()
+		.filter_map(|name| environment.get(name.clone()).map(|value| (name.clone(), value)))
+		.chain(extra)
+		.filter(|(_, value)| !value.is_function())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		environment::Value,
+		interpreter::{caught, evaluate},
+	};
+
+	#[test]
+	fn it_parses_numbers_without_losing_precision() {
+		assert_eq!(evaluate(r#"Json.parse!("[1, -2.5, 1e3, 9007199254740991]")"#).unwrap().to_string(), "[1, -2.5, 1000, 9007199254740991]");
+		assert!(matches!(evaluate(r#"Json.parse!("12345678901234567890")"#).unwrap(), Value::BigInt(..)));
+		assert_eq!(evaluate(r#"Json.stringify!(Json.parse!("[12345678901234567890, -98765432109876543210]"))"#).unwrap().to_string(), "[12345678901234567890,-98765432109876543210]");
+		assert_eq!(evaluate(r#"Json.parse!("1e400")"#).unwrap_err().to_string(), "Invalid JSON at line 1, column 6: number 1e400 is out of range.");
+	}
+
+	#[test]
+	fn it_round_trips_values_through_stringify_and_parse() {
+		let source = "create m = Json.parse!(\"{}\")
+m.name = \"Ana Lúcia\"
+m.tags = [1, -2.5, true, null, 12345678901234567890n]
+m.owner = Json.parse!(\"{}\")
+m.owner.admin = false
+m.owner.empty = []
+create text = Json.stringify!(m)
+";
+
+		assert_eq!(evaluate(&format!("{}text", source)).unwrap().to_string(), r#"{"name":"Ana Lúcia","tags":[1,-2.5,true,null,12345678901234567890],"owner":{"admin":false,"empty":[]}}"#);
+		assert_eq!(evaluate(&format!("{}Json.parse!(text) == m", source)).unwrap().to_string(), "true");
+		assert_eq!(evaluate(&format!("{}create again = Json.stringify!(Json.parse!(text))\nagain == text", source)).unwrap().to_string(), "true");
+		assert_eq!(evaluate(&format!("{}create pretty = Json.stringify!(m, true)\nJson.parse!(pretty) == m", source)).unwrap().to_string(), "true");
+	}
+
+	#[test]
+	fn it_refuses_to_serialize_values_that_contain_themselves() {
+		let error = evaluate("create a = [1]\na.push!(a)\nJson.stringify!(a)").unwrap_err();
+
+		assert_eq!(error.to_string(), "Cannot serialize a value that contains itself to JSON.");
+		assert_eq!(caught("create a = [1]\na.push!(a)\nJson.stringify!(a)"), error.to_string());
+		// The same list twice is shared, not cyclic.
+		assert_eq!(evaluate("create a = [1]\nJson.stringify!([a, a])").unwrap().to_string(), "[[1],[1]]");
+	}
+}
@@ -1,13 +1,25 @@
-#![allow(non_snake_case)]
+#![allow(non_snake_case, clippy::result_large_err, clippy::wrong_self_convention, clippy::inherent_to_string, clippy::unit_arg)]
 use clap::{App, Arg};
 use std::fs::read_to_string;
 
 mod ast;
+mod checker;
 mod environment;
+mod event_loop;
+mod gc;
+mod coroutine;
 mod interpreter;
+mod iterator;
+mod json;
+mod map;
+mod numeric;
 mod parser;
+mod random;
+mod range;
 mod stdlib;
 mod token;
+mod worker;
+mod zone;
 
 const VERSION: &str = "0.2-beta";
 
@@ -16,22 +28,39 @@ fn main() {
 		.version(VERSION)
 		.author("Vinicios Lugli <vinicioslugli@gmail.com>")
 		.about("My 'lugli' language interpreter for learning purposes.")
-		.subcommand(App::new("run").about("Run a Lugli file.").version(VERSION).arg(Arg::new("file").required(true)))
+		.subcommand(
+			App::new("run")
+				.about("Run a Lugli file.")
+				.version(VERSION)
+				.trailing_var_arg(true)
+				.arg(Arg::new("file").required(true))
+				.arg(Arg::new("args").multiple_values(true).allow_hyphen_values(true).help("Arguments for the script, read with Application.args?()")),
+		)
 		.get_matches();
 
-	if let Some(ref run) = matches.subcommand_matches("run") {
+	if let Some(run) = matches.subcommand_matches("run") {
 		let file = run.value_of("file").unwrap();
 		let contents = read_to_string(file).unwrap();
 		let path = std::path::PathBuf::from(file);
+		let args = run.values_of("args").map_or_else(Vec::new, |args| args.map(str::to_string).collect());
 		let tokens = token::generate(contents.as_str());
 		match parser::parse(tokens) {
 			Ok(ast) => {
-				match interpreter::interpret(ast, path) {
-					Ok(_) => {}
-					Err(e) => {
-						e.print();
-					}
-				};
+				checker::report(checker::check(&ast));
+
+				// The script runs on its own thread, so deep recursion has the stack `interpreter::STACK_SIZE` gives it.
+				let script = std::thread::Builder::new().stack_size(interpreter::STACK_SIZE).spawn(move || {
+					match interpreter::interpret(ast, path, args) {
+						Ok(_) => {}
+						Err(e) => {
+							e.print();
+						}
+					};
+				});
+
+				if script.unwrap().join().is_err() {
+					std::process::exit(101);
+				}
 			}
 			Err(e) => {
 				e.print();
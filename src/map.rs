@@ -0,0 +1,84 @@
+use hashbrown::HashMap;
+use std::iter::FromIterator;
+
+use crate::environment::Value;
+
+// String keyed map behind `Value::Map`. Entries keep the order they were first inserted in, so parsed JSON objects
+// print and serialize back in their original order.
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+	entries: Vec<(String, Value)>,
+	positions: HashMap<String, usize>,
+}
+
+impl Map {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn get(&self, key: &str) -> Option<&Value> {
+		self.positions.get(key).map(|&position| &self.entries[position].1)
+	}
+
+	pub fn contains(&self, key: &str) -> bool {
+		self.positions.contains_key(key)
+	}
+
+	// Replaces the value of an existing key in place, new keys go at the end.
+	pub fn set(&mut self, key: String, value: Value) {
+		match self.positions.get(&key) {
+			Some(&position) => self.entries[position].1 = value,
+			None => {
+				self.positions.insert(key.clone(), self.entries.len());
+				self.entries.push((key, value));
+			}
+		}
+	}
+
+	pub fn remove(&mut self, key: &str) -> Option<Value> {
+		let position = self.positions.remove(key)?;
+		let (_, value) = self.entries.remove(position);
+
+		for (key, _) in &self.entries[position..] {
+			*self.positions.get_mut(key).unwrap() -= 1;
+		}
+
+		Some(value)
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &(String, Value)> {
+		self.entries.iter()
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &String> {
+		self.entries.iter().map(|(key, _)| key)
+	}
+
+	pub fn values(&self) -> impl Iterator<Item = &Value> {
+		self.entries.iter().map(|(_, value)| value)
+	}
+
+	pub fn into_values(self) -> Vec<Value> {
+		self.entries.into_iter().map(|(_, value)| value).collect()
+	}
+}
+
+impl FromIterator<(String, Value)> for Map {
+	fn from_iter<I: IntoIterator<Item = (String, Value)>>(entries: I) -> Self {
+		let mut map = Map::new();
+
+		for (key, value) in entries {
+			map.set(key, value);
+		}
+
+		map
+	}
+}
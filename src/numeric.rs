@@ -0,0 +1,351 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+
+use crate::{ast::Op, environment::Value, interpreter::InterpreterResult};
+
+// Largest integer an f64 holds exactly, integer results past it are promoted to `Value::BigInt`.
+pub const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+const WITNESSES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+pub fn is_numeric(value: &Value) -> bool {
+	match value {
+		Value::Number(..) | Value::BigInt(..) | Value::Rational(..) => true,
+		Value::Constant(v) => is_numeric(v),
+		_ => false,
+	}
+}
+
+// The value as a float, `None` for anything that isn't a number, even values `Value::to_number` can coerce.
+pub fn to_float(value: &Value) -> Option<f64> {
+	if is_numeric(value) {
+		value.clone().to_number()
+	} else {
+		None
+	}
+}
+
+pub fn parse_decimal(source: &str) -> Option<BigRational> {
+	let source = source.trim();
+	let (negative, source) = match source.strip_prefix('-') {
+		Some(rest) => (true, rest),
+		None => (false, source),
+	};
+
+	let (integer, fraction) = match source.split_once('.') {
+		Some((integer, fraction)) => (integer, fraction),
+		None => (source, ""),
+	};
+
+	if integer.is_empty() && fraction.is_empty() {
+		return None;
+	}
+
+	let digits = format!("{}{}", integer, fraction);
+	let mut numerator: BigInt = digits.parse().ok()?;
+	let denominator = num_traits::pow(BigInt::from(10), fraction.len());
+
+	if negative {
+		numerator = -numerator;
+	}
+
+	Some(BigRational::new(numerator, denominator))
+}
+
+pub fn to_rational(value: &Value) -> Option<BigRational> {
+	match value {
+		Value::Number(n) if n.is_finite() => parse_decimal(&n.to_string()),
+		Value::BigInt(n) => Some(BigRational::from_integer(n.clone())),
+		Value::Rational(r) => Some(r.clone()),
+		Value::Constant(v) => to_rational(v),
+		_ => None,
+	}
+}
+
+pub fn to_integer(value: &Value) -> Option<BigInt> {
+	match value {
+		Value::Number(n) if n.is_finite() && n.fract() == 0.0 => BigInt::from_f64(*n),
+		Value::BigInt(n) => Some(n.clone()),
+		Value::Rational(r) if r.is_integer() => Some(r.to_integer()),
+		Value::Constant(v) => to_integer(v),
+		_ => None,
+	}
+}
+
+// Wraps an exact integer result, keeping it as a plain `Value::Number` when the operands were plain numbers and it still fits.
+pub fn integer_value(n: BigInt, prefer_number: bool) -> Value {
+	match n.to_f64() {
+		Some(f) if prefer_number && f.abs() <= MAX_SAFE_INTEGER => Value::Number(f),
+		_ => Value::BigInt(n),
+	}
+}
+
+// Formats a float the way scripts print it, `NaN`, `Infinity` and `-Infinity` for the non-finite values.
+pub fn format(n: f64) -> String {
+	if n.is_nan() {
+		"NaN".to_string()
+	} else if n.is_infinite() {
+		if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+	} else {
+		n.to_string()
+	}
+}
+
+// `abs`, `floor` and `ceil` keep big integers and rationals exact, rounding a rational gives a big integer.
+pub fn abs(value: Value) -> Value {
+	match value {
+		Value::BigInt(n) => Value::BigInt(n.abs()),
+		Value::Rational(r) => Value::Rational(r.abs()),
+		Value::Constant(v) => abs(*v),
+		v => Value::Number(v.to_number().map_or(f64::NAN, f64::abs)),
+	}
+}
+
+pub fn floor(value: Value) -> Value {
+	match value {
+		Value::BigInt(n) => Value::BigInt(n),
+		Value::Rational(r) => Value::BigInt(r.floor().to_integer()),
+		Value::Constant(v) => floor(*v),
+		v => Value::Number(v.to_number().map_or(f64::NAN, f64::floor)),
+	}
+}
+
+pub fn ceil(value: Value) -> Value {
+	match value {
+		Value::BigInt(n) => Value::BigInt(n),
+		Value::Rational(r) => Value::BigInt(r.ceil().to_integer()),
+		Value::Constant(v) => ceil(*v),
+		v => Value::Number(v.to_number().map_or(f64::NAN, f64::ceil)),
+	}
+}
+
+pub fn negate(value: Value) -> Value {
+	match value {
+		Value::BigInt(n) => Value::BigInt(-n),
+		Value::Rational(r) => Value::Rational(-r),
+		Value::Constant(v) => negate(*v),
+		v => Value::Number(v.to_number().map_or(f64::NAN, |n| -n)),
+	}
+}
+
+fn is_integral(n: f64) -> bool {
+	n.is_finite() && n.fract() == 0.0
+}
+
+pub fn arithmetic(left: Value, op: Op, right: Value) -> Result<Value, InterpreterResult> {
+	let left = left.unwrap_constant();
+	let right = right.unwrap_constant();
+
+	match (&left, &right) {
+		(Value::Number(l), Value::Number(r)) => {
+			let result = float_arithmetic(*l, &op, *r);
+
+			let overflowed = is_integral(*l)
+				&& is_integral(*r) && matches!(op, Op::Add | Op::Subtract | Op::Multiply | Op::Pow)
+				&& !(op == Op::Pow && *r < 0.0)
+				&& (result.is_infinite() || result.abs() > MAX_SAFE_INTEGER);
+
+			if overflowed {
+				integer_arithmetic(BigInt::from_f64(*l).unwrap(), op, BigInt::from_f64(*r).unwrap())
+			} else {
+				Ok(Value::Number(result))
+			}
+		}
+		(Value::Rational(..), _) | (_, Value::Rational(..)) => match (to_rational(&left), to_rational(&right)) {
+			(Some(l), Some(r)) => rational_arithmetic(l, op, r),
+			_ => float_fallback(&left, op, &right),
+		},
+		_ => match (to_integer(&left), to_integer(&right)) {
+			(Some(l), Some(r)) => integer_arithmetic(l, op, r),
+			_ => match (to_rational(&left), to_rational(&right)) {
+				(Some(l), Some(r)) => rational_arithmetic(l, op, r),
+				_ => float_fallback(&left, op, &right),
+			},
+		},
+	}
+}
+
+// Values that are neither integers nor rationals, like `NaN` or infinities, are computed as floats.
+fn float_fallback(left: &Value, op: Op, right: &Value) -> Result<Value, InterpreterResult> {
+	match (left.clone().to_number(), right.clone().to_number()) {
+		(Some(l), Some(r)) => Ok(Value::Number(float_arithmetic(l, &op, r))),
+		_ => Err(InterpreterResult::TypeError(op.symbol().to_string(), left.clone().typestring(), right.clone().typestring())),
+	}
+}
+
+fn float_arithmetic(l: f64, op: &Op, r: f64) -> f64 {
+	match op {
+		Op::Add => l + r,
+		Op::Subtract => l - r,
+		Op::Multiply => l * r,
+		Op::Divide => l / r,
+		Op::Modulo => l % r,
+		Op::Pow => l.powf(r),
+		_ => unreachable!("{:?}", op),
+	}
+}
+
+fn integer_arithmetic(l: BigInt, op: Op, r: BigInt) -> Result<Value, InterpreterResult> {
+	Ok(match op {
+		Op::Add => Value::BigInt(l + r),
+		Op::Subtract => Value::BigInt(l - r),
+		Op::Multiply => Value::BigInt(l * r),
+		Op::Divide | Op::Modulo if r.is_zero() => return Err(InterpreterResult::Error("Division by zero.".to_string())),
+		Op::Divide if l.is_multiple_of(&r) => Value::BigInt(l / r),
+		Op::Divide => Value::Rational(BigRational::new(l, r)),
+		Op::Modulo => Value::BigInt(l % r),
+		Op::Pow => match r.to_u32() {
+			Some(exponent) => Value::BigInt(num_traits::pow(l, exponent as usize)),
+			None => return rational_arithmetic(BigRational::from_integer(l), op, BigRational::from_integer(r)),
+		},
+		_ => unreachable!("{:?}", op),
+	})
+}
+
+fn rational_arithmetic(l: BigRational, op: Op, r: BigRational) -> Result<Value, InterpreterResult> {
+	Ok(Value::Rational(match op {
+		Op::Add => l + r,
+		Op::Subtract => l - r,
+		Op::Multiply => l * r,
+		Op::Divide | Op::Modulo if r.is_zero() => return Err(InterpreterResult::Error("Division by zero.".to_string())),
+		Op::Divide => l / r,
+		Op::Modulo => l % r,
+		Op::Pow => match r.to_integer().to_i32() {
+			Some(exponent) if r.is_integer() => {
+				if l.is_zero() && exponent < 0 {
+					return Err(InterpreterResult::Error("Division by zero.".to_string()));
+				}
+
+				l.pow(exponent)
+			}
+			_ => return Ok(Value::Number(l.to_f64().unwrap_or(f64::NAN).powf(r.to_f64().unwrap_or(f64::NAN)))),
+		},
+		_ => unreachable!("{:?}", op),
+	}))
+}
+
+pub fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+	match (left, right) {
+		(Value::Constant(l), r) => compare(l, r),
+		(l, Value::Constant(r)) => compare(l, r),
+		(Value::Number(l), Value::Number(r)) => l.partial_cmp(r),
+		(l, r) => match (to_rational(l), to_rational(r)) {
+			(Some(l), Some(r)) => Some(l.cmp(&r)),
+			_ => left.clone().to_number()?.partial_cmp(&right.clone().to_number()?),
+		},
+	}
+}
+
+pub fn equals(left: &Value, right: &Value) -> bool {
+	compare(left, right) == Some(Ordering::Equal)
+}
+
+pub fn is_prime(n: &BigInt) -> bool {
+	if n < &BigInt::from(2) {
+		return false;
+	}
+
+	for witness in WITNESSES.iter() {
+		let witness = BigInt::from(*witness);
+
+		if n == &witness {
+			return true;
+		}
+
+		if (n % &witness).is_zero() {
+			return false;
+		}
+	}
+
+	let n_minus_one: BigInt = n - 1;
+	let mut d = n_minus_one.clone();
+	let mut s = 0;
+
+	while d.is_even() {
+		d >>= 1;
+		s += 1;
+	}
+
+	// Deterministic for every n below 3.3 * 10^24, a strong probable-prime test beyond that.
+	'witness: for witness in WITNESSES.iter() {
+		let mut x = BigInt::from(*witness).modpow(&d, n);
+
+		if x.is_one() || x == n_minus_one {
+			continue;
+		}
+
+		for _ in 1..s {
+			x = (&x * &x) % n;
+
+			if x == n_minus_one {
+				continue 'witness;
+			}
+		}
+
+		return false;
+	}
+
+	true
+}
+
+pub fn factorial(n: &BigInt) -> Option<BigInt> {
+	if n.is_negative() {
+		return None;
+	}
+
+	let mut result = BigInt::one();
+	let mut i = BigInt::from(2);
+
+	while &i <= n {
+		result *= &i;
+		i += 1;
+	}
+
+	Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		environment::Value,
+		interpreter::{caught, evaluate},
+	};
+
+	#[test]
+	fn it_promotes_integers_that_overflow_to_big_integers() {
+		assert_eq!(evaluate("create product = 1\nfor i in 1..=30 {\n\tproduct = product * i\n}\nproduct").unwrap().to_string(), "265252859812191058636308480000000");
+		assert_eq!(evaluate("2 ** 100").unwrap().to_string(), "1267650600228229401496703205376");
+		assert!(matches!(evaluate("2 ** 100").unwrap(), Value::BigInt(..)));
+		assert!(matches!(evaluate("2 ** 10").unwrap(), Value::Number(..)));
+		assert_eq!(evaluate("100n * 100n").unwrap().to_string(), "10000");
+		assert_eq!(evaluate("create n = 30\nn.factorial!()").unwrap().to_string(), "265252859812191058636308480000000");
+	}
+
+	#[test]
+	fn it_keeps_suffixed_decimals_exact() {
+		assert_eq!(evaluate("0.1 + 0.2").unwrap().to_string(), "0.30000000000000004");
+		assert_eq!(evaluate("0.1r + 0.2r").unwrap().to_string(), "3/10");
+		assert_eq!(evaluate("1/3r + 1/6r").unwrap().to_string(), "1/2");
+		assert_eq!(evaluate("0.1r + 0.2r == 0.3r").unwrap().to_string(), "true");
+	}
+
+	#[test]
+	fn it_negates_only_numbers() {
+		assert_eq!(evaluate("create n = 2 ** 100\ncreate m = -n\nm").unwrap().to_string(), "-1267650600228229401496703205376");
+		assert_eq!(evaluate("-1/3r").unwrap().to_string(), "-1/3");
+		assert_eq!(caught("Console.println!(-[1])"), "TypeError: unsupported operand types for -: number and list.");
+	}
+
+	#[test]
+	fn it_raises_type_errors_where_numbers_are_expected() {
+		assert_eq!(Value::list(vec![]).to_number(), None);
+		assert_eq!(caught("create l = [1, 2]\nl.slice!([0])"), "List.slice!() expects a number for start, got list.");
+		assert_eq!(caught("\"ab\".padStart!([3])"), "String.padStart! expects a number for length, got list.");
+		assert_eq!(caught("Duration.seconds!(1) * [2]"), "TypeError: unsupported operand types for *: duration and list.");
+		assert_eq!(caught("range!(1, [2])"), "TypeError: range! expects number, got list.");
+		assert_eq!(evaluate("[1] in 1..3").unwrap().to_string(), "false");
+	}
+}
@@ -1,5 +1,8 @@
 use colored::*;
 use hashbrown::HashMap;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
 use std::slice::Iter;
 use thiserror::Error;
 
@@ -34,6 +37,7 @@ enum Precedence {
 	AndOr,
 	LessThanGreaterThan,
 	Equals,
+	Range,
 	Sum,
 	Product,
 	Pow,
@@ -50,6 +54,7 @@ impl Precedence {
 			Token::Percent => Self::Modulo,
 			Token::Pow => Self::Pow,
 			Token::Equals | Token::NotEquals => Self::Equals,
+			Token::DotDot | Token::DotDotEquals => Self::Range,
 			Token::And | Token::Or | Token::In | Token::NotIn => Self::AndOr,
 			Token::Assign => Self::Assign,
 			Token::PlusAssign => Self::PlusAssign,
@@ -79,15 +84,19 @@ impl<'p> Parser<'p> {
 
 	fn parse_statement(&mut self) -> Result<Statement, ParseError> {
 		match self.current {
-			Token::Fn => self.parse_fn(true),
+			Token::Fn | Token::Async => self.parse_fn(true),
 			Token::Struct => self.parse_struct(),
+			Token::Enum => self.parse_enum(),
+			Token::Interface => self.parse_interface(),
 			Token::Create => self.parse_create(),
 			Token::Const => self.parse_const(),
 			Token::If => self.parse_if(),
 			Token::For => self.parse_for(),
 			Token::While => self.parse_while(),
 			Token::Loop => self.parse_loop(),
+			Token::Try => self.parse_try(),
 			Token::Return => self.parse_return(),
+			Token::Yield => self.parse_yield(),
 			Token::Break => self.parse_break(),
 			Token::Continue => self.parse_continue(),
 			_ => Ok(Statement::Expression { expression: self.parse_expression(Precedence::Lowest)? }),
@@ -101,17 +110,27 @@ impl<'p> Parser<'p> {
 			self.expect_token_and_read(Token::LeftParen)?;
 			let index = self.expect_identifier_and_read()?;
 			self.expect_token_and_read(Token::Comma)?;
-			let tuple = (Some(index.into()), self.expect_identifier_and_read()?.into());
+			let tuple = (Some(index.into()), self.parse_binding()?);
 			self.expect_token_and_read(Token::RightParen)?;
 			tuple
 		} else {
-			(None, self.expect_identifier_and_read()?.into())
+			(None, self.parse_binding()?)
 		};
 
 		self.expect_token_and_read(Token::In)?;
 
 		let iterable = self.parse_expression(Precedence::Statement)?;
-		let then = self.parse_block()?;
+		let mut then = self.parse_block()?;
+
+		// Destructuring loop variables bind a hidden name, the body starts by unpacking it.
+		let value = match value {
+			Pattern::Binding(name) => name,
+			pattern => {
+				let name = String::from("$item");
+				then.insert(0, Statement::DestructuringDeclaration { pattern, initial: Expression::Identifier(name.clone()), constant: false });
+				name
+			}
+		};
 
 		Ok(Statement::For { index, value, iterable, then })
 	}
@@ -130,6 +149,14 @@ impl<'p> Parser<'p> {
 				self.expect_token_and_read(Token::Number(0.0))?;
 				Expression::Number(n)
 			}
+			Token::BigInt(n) => {
+				self.expect_token_and_read(Token::BigInt(BigInt::zero()))?;
+				Expression::BigInt(n)
+			}
+			Token::Rational(r) => {
+				self.expect_token_and_read(Token::Rational(BigRational::zero()))?;
+				Expression::Rational(r)
+			}
 			Token::True => {
 				self.expect_token_and_read(Token::True)?;
 				Expression::Bool(true)
@@ -142,19 +169,30 @@ impl<'p> Parser<'p> {
 				self.expect_identifier_and_read()?;
 				Expression::Identifier(s)
 			}
-			Token::Fn => {
-				let (params, body) = match self.parse_fn(false)? {
-					Statement::FunctionDeclaration { params, body, .. } => (params, body),
+			Token::Fn | Token::Async => {
+				let (params, body, return_type, is_async) = match self.parse_fn(false)? {
+					Statement::FunctionDeclaration { params, body, return_type, is_async, .. } => (params, body, return_type, is_async),
 					_ => return Err(ParseError::Unreachable),
 				};
 
-				Expression::Closure(params, body)
+				Expression::Closure(params, body, return_type, is_async)
+			}
+			Token::Await => {
+				self.expect_token_and_read(Token::Await)?;
+
+				Expression::Await(self.parse_expression(Precedence::Prefix)?.boxed())
+			}
+			Token::Spawn => {
+				self.expect_token_and_read(Token::Spawn)?;
+
+				Expression::Spawn(self.parse_expression(Precedence::Prefix)?.boxed())
 			}
 			t @ Token::Minus | t @ Token::Bang => {
 				self.expect_token_and_read(t.clone())?;
 
 				Expression::Prefix(Op::token(t), self.parse_expression(Precedence::Prefix)?.boxed())
 			}
+			Token::Match => self.parse_match()?,
 			Token::LeftBracket => {
 				self.expect_token_and_read(Token::LeftBracket)?;
 
@@ -306,6 +344,21 @@ impl<'p> Parser<'p> {
 
 				Some(Expression::Infix(Box::new(left), Op::token(token), Box::new(right)))
 			}
+			Token::DotDot | Token::DotDotEquals => {
+				let inclusive = self.current_is(Token::DotDotEquals);
+
+				self.read();
+
+				let end = self.parse_expression(Precedence::Range)?;
+				let step = if self.current_is(Token::Step) {
+					self.expect_token_and_read(Token::Step)?;
+					Some(self.parse_expression(Precedence::Range)?.boxed())
+				} else {
+					None
+				};
+
+				Some(Expression::Range { start: left.boxed(), end: end.boxed(), step, inclusive })
+			}
 			Token::Assign => {
 				self.read();
 
@@ -435,6 +488,19 @@ impl<'p> Parser<'p> {
 		Ok(Statement::Loop { body: then })
 	}
 
+	fn parse_try(&mut self) -> Result<Statement, ParseError> {
+		self.expect_token_and_read(Token::Try)?;
+
+		let body = self.parse_block()?;
+
+		self.expect_token_and_read(Token::Catch)?;
+
+		let name = if self.current_is(Token::Identifier("".to_string())) { Some(self.expect_identifier_and_read()?.into()) } else { None };
+		let handler = self.parse_block()?;
+
+		Ok(Statement::Try { body, name, handler })
+	}
+
 	fn parse_return(&mut self) -> Result<Statement, ParseError> {
 		self.expect_token_and_read(Token::Return)?;
 
@@ -445,6 +511,12 @@ impl<'p> Parser<'p> {
 		}
 	}
 
+	fn parse_yield(&mut self) -> Result<Statement, ParseError> {
+		self.expect_token_and_read(Token::Yield)?;
+
+		Ok(Statement::Yield { value: self.parse_expression(Precedence::Lowest)? })
+	}
+
 	fn parse_break(&mut self) -> Result<Statement, ParseError> {
 		self.expect_token_and_read(Token::Break)?;
 
@@ -460,7 +532,12 @@ impl<'p> Parser<'p> {
 	fn parse_create(&mut self) -> Result<Statement, ParseError> {
 		self.expect_token_and_read(Token::Create)?;
 
+		if self.current_is(Token::LeftBracket) || self.current_is(Token::LeftBrace) {
+			return self.parse_destructuring(false);
+		}
+
 		let name: Identifier = self.expect_identifier_and_read()?.into();
+		let kind = self.parse_annotation()?;
 		let initial: Option<Expression> = if self.current_is(Token::Assign) {
 			self.expect_token_and_read(Token::Assign)?;
 
@@ -469,18 +546,91 @@ impl<'p> Parser<'p> {
 			None
 		};
 
-		Ok(Statement::CreateDeclaration { name, initial })
+		Ok(Statement::CreateDeclaration { name, kind, initial })
 	}
 
 	fn parse_const(&mut self) -> Result<Statement, ParseError> {
 		self.expect_token_and_read(Token::Const)?;
 
+		if self.current_is(Token::LeftBracket) || self.current_is(Token::LeftBrace) {
+			return self.parse_destructuring(true);
+		}
+
 		let name: Identifier = self.expect_identifier_and_read()?.into();
+		let kind = self.parse_annotation()?;
+
 		self.expect_token_and_read(Token::Assign)?;
 
 		let initial = self.parse_expression(Precedence::Lowest)?;
 
-		Ok(Statement::ConstDeclaration { name, initial })
+		Ok(Statement::ConstDeclaration { name, kind, initial })
+	}
+
+	// `: type` after a name, annotations are optional everywhere.
+	fn parse_annotation(&mut self) -> Result<Option<Type>, ParseError> {
+		if !self.current_is(Token::Colon) {
+			return Ok(None);
+		}
+
+		self.expect_token_and_read(Token::Colon)?;
+
+		Ok(Some(self.parse_type()?))
+	}
+
+	fn parse_type(&mut self) -> Result<Type, ParseError> {
+		let mut types: Vec<Type> = Vec::new();
+
+		loop {
+			let mut name: Identifier = match self.current.clone() {
+				Token::Null => {
+					self.read();
+					"null".to_string()
+				}
+				_ => self.expect_identifier_and_read()?.into(),
+			};
+
+			if self.current_is(Token::Dot) {
+				self.expect_token_and_read(Token::Dot)?;
+				name = format!("{}.{}", name, String::from(self.expect_identifier_and_read()?));
+			}
+
+			if name == "list" && self.current_is(Token::LessThan) {
+				self.expect_token_and_read(Token::LessThan)?;
+				let item = self.parse_type()?;
+				self.expect_token_and_read(Token::GreaterThan)?;
+
+				types.push(Type::List(Box::new(item)));
+			} else {
+				types.push(Type::Named(name));
+			}
+
+			if !self.current_is(Token::Pipe) {
+				break;
+			}
+
+			self.read();
+		}
+
+		Ok(if types.len() == 1 { types.remove(0) } else { Type::Union(types) })
+	}
+
+	fn parse_destructuring(&mut self, constant: bool) -> Result<Statement, ParseError> {
+		let pattern = self.parse_pattern()?;
+
+		self.expect_token_and_read(Token::Assign)?;
+
+		let initial = self.parse_expression(Precedence::Lowest)?;
+
+		Ok(Statement::DestructuringDeclaration { pattern, initial, constant })
+	}
+
+	// A plain name or a list/struct destructuring pattern, as used by `for` heads and parameters.
+	fn parse_binding(&mut self) -> Result<Pattern, ParseError> {
+		if self.current_is(Token::LeftBracket) || self.current_is(Token::LeftBrace) {
+			self.parse_pattern()
+		} else {
+			Ok(Pattern::Binding(self.expect_identifier_and_read()?.into()))
+		}
 	}
 
 	fn parse_struct(&mut self) -> Result<Statement, ParseError> {
@@ -488,43 +638,160 @@ impl<'p> Parser<'p> {
 
 		let name: Identifier = self.expect_identifier_and_read()?.into();
 
+		// `struct Admin : User, Printable` names a parent struct and/or the interfaces it implements.
+		let mut extends: Vec<Identifier> = Vec::new();
+
+		if self.current_is(Token::Colon) {
+			self.expect_token_and_read(Token::Colon)?;
+
+			loop {
+				extends.push(self.expect_identifier_and_read()?.into());
+
+				if !self.current_is(Token::Comma) {
+					break;
+				}
+
+				self.read();
+			}
+		}
+
 		self.expect_token_and_read(Token::LeftBrace)?;
 
 		let mut fields: Vec<Parameter> = Vec::new();
 
 		while !self.current_is(Token::RightBrace) {
-			if self.current_is(Token::Fn) {
+			if self.current_is(Token::Fn) || self.current_is(Token::Async) {
 				let function = self.parse_fn(true)?;
-				if let Statement::FunctionDeclaration { name, params, body } = function {
-					let closure = Expression::Closure(params.clone(), body);
+				if let Statement::FunctionDeclaration { name, params, body, return_type, is_async } = function {
+					let closure = Expression::Closure(params.clone(), body, return_type, is_async);
 
-					fields.push(Parameter { name, initial: Some(closure) });
+					fields.push(Parameter::new(name, Some(closure)));
 				} else {
 					return Err(ParseError::UnexpectedToken(self.current.clone()));
 				}
 			} else {
 				let field: String = self.expect_identifier_and_read()?.into();
+				let kind = self.parse_annotation()?;
 
 				match self.current.clone() {
-					Token::Comma | Token::RightBrace | Token::Fn | Token::Identifier(..) => fields.push(Parameter { name: field, initial: None }),
+					Token::Comma | Token::RightBrace | Token::Fn | Token::Async | Token::Identifier(..) => fields.push(Parameter { name: field, initial: None, kind }),
 					Token::Assign => {
 						self.expect_token_and_read(Token::Assign)?;
 
 						let initial = self.parse_expression(Precedence::Lowest)?;
 
-						fields.push(Parameter { name: field, initial: Some(initial) });
+						fields.push(Parameter { name: field, initial: Some(initial), kind });
 					}
-					_ => unreachable!(),
+					_ => return Err(ParseError::UnexpectedToken(self.current.clone())),
+				}
+
+				if self.current_is(Token::Comma) {
+					self.read();
 				}
 			}
 		}
 
 		self.expect_token_and_read(Token::RightBrace)?;
 
-		Ok(Statement::StructDeclaration { name, fields })
+		Ok(Statement::StructDeclaration { name, extends, fields })
+	}
+
+	fn parse_interface(&mut self) -> Result<Statement, ParseError> {
+		self.expect_token_and_read(Token::Interface)?;
+
+		let name: Identifier = self.expect_identifier_and_read()?.into();
+
+		self.expect_token_and_read(Token::LeftBrace)?;
+
+		let mut methods: Vec<(Identifier, Vec<Parameter>)> = Vec::new();
+
+		while !self.current_is(Token::RightBrace) {
+			self.expect_token_and_read(Token::Fn)?;
+
+			let method: Identifier = self.expect_identifier_and_read()?.into();
+
+			self.expect_token_and_read(Token::LeftParen)?;
+
+			let mut params: Vec<Parameter> = Vec::new();
+
+			while !self.current_is(Token::RightParen) {
+				params.push(Parameter::new(self.expect_identifier_and_read()?.into(), None));
+
+				if self.current_is(Token::Comma) {
+					self.read();
+				}
+			}
+
+			self.expect_token_and_read(Token::RightParen)?;
+
+			methods.push((method, params));
+		}
+
+		self.expect_token_and_read(Token::RightBrace)?;
+
+		Ok(Statement::InterfaceDeclaration { name, methods })
+	}
+
+	fn parse_enum(&mut self) -> Result<Statement, ParseError> {
+		self.expect_token_and_read(Token::Enum)?;
+
+		let name: Identifier = self.expect_identifier_and_read()?.into();
+
+		self.expect_token_and_read(Token::LeftBrace)?;
+
+		let mut variants: Vec<Variant> = Vec::new();
+		let mut methods: Vec<Parameter> = Vec::new();
+
+		while !self.current_is(Token::RightBrace) {
+			if self.current_is(Token::Fn) || self.current_is(Token::Async) {
+				if let Statement::FunctionDeclaration { name, params, body, return_type, is_async } = self.parse_fn(true)? {
+					methods.push(Parameter::new(name, Some(Expression::Closure(params, body, return_type, is_async))));
+				}
+
+				continue;
+			}
+
+			let variant: Identifier = self.expect_identifier_and_read()?.into();
+
+			let fields = if self.current_is(Token::LeftParen) {
+				self.expect_token_and_read(Token::LeftParen)?;
+
+				let mut fields: Vec<Identifier> = Vec::new();
+
+				while !self.current_is(Token::RightParen) {
+					fields.push(self.expect_identifier_and_read()?.into());
+
+					if self.current_is(Token::Comma) {
+						self.read();
+					}
+				}
+
+				self.expect_token_and_read(Token::RightParen)?;
+
+				Some(fields)
+			} else {
+				None
+			};
+
+			variants.push(Variant { name: variant, fields });
+
+			if self.current_is(Token::Comma) {
+				self.read();
+			}
+		}
+
+		self.expect_token_and_read(Token::RightBrace)?;
+
+		Ok(Statement::EnumDeclaration { name, variants, methods })
 	}
 
 	fn parse_fn(&mut self, with_identifier: bool) -> Result<Statement, ParseError> {
+		let asynchronous = self.current_is(Token::Async);
+
+		if asynchronous {
+			self.expect_token_and_read(Token::Async)?;
+		}
+
 		self.expect_token_and_read(Token::Fn)?;
 
 		let name: Identifier = if with_identifier { self.expect_identifier_and_read()?.into() } else { String::from("<Closure>") };
@@ -532,30 +799,221 @@ impl<'p> Parser<'p> {
 		self.expect_token_and_read(Token::LeftParen)?;
 
 		let mut params: Vec<Parameter> = Vec::new();
+		let mut destructuring: Vec<Statement> = Vec::new();
 
 		while !self.current_is(Token::RightParen) {
 			if self.current_is(Token::Comma) {
 				self.expect_token_and_read(Token::Comma)?;
 			}
 
-			let param: String = self.expect_identifier_and_read()?.into();
+			// Destructured parameters get a hidden name, the body starts by unpacking it.
+			let param: String = match self.parse_binding()? {
+				Pattern::Binding(name) => name,
+				pattern => {
+					let name = format!("${}", params.len());
+					destructuring.push(Statement::DestructuringDeclaration { pattern, initial: Expression::Identifier(name.clone()), constant: false });
+					name
+				}
+			};
+
+			let kind = self.parse_annotation()?;
 
 			if self.current_is(Token::Assign) {
 				self.expect_token_and_read(Token::Assign)?;
 
 				let initial = self.parse_expression(Precedence::Lowest)?;
 
-				params.push(Parameter { name: param, initial: Some(initial) });
+				params.push(Parameter { name: param, initial: Some(initial), kind });
 			} else {
-				params.push(Parameter { name: param, initial: None });
+				params.push(Parameter { name: param, initial: None, kind });
 			}
 		}
 
 		self.expect_token_and_read(Token::RightParen)?;
 
-		let body: Vec<Statement> = self.parse_block()?;
+		let return_type = if self.current_is(Token::Arrow) {
+			self.expect_token_and_read(Token::Arrow)?;
+			Some(self.parse_type()?)
+		} else {
+			None
+		};
+
+		let body: Vec<Statement> = destructuring.into_iter().chain(self.parse_block()?).collect();
+
+		Ok(Statement::FunctionDeclaration { name, params, body, return_type, is_async: asynchronous })
+	}
+
+	fn parse_match(&mut self) -> Result<Expression, ParseError> {
+		self.expect_token_and_read(Token::Match)?;
+
+		let subject = self.parse_expression(Precedence::Statement)?;
+
+		self.expect_token_and_read(Token::LeftBrace)?;
+
+		let mut arms: Vec<MatchArm> = Vec::new();
+
+		while !self.current_is(Token::RightBrace) {
+			let pattern = self.parse_pattern()?;
+
+			let guard = if self.current_is(Token::If) {
+				self.expect_token_and_read(Token::If)?;
+				Some(self.parse_expression(Precedence::Lowest)?)
+			} else {
+				None
+			};
+
+			self.expect_token_and_read(Token::FatArrow)?;
+
+			let body = if self.current_is(Token::LeftBrace) {
+				self.parse_block()?
+			} else {
+				vec![Statement::Expression { expression: self.parse_expression(Precedence::Lowest)? }]
+			};
+
+			arms.push(MatchArm { pattern, guard, body });
+
+			if self.current_is(Token::Comma) {
+				self.read();
+			}
+		}
+
+		self.expect_token_and_read(Token::RightBrace)?;
+
+		Ok(Expression::Match(subject.boxed(), arms))
+	}
+
+	fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+		Ok(match self.current.clone() {
+			Token::Identifier(name) if name == "_" => {
+				self.read();
+				Pattern::Wildcard
+			}
+			Token::Identifier(name) => {
+				self.read();
+
+				if self.current_is(Token::Dot) {
+					self.expect_token_and_read(Token::Dot)?;
+
+					let variant: Identifier = self.expect_identifier_and_read()?.into();
+
+					let fields = if self.current_is(Token::LeftParen) {
+						self.expect_token_and_read(Token::LeftParen)?;
+
+						let mut fields: Vec<Pattern> = Vec::new();
+
+						while !self.current_is(Token::RightParen) {
+							fields.push(self.parse_pattern()?);
 
-		Ok(Statement::FunctionDeclaration { name, params, body })
+							if self.current_is(Token::Comma) {
+								self.read();
+							}
+						}
+
+						self.expect_token_and_read(Token::RightParen)?;
+
+						Some(fields)
+					} else {
+						None
+					};
+
+					return Ok(Pattern::Variant { name, variant, fields });
+				}
+
+				if !self.current_is(Token::LeftBrace) {
+					return Ok(Pattern::Binding(name));
+				}
+
+				Pattern::Struct { name: Some(name), fields: self.parse_field_patterns()? }
+			}
+			Token::LeftBrace => Pattern::Struct { name: None, fields: self.parse_field_patterns()? },
+			Token::LeftBracket => {
+				self.expect_token_and_read(Token::LeftBracket)?;
+
+				let mut items: Vec<Pattern> = Vec::new();
+				let mut rest: Option<Option<Identifier>> = None;
+
+				while !self.current_is(Token::RightBracket) {
+					if self.current_is(Token::DotDot) {
+						self.expect_token_and_read(Token::DotDot)?;
+
+						rest = match self.current.clone() {
+							Token::Identifier(name) => {
+								self.read();
+								Some(Some(name))
+							}
+							_ => Some(None),
+						};
+					} else if rest.is_some() {
+						return Err(ParseError::UnexpectedToken(self.current.clone()));
+					} else {
+						items.push(self.parse_pattern_with_default()?);
+					}
+
+					if self.current_is(Token::Comma) {
+						self.read();
+					}
+				}
+
+				self.expect_token_and_read(Token::RightBracket)?;
+
+				Pattern::List { items, rest }
+			}
+			Token::Number(..)
+			| Token::BigInt(..)
+			| Token::Rational(..)
+			| Token::String(..)
+			| Token::True
+			| Token::False
+			| Token::Null
+			| Token::Minus => match self.parse_expression(Precedence::Equals)? {
+				range @ Expression::Range { .. } => Pattern::Range(range),
+				literal => Pattern::Literal(literal),
+			},
+			_ => return Err(ParseError::UnexpectedToken(self.current.clone())),
+		})
+	}
+
+	fn parse_field_patterns(&mut self) -> Result<Vec<(Identifier, Pattern)>, ParseError> {
+		self.expect_token_and_read(Token::LeftBrace)?;
+
+		let mut fields: Vec<(Identifier, Pattern)> = Vec::new();
+
+		while !self.current_is(Token::RightBrace) {
+			let field: Identifier = self.expect_identifier_and_read()?.into();
+
+			let pattern = if self.current_is(Token::Colon) {
+				self.expect_token_and_read(Token::Colon)?;
+				self.parse_pattern_with_default()?
+			} else {
+				self.parse_default(Pattern::Binding(field.clone()))?
+			};
+
+			fields.push((field, pattern));
+
+			if self.current_is(Token::Comma) {
+				self.read();
+			}
+		}
+
+		self.expect_token_and_read(Token::RightBrace)?;
+
+		Ok(fields)
+	}
+
+	fn parse_pattern_with_default(&mut self) -> Result<Pattern, ParseError> {
+		let pattern = self.parse_pattern()?;
+
+		self.parse_default(pattern)
+	}
+
+	fn parse_default(&mut self, pattern: Pattern) -> Result<Pattern, ParseError> {
+		if !self.current_is(Token::Assign) {
+			return Ok(pattern);
+		}
+
+		self.expect_token_and_read(Token::Assign)?;
+
+		Ok(Pattern::Default(Box::new(pattern), self.parse_expression(Precedence::Lowest)?))
 	}
 
 	fn parse_block(&mut self) -> Result<Block, ParseError> {
@@ -639,16 +1097,28 @@ mod tests {
 		parse(tokens).unwrap()
 	}
 
+	fn call_arguments(expressions: Vec<Expression>) -> CallArguments {
+		let mut arguments = CallArguments::new();
+
+		for expression in expressions {
+			arguments.add_argument(Argument::new(None, expression));
+		}
+
+		arguments
+	}
+
 	#[test]
 	fn it_can_parse_fn_declarations() {
-		assert_eq!(lex_and_parse("fn name() {}"), vec![Statement::FunctionDeclaration { name: String::from("name"), body: vec![], params: vec![] }]);
+		assert_eq!(lex_and_parse("fn name() {}"), vec![Statement::FunctionDeclaration { name: String::from("name"), body: vec![], params: vec![], return_type: None, is_async: false }]);
 
 		assert_eq!(
 			lex_and_parse("fn name(person) {}"),
 			vec![Statement::FunctionDeclaration {
 				name: String::from("name"),
 				body: vec![],
-				params: vec![Parameter { name: String::from("person") }]
+				params: vec![Parameter::new(String::from("person"), None)],
+				return_type: None,
+				is_async: false,
 			}]
 		);
 
@@ -657,7 +1127,9 @@ mod tests {
 			vec![Statement::FunctionDeclaration {
 				name: String::from("say_hello"),
 				body: vec![],
-				params: vec![Parameter { name: String::from("name") }, Parameter { name: String::from("separator") }]
+				params: vec![Parameter::new(String::from("name"), None), Parameter::new(String::from("separator"), None)],
+				return_type: None,
+				is_async: false,
 			}]
 		);
 
@@ -671,24 +1143,26 @@ mod tests {
 			),
 			vec![Statement::FunctionDeclaration {
 				name: String::from("say_hello"),
-				body: vec![Statement::CreateDeclaration { name: String::from("name"), initial: Expression::Bool(true).some() }],
-				params: vec![]
+				body: vec![Statement::CreateDeclaration { name: String::from("name"), kind: None, initial: Expression::Bool(true).some() }],
+				params: vec![],
+				return_type: None,
+				is_async: false,
 			}]
 		)
 	}
 
 	#[test]
 	fn it_can_parse_create_declarations_and_const() {
-		assert_eq!(lex_and_parse("create name"), vec![Statement::CreateDeclaration { name: String::from("name"), initial: None }]);
+		assert_eq!(lex_and_parse("create name"), vec![Statement::CreateDeclaration { name: String::from("name"), kind: None, initial: None }]);
 
 		assert_eq!(
 			lex_and_parse("create bool = true"),
-			vec![Statement::CreateDeclaration { name: String::from("bool"), initial: Expression::Bool(true).some() }]
+			vec![Statement::CreateDeclaration { name: String::from("bool"), kind: None, initial: Expression::Bool(true).some() }]
 		);
 
 		assert_eq!(
 			lex_and_parse("const bool = false"),
-			vec![Statement::ConstDeclaration { name: String::from("bool"), initial: Expression::Bool(false) }]
+			vec![Statement::ConstDeclaration { name: String::from("bool"), kind: None, initial: Expression::Bool(false) }]
 		);
 	}
 
@@ -706,6 +1180,230 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn it_can_parse_suffixed_number_literals() {
+		assert_eq!(
+			lex_and_parse("100n 1/3r"),
+			vec![
+				Statement::Expression { expression: Expression::BigInt(BigInt::from(100)) },
+				Statement::Expression {
+					expression: Expression::Infix(
+						Box::new(Expression::Number(1.0)),
+						Op::Divide,
+						Box::new(Expression::Rational(BigRational::from_integer(BigInt::from(3))))
+					)
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_ranges() {
+		assert_eq!(
+			lex_and_parse("0..n + 1 0..=10 step 2"),
+			vec![
+				Statement::Expression {
+					expression: Expression::Range {
+						start: Box::new(Expression::Number(0.0)),
+						end: Box::new(Expression::Infix(
+							Box::new(Expression::Identifier("n".to_owned())),
+							Op::Add,
+							Box::new(Expression::Number(1.0))
+						)),
+						step: None,
+						inclusive: false,
+					}
+				},
+				Statement::Expression {
+					expression: Expression::Range {
+						start: Box::new(Expression::Number(0.0)),
+						end: Box::new(Expression::Number(10.0)),
+						step: Some(Box::new(Expression::Number(2.0))),
+						inclusive: true,
+					}
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_match_expressions() {
+		assert_eq!(
+			lex_and_parse("match value { 1..10 => 1, [first, ..rest] if first => first, Person { name } => name, _ => 0 }"),
+			vec![Statement::Expression {
+				expression: Expression::Match(
+					Box::new(Expression::Identifier("value".to_owned())),
+					vec![
+						MatchArm {
+							pattern: Pattern::Range(Expression::Range {
+								start: Box::new(Expression::Number(1.0)),
+								end: Box::new(Expression::Number(10.0)),
+								step: None,
+								inclusive: false,
+							}),
+							guard: None,
+							body: vec![Statement::Expression { expression: Expression::Number(1.0) }],
+						},
+						MatchArm {
+							pattern: Pattern::List { items: vec![Pattern::Binding("first".to_owned())], rest: Some(Some("rest".to_owned())) },
+							guard: Some(Expression::Identifier("first".to_owned())),
+							body: vec![Statement::Expression { expression: Expression::Identifier("first".to_owned()) }],
+						},
+						MatchArm {
+							pattern: Pattern::Struct {
+								name: Some("Person".to_owned()),
+								fields: vec![("name".to_owned(), Pattern::Binding("name".to_owned()))]
+							},
+							guard: None,
+							body: vec![Statement::Expression { expression: Expression::Identifier("name".to_owned()) }],
+						},
+						MatchArm {
+							pattern: Pattern::Wildcard,
+							guard: None,
+							body: vec![Statement::Expression { expression: Expression::Number(0.0) }]
+						},
+					]
+				)
+			}]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_destructuring() {
+		assert_eq!(
+			lex_and_parse("create [a, b = 2, ..rest] = pair fn name({ email }) {}"),
+			vec![
+				Statement::DestructuringDeclaration {
+					pattern: Pattern::List {
+						items: vec![
+							Pattern::Binding("a".to_owned()),
+							Pattern::Default(Box::new(Pattern::Binding("b".to_owned())), Expression::Number(2.0))
+						],
+						rest: Some(Some("rest".to_owned()))
+					},
+					initial: Expression::Identifier("pair".to_owned()),
+					constant: false,
+				},
+				Statement::FunctionDeclaration {
+					name: "name".to_owned(),
+					params: vec![Parameter::new("$0".to_owned(), None)],
+					body: vec![Statement::DestructuringDeclaration {
+						pattern: Pattern::Struct { name: None, fields: vec![("email".to_owned(), Pattern::Binding("email".to_owned()))] },
+						initial: Expression::Identifier("$0".to_owned()),
+						constant: false,
+					}],
+					return_type: None,
+					is_async: false,
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_enum_declarations() {
+		assert_eq!(
+			lex_and_parse("enum Shape { Circle(radius), Empty }"),
+			vec![Statement::EnumDeclaration {
+				name: "Shape".to_owned(),
+				variants: vec![
+					Variant { name: "Circle".to_owned(), fields: Some(vec!["radius".to_owned()]) },
+					Variant { name: "Empty".to_owned(), fields: None },
+				],
+				methods: vec![],
+			}]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_inheritance_and_interfaces() {
+		assert_eq!(
+			lex_and_parse("interface Named { fn name(this) } struct Admin : User, Named {}"),
+			vec![
+				Statement::InterfaceDeclaration {
+					name: "Named".to_owned(),
+					methods: vec![("name".to_owned(), vec![Parameter::new("this".to_owned(), None)])]
+				},
+				Statement::StructDeclaration { name: "Admin".to_owned(), extends: vec!["User".to_owned(), "Named".to_owned()], fields: vec![] },
+			]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_type_annotations() {
+		let number = Some(Type::Named("number".to_owned()));
+
+		assert_eq!(
+			lex_and_parse("fn area(w: number) -> number {}"),
+			vec![Statement::FunctionDeclaration {
+				name: "area".to_owned(),
+				params: vec![Parameter { name: "w".to_owned(), initial: None, kind: number.clone() }],
+				body: vec![],
+				return_type: number.clone(),
+				is_async: false,
+			}]
+		);
+
+		assert_eq!(
+			lex_and_parse("create xs: list<number> | null = null"),
+			vec![Statement::CreateDeclaration {
+				name: "xs".to_owned(),
+				kind: Some(Type::Union(vec![Type::List(Box::new(Type::Named("number".to_owned()))), Type::Named("null".to_owned())])),
+				initial: Some(Expression::Null),
+			}]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_yield_statements() {
+		assert_eq!(
+			lex_and_parse("fn numbers() { yield 1 }"),
+			vec![Statement::FunctionDeclaration {
+				name: "numbers".to_owned(),
+				params: vec![],
+				body: vec![Statement::Yield { value: Expression::Number(1.0) }],
+				return_type: None,
+				is_async: false,
+			}]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_try_statements() {
+		assert_eq!(
+			lex_and_parse("try { fail() } catch error { retry() } try {} catch {}"),
+			vec![
+				Statement::Try {
+					body: vec![Statement::Expression { expression: Expression::Call(Expression::Identifier("fail".to_owned()).boxed(), call_arguments(vec![])) }],
+					name: Some("error".to_owned()),
+					handler: vec![Statement::Expression { expression: Expression::Call(Expression::Identifier("retry".to_owned()).boxed(), call_arguments(vec![])) }],
+				},
+				Statement::Try { body: vec![], name: None, handler: vec![] },
+			]
+		);
+	}
+
+	#[test]
+	fn it_can_parse_async_functions() {
+		assert_eq!(
+			lex_and_parse("async fn main() { create x = await spawn work() }"),
+			vec![Statement::FunctionDeclaration {
+				name: "main".to_owned(),
+				params: vec![],
+				body: vec![
+					Statement::CreateDeclaration {
+						name: "x".to_owned(),
+						kind: None,
+						initial: Some(Expression::Await(
+							Expression::Spawn(Expression::Call(Expression::Identifier("work".to_owned()).boxed(), call_arguments(vec![])).boxed()).boxed()
+						)),
+					},
+				],
+				return_type: None,
+				is_async: true,
+			}]
+		);
+	}
+
 	#[test]
 	fn it_can_parse_mathematical_operations() {
 		assert_eq!(
@@ -767,13 +1465,13 @@ mod tests {
 	fn it_can_parse_call_expressions() {
 		assert_eq!(
 			lex_and_parse("hello()"),
-			vec![Statement::Expression { expression: Expression::Call(Box::new(Expression::Identifier("hello".to_owned())), vec![]) }]
+			vec![Statement::Expression { expression: Expression::Call(Box::new(Expression::Identifier("hello".to_owned())), call_arguments(vec![])) }]
 		);
 
 		assert_eq!(
 			lex_and_parse("hello(true)"),
 			vec![Statement::Expression {
-				expression: Expression::Call(Box::new(Expression::Identifier("hello".to_owned())), vec![Expression::Bool(true)])
+				expression: Expression::Call(Box::new(Expression::Identifier("hello".to_owned())), call_arguments(vec![Expression::Bool(true)]))
 			}]
 		);
 
@@ -782,7 +1480,7 @@ mod tests {
 			vec![Statement::Expression {
 				expression: Expression::Call(
 					Box::new(Expression::Identifier("hello".to_owned())),
-					vec![Expression::Bool(true), Expression::Number(1234.0)]
+					call_arguments(vec![Expression::Bool(true), Expression::Number(1234.0)])
 				)
 			}]
 		);
@@ -808,7 +1506,7 @@ mod tests {
 			vec![Statement::If {
 				condition: ConditionBlock {
 					expression: Expression::Bool(true),
-					then: vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) }]
+					then: vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) }]
 				},
 				others_conditions: None,
 				otherwise: None
@@ -826,10 +1524,10 @@ mod tests {
 			vec![Statement::If {
 				condition: ConditionBlock {
 					expression: Expression::Bool(false),
-					then: vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) },]
+					then: vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) },]
 				},
 				others_conditions: None,
-				otherwise: Some(vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(2.0)) },])
+				otherwise: Some(vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(2.0)) },])
 			}]
 		);
 
@@ -846,13 +1544,13 @@ mod tests {
 			vec![Statement::If {
 				condition: ConditionBlock {
 					expression: Expression::Bool(false),
-					then: vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(3.0)) },]
+					then: vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(3.0)) },]
 				},
 				others_conditions: Some(vec![ConditionBlock {
 					expression: Expression::Bool(true),
-					then: vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(6.0)) },]
+					then: vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(6.0)) },]
 				}]),
-				otherwise: Some(vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(9.0)) },])
+				otherwise: Some(vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(9.0)) },])
 			}]
 		);
 	}
@@ -873,7 +1571,7 @@ mod tests {
 			vec![Statement::While {
 				condition: ConditionBlock {
 					expression: Expression::Bool(true),
-					then: vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) }]
+					then: vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) }]
 				}
 			}]
 		);
@@ -890,7 +1588,7 @@ mod tests {
 					expression: Expression::Bool(true),
 					then: vec![
 						Statement::Break,
-						Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) },
+						Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) },
 					]
 				},
 			}]
@@ -902,7 +1600,7 @@ mod tests {
 					if true {
 						continue
 					}
-					-- never touch this create number
+					# never touch this create number
 					create number = 1
 				}"
 			),
@@ -915,7 +1613,7 @@ mod tests {
 							others_conditions: None,
 							otherwise: None
 						},
-						Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) },
+						Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) },
 					]
 				},
 			}]
@@ -933,7 +1631,7 @@ mod tests {
 				}"
 			),
 			vec![Statement::Loop {
-				body: vec![Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) }]
+				body: vec![Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) }]
 			}]
 		);
 
@@ -945,7 +1643,7 @@ mod tests {
 				}"
 			),
 			vec![Statement::Loop {
-				body: vec![Statement::Break, Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) },]
+				body: vec![Statement::Break, Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) },]
 			}]
 		);
 
@@ -955,7 +1653,7 @@ mod tests {
 					if true {
 						continue
 					}
-					-- never touch this create number
+					# never touch this create number
 					create number = 1
 				}"
 			),
@@ -966,7 +1664,7 @@ mod tests {
 						others_conditions: None,
 						otherwise: None
 					},
-					Statement::CreateDeclaration { name: String::from("number"), initial: Some(Expression::Number(1.0)) },
+					Statement::CreateDeclaration { name: String::from("number"), kind: None, initial: Some(Expression::Number(1.0)) },
 				]
 			}]
 		);
@@ -982,7 +1680,8 @@ mod tests {
 			),
 			vec![Statement::StructDeclaration {
 				name: String::from("Point"),
-				fields: vec![Parameter { name: String::from("x") }, Parameter { name: String::from("y") }]
+				extends: vec![],
+				fields: vec![Parameter::new(String::from("x"), None), Parameter::new(String::from("y"), None)]
 			}]
 		);
 
@@ -1004,16 +1703,20 @@ mod tests {
 			vec![
 				Statement::StructDeclaration {
 					name: "Person".to_owned(),
-					fields: vec![Parameter { name: "name".to_owned() }, Parameter { name: "email".to_owned() }]
+					extends: vec![],
+					fields: vec![Parameter::new("name".to_owned(), None), Parameter::new("email".to_owned(), None)]
 				},
 				Statement::Expression {
-					expression: Expression::Assign(
-						Box::new(Expression::Get(Box::new(Expression::Identifier("Person".to_owned())), "new".to_owned())),
+					expression: Expression::SetProperty(
+						Box::new(Expression::Identifier("Person".to_owned())),
+						"new".to_owned(),
 						Box::new(Expression::Closure(
-							vec![Parameter { name: "name".to_owned() }, Parameter { name: "email".to_owned() }],
+							vec![Parameter::new("name".to_owned(), None), Parameter::new("email".to_owned(), None)],
 							vec![Statement::Return {
 								value: Expression::Struct(Box::new(Expression::Identifier("Person".to_owned())), struct_fields)
-							}]
+							}],
+							None,
+							false
 						))
 					)
 				}
@@ -0,0 +1,127 @@
+use std::{
+	sync::atomic::{AtomicU64, Ordering},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+// Counts the generators started from the clock, so workers started at the same instant still get different streams.
+static STARTED: AtomicU64 = AtomicU64::new(0);
+
+// xoshiro256** seeded through SplitMix64. Implemented here rather than pulled from a crate so that `Random.seed!()`
+// replays the same sequence on every platform and every release.
+#[derive(Debug, Clone)]
+pub struct Random {
+	state: [u64; 4],
+}
+
+impl Random {
+	pub fn new(seed: u64) -> Self {
+		let mut seed = seed;
+		let mut next = || {
+			seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+			let mut z = seed;
+			z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+			z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+			z ^ (z >> 31)
+		};
+
+		Self { state: [next(), next(), next(), next()] }
+	}
+
+	// Unseeded generators start from the clock, each interpreter gets its own.
+	pub fn from_time() -> Self {
+		let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64);
+
+		let started = STARTED.fetch_add(1, Ordering::Relaxed);
+
+		Self::new(nanos ^ (std::process::id() as u64).rotate_left(32) ^ started.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+		let t = self.state[1] << 17;
+
+		self.state[2] ^= self.state[0];
+		self.state[3] ^= self.state[1];
+		self.state[1] ^= self.state[2];
+		self.state[0] ^= self.state[3];
+		self.state[2] ^= t;
+		self.state[3] = self.state[3].rotate_left(45);
+
+		result
+	}
+
+	// Uniform in `[0, 1)` with 53 bits of precision.
+	pub fn float(&mut self) -> f64 {
+		(self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+	}
+
+	// Uniform in `0..bound`, rejecting the values that would bias the low end.
+	pub fn below(&mut self, bound: u64) -> u64 {
+		if bound == 0 {
+			return 0;
+		}
+
+		let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+
+		loop {
+			let value = self.next_u64();
+
+			if value <= zone {
+				return value % bound;
+			}
+		}
+	}
+
+	// Fisher-Yates.
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			items.swap(i, self.below(i as u64 + 1) as usize);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::interpreter::{caught, evaluate};
+
+	#[test]
+	fn it_replays_seeded_sequences() {
+		let mut random = Random::new(42);
+		let first = (0..3).map(|_| random.next_u64()).collect::<Vec<u64>>();
+
+		let mut again = Random::new(42);
+
+		assert_eq!((0..3).map(|_| again.next_u64()).collect::<Vec<u64>>(), first);
+		assert_ne!(Random::new(43).next_u64(), first[0]);
+	}
+
+	#[test]
+	fn it_stays_within_bounds() {
+		let mut random = Random::new(7);
+
+		for _ in 0..1000 {
+			assert!(random.below(6) < 6);
+			assert!((0.0..1.0).contains(&random.float()));
+		}
+	}
+
+	#[test]
+	fn it_replays_scripts_after_seeding() {
+		let rolls = "Random.seed!(42)\ncreate rolls = [Random.int!(1, 6), Random.int!(1, 6), Random.int!(1, 6)]\nrolls";
+
+		// Pinned, seeded sequences must not change between platforms or releases.
+		assert_eq!(evaluate(rolls).unwrap().to_string(), "[1, 1, 6]");
+		assert_eq!(evaluate("Random.seed!(1)\nRandom.choice!([\"a\", \"b\", \"c\"])").unwrap().to_string(), "b");
+		assert_eq!(evaluate("Random.seed!(1)\ncreate l = [1, 2, 3, 4]\nRandom.shuffle!(l)\nl").unwrap().to_string(), "[3, 1, 4, 2]");
+		assert_eq!(evaluate("Random.seed!(1)\nRandom.sample!([1, 2, 3, 4], 2)").unwrap().to_string(), "[3, 1]");
+	}
+
+	#[test]
+	fn it_rejects_impossible_requests() {
+		assert_eq!(caught("Random.int!(5, 1)"), "int! expects min to be at most max, got 5 and 1.");
+		assert_eq!(caught("Random.choice!([])"), "choice! cannot pick from an empty list.");
+		assert_eq!(caught("Random.sample!([1], 3)"), "sample! cannot pick 3 items from a list of 1.");
+	}
+}
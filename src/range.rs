@@ -0,0 +1,133 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::{environment::Value, iterator::ValueIterator};
+
+// `start..end`, `start..=end` and `start..end step n`. Ranges are lazy, items are only produced while iterating.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+	pub start: f64,
+	pub end: f64,
+	pub step: f64,
+	pub inclusive: bool,
+}
+
+impl Range {
+	pub fn new(start: f64, end: f64, step: f64, inclusive: bool) -> Self {
+		Self { start, end, step, inclusive }
+	}
+
+	pub fn iter(&self) -> ValueIterator {
+		ValueIterator::Range { range: self.clone(), index: 0 }
+	}
+
+	pub fn len(&self) -> usize {
+		let span = steps(self.end - self.start, self.step);
+
+		if span < 0.0 || !span.is_finite() {
+			return 0;
+		}
+
+		if self.inclusive || span.fract() != 0.0 {
+			span.floor() as usize + 1
+		} else {
+			span as usize
+		}
+	}
+
+	pub fn last(&self) -> Option<f64> {
+		match self.len() {
+			0 => None,
+			len => Some(self.start + (len - 1) as f64 * self.step),
+		}
+	}
+
+	pub fn contains(&self, n: f64) -> bool {
+		let offset = steps(n - self.start, self.step);
+
+		offset.fract() == 0.0 && offset >= 0.0 && offset < self.len() as f64
+	}
+
+	// The same items in the opposite order, `0..10 step 3` becomes `9..=0 step -3`.
+	pub fn reverse(&self) -> Range {
+		match self.last() {
+			Some(last) => Range::new(last, self.start, -self.step, true),
+			None => Range::new(self.start, self.start, -self.step, false),
+		}
+	}
+
+	// `0..Math.INFINITY` and the like, which a loop can run through until it breaks.
+	pub fn endless(&self) -> bool {
+		self.end.is_infinite() && self.end.signum() == self.step.signum() && self.start.is_finite()
+	}
+
+	// Items are computed from their index rather than by adding up the step, so rounding errors don't accumulate.
+	pub fn get(&self, index: usize) -> f64 {
+		self.start + index as f64 * self.step
+	}
+
+	pub fn values(&self) -> Vec<f64> {
+		(0..self.len()).map(|i| self.get(i)).collect()
+	}
+
+	pub fn to_list(&self) -> Value {
+		Value::list(self.values().into_iter().map(Value::Number).collect())
+	}
+
+	// Picks the items of a list or string at the indexes of the range, indexes past the end are skipped.
+	pub fn slice<T: Clone>(&self, items: &[T]) -> Vec<T> {
+		self.values().into_iter().filter(|i| *i >= 0.0).filter_map(|i| items.get(i as usize).cloned()).collect()
+	}
+}
+
+// How many steps fit in `span`. A count within rounding error of a whole number is that number, so `0.3` is the
+// third step of `0..1 step 0.1` even though `0.3 / 0.1` is `2.9999999999999996`.
+fn steps(span: f64, step: f64) -> f64 {
+	let steps = span / step;
+	let nearest = steps.round();
+
+	if (steps - nearest).abs() < 1e-9 {
+		nearest
+	} else {
+		steps
+	}
+}
+
+impl Display for Range {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		let operator = if self.inclusive { "..=" } else { ".." };
+
+		if self.step == 1.0 {
+			write!(f, "{}{}{}", self.start, operator, self.end)
+		} else {
+			write!(f, "{}{}{} step {}", self.start, operator, self.end, self.step)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::interpreter::evaluate;
+
+	#[test]
+	fn it_agrees_on_fractional_steps() {
+		let range = Range::new(0.0, 1.0, 0.1, false);
+
+		assert_eq!(range.len(), 10);
+		assert!(range.contains(0.3));
+		assert!(!range.contains(1.0));
+		assert!(!range.contains(0.35));
+		assert_eq!(Range::new(0.0, 0.3, 0.1, true).len(), 4);
+
+		let source = "create count = 0\nfor i in 0..1 step 0.1 {\n\tcount += 1\n}\ncreate range = 0..1 step 0.1\ncreate items = range.toList!()\ncreate result = [count, items.len?()]\nresult";
+
+		assert_eq!(evaluate(source).unwrap().to_string(), "[10, 10]");
+	}
+
+	#[test]
+	fn it_iterates_endless_ranges_lazily() {
+		let source = "create items = []\nfor i in 0..Math.INFINITY step 2 {\n\tif i > 6 {\n\t\tbreak\n\t}\n\titems.push!(i)\n}\nitems";
+
+		assert_eq!(evaluate(source).unwrap().to_string(), "[0, 2, 4, 6]");
+	}
+}
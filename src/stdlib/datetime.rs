@@ -3,16 +3,21 @@ use crate::environment::NativeMethodCallback;
 pub struct DateTimeObject;
 
 impl DateTimeObject {
-	pub fn get_method(name: String) -> NativeMethodCallback {
-		match name.as_str() {
+	pub fn get_method(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
 			"format!" => methods::datetime_format,
 			"strweekday?" => methods::datetime_strweekday,
-			_ => panic!("Undefined method: `{}` for DateTime object", name),
-		}
+			"addDays!" => methods::datetime_add_days,
+			"startOfDay!" => methods::datetime_start_of_day,
+			"toTimestamp?" => methods::datetime_to_timestamp,
+			"toZone!" => methods::datetime_to_zone,
+			"zone?" => methods::datetime_zone,
+			_ => return None,
+		})
 	}
 
-	pub fn getter_property(name: String) -> NativeMethodCallback {
-		match name.as_str() {
+	pub fn getter_property(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
 			"nanoseconds" => getters::datetime_nanoseconds,
 			"seconds" => getters::datetime_seconds,
 			"minutes" => getters::datetime_minutes,
@@ -21,12 +26,12 @@ impl DateTimeObject {
 			"weeks" => getters::datetime_weeks,
 			"months" => getters::datetime_months,
 			"years" => getters::datetime_years,
-			_ => panic!("Undefined property: `{}` for DateTime object", name),
-		}
+			_ => return None,
+		})
 	}
 
-	pub fn setter_property(name: String) -> NativeMethodCallback {
-		match name.as_str() {
+	pub fn setter_property(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
 			"nanoseconds" => setters::datetime_nanoseconds,
 			"seconds" => setters::datetime_seconds,
 			"minutes" => setters::datetime_minutes,
@@ -34,8 +39,8 @@ impl DateTimeObject {
 			"days" => setters::datetime_days,
 			"months" => setters::datetime_months,
 			"years" => setters::datetime_years,
-			_ => panic!("Undefined property: `{}` for DateTime object", name),
-		}
+			_ => return None,
+		})
 	}
 }
 
@@ -44,25 +49,79 @@ mod methods {
 		ast::ArgumentValues,
 		environment::Value,
 		interpreter::{Interpreter, InterpreterResult},
+		numeric,
+		zone::{self, Zone},
 	};
-	use chrono::Datelike;
+	use chrono::{Datelike, Duration, NaiveTime};
 
 	pub fn datetime_format(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.format!", 1, &args, false);
+		super::arity("DateTime.format!", 1, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 		let format = args.get_from_name_or_index("format".to_string(), 0).unwrap().to_string();
 
 		Ok(Value::String(datetime.format(&format).to_string()))
 	}
 
 	pub fn datetime_strweekday(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.strWeekday?", 0, &args, false);
+		super::arity("DateTime.strWeekday?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::String(datetime.weekday().to_string()))
 	}
+
+	// Whole days move the calendar date and keep the wall clock time, even across a daylight saving change, the
+	// fraction of a day left is added as a duration. Negative days go back.
+	pub fn datetime_add_days(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		super::arity("DateTime.addDays!", 1, &args, false)?;
+
+		let datetime = context.to_datetime()?;
+		let days = crate::stdlib::number_argument("DateTime.addDays!", &args, "days", 0)?;
+		let out_of_range = || InterpreterResult::Error(format!("Adding {} days to {} is out of range.", numeric::format(days), datetime));
+
+		let whole = Duration::try_days(days.trunc() as i64).ok_or_else(out_of_range)?;
+		let fraction = crate::stdlib::duration::from_seconds(days.fract() * 86_400.0)?;
+		let local = datetime.naive_local().checked_add_signed(whole).ok_or_else(out_of_range)?;
+
+		zone::resolve(&datetime.timezone(), local).and_then(|moved| moved.checked_add_signed(fraction)).map(Value::DateTime).ok_or_else(out_of_range)
+	}
+
+	// Midnight of the same day in the datetime's own zone.
+	pub fn datetime_start_of_day(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		super::arity("DateTime.startOfDay!", 0, &args, false)?;
+
+		let datetime = context.to_datetime()?;
+		let midnight = datetime.date_naive().and_time(NaiveTime::MIN);
+
+		match zone::resolve(&datetime.timezone(), midnight) {
+			Some(result) => Ok(Value::DateTime(result)),
+			None => Err(InterpreterResult::Error(format!("{} has no start of day in its time zone.", datetime))),
+		}
+	}
+
+	// Same instant seen from another zone, see `Zone::parse` for the accepted names.
+	pub fn datetime_to_zone(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		super::arity("DateTime.toZone!", 1, &args, false)?;
+
+		let datetime = context.to_datetime()?;
+		let zone = Zone::parse(&args.get_from_name_or_index("zone".to_string(), 0).unwrap().to_string()).map_err(InterpreterResult::Error)?;
+
+		Ok(Value::DateTime(datetime.with_timezone(&zone)))
+	}
+
+	pub fn datetime_zone(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		super::arity("DateTime.zone?", 0, &args, false)?;
+
+		Ok(Value::String(context.to_datetime()?.timezone().name()))
+	}
+
+	// Seconds since the Unix epoch, with milliseconds as the fraction.
+	pub fn datetime_to_timestamp(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		super::arity("DateTime.toTimestamp?", 0, &args, false)?;
+
+		Ok(Value::Number(context.to_datetime()?.timestamp_millis() as f64 / 1000.0))
+	}
 }
 
 mod getters {
@@ -74,65 +133,65 @@ mod getters {
 	use chrono::{Datelike, Timelike};
 
 	pub fn datetime_hours(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.hour?", 0, &args, false);
+		super::arity("DateTime.hour?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.hour() as f64))
 	}
 
 	pub fn datetime_minutes(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.minute?", 0, &args, false);
+		super::arity("DateTime.minute?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.minute() as f64))
 	}
 
 	pub fn datetime_seconds(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.seconds", 0, &args, false);
+		super::arity("DateTime.seconds", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.second() as f64))
 	}
 
 	pub fn datetime_nanoseconds(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.nanosecond?", 0, &args, false);
+		super::arity("DateTime.nanosecond?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.nanosecond() as f64))
 	}
 
 	pub fn datetime_years(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.year?", 0, &args, false);
+		super::arity("DateTime.year?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.year() as f64))
 	}
 
 	pub fn datetime_months(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.month?", 0, &args, false);
+		super::arity("DateTime.month?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.month() as f64))
 	}
 
 	pub fn datetime_days(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.day?", 0, &args, false);
+		super::arity("DateTime.day?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.day() as f64))
 	}
 
 	pub fn datetime_weeks(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.weekday?", 0, &args, false);
+		super::arity("DateTime.weekday?", 0, &args, false)?;
 
-		let datetime = context.to_datetime();
+		let datetime = context.to_datetime()?;
 
 		Ok(Value::Number(datetime.weekday().num_days_from_sunday() as f64))
 	}
@@ -148,10 +207,10 @@ mod setters {
 	};
 
 	pub fn datetime_seconds(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.seconds", 1, &args, true);
+		super::arity("DateTime.seconds", 1, &args, true)?;
 
-		let datetime = context.to_datetime();
-		let seconds = args.get_from_name_or_index("seconds".to_string(), 0).unwrap().to_number();
+		let datetime = context.to_datetime()?;
+		let seconds = crate::stdlib::number_argument("DateTime.seconds", &args, "seconds", 0)?;
 		if let Some(result) = datetime.with_second(seconds as u32) {
 			Ok(Value::DateTime(result))
 		} else {
@@ -160,10 +219,10 @@ mod setters {
 	}
 
 	pub fn datetime_minutes(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.minutes", 1, &args, true);
+		super::arity("DateTime.minutes", 1, &args, true)?;
 
-		let datetime = context.to_datetime();
-		let minutes = args.get_from_name_or_index("minutes".to_string(), 0).unwrap().to_number();
+		let datetime = context.to_datetime()?;
+		let minutes = crate::stdlib::number_argument("DateTime.minutes", &args, "minutes", 0)?;
 		if let Some(result) = datetime.with_minute(minutes as u32) {
 			Ok(Value::DateTime(result))
 		} else {
@@ -172,10 +231,10 @@ mod setters {
 	}
 
 	pub fn datetime_hours(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.hours", 1, &args, true);
+		super::arity("DateTime.hours", 1, &args, true)?;
 
-		let datetime = context.to_datetime();
-		let hours = args.get_from_name_or_index("hours".to_string(), 0).unwrap().to_number();
+		let datetime = context.to_datetime()?;
+		let hours = crate::stdlib::number_argument("DateTime.hours", &args, "hours", 0)?;
 		if let Some(result) = datetime.with_hour(hours as u32) {
 			Ok(Value::DateTime(result))
 		} else {
@@ -184,10 +243,10 @@ mod setters {
 	}
 
 	pub fn datetime_nanoseconds(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.nanoseconds", 1, &args, true);
+		super::arity("DateTime.nanoseconds", 1, &args, true)?;
 
-		let datetime = context.to_datetime();
-		let nanoseconds = args.get_from_name_or_index("nanoseconds".to_string(), 0).unwrap().to_number();
+		let datetime = context.to_datetime()?;
+		let nanoseconds = crate::stdlib::number_argument("DateTime.nanoseconds", &args, "nanoseconds", 0)?;
 		if let Some(result) = datetime.with_nanosecond(nanoseconds as u32) {
 			Ok(Value::DateTime(result))
 		} else {
@@ -196,10 +255,10 @@ mod setters {
 	}
 
 	pub fn datetime_years(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.years", 1, &args, true);
+		super::arity("DateTime.years", 1, &args, true)?;
 
-		let datetime = context.to_datetime();
-		let years = args.get_from_name_or_index("years".to_string(), 0).unwrap().to_number();
+		let datetime = context.to_datetime()?;
+		let years = crate::stdlib::number_argument("DateTime.years", &args, "years", 0)?;
 		if let Some(result) = datetime.with_year(years as i32) {
 			Ok(Value::DateTime(result))
 		} else {
@@ -208,10 +267,10 @@ mod setters {
 	}
 
 	pub fn datetime_months(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.months", 1, &args, true);
+		super::arity("DateTime.months", 1, &args, true)?;
 
-		let datetime = context.to_datetime();
-		let months = args.get_from_name_or_index("months".to_string(), 0).unwrap().to_number();
+		let datetime = context.to_datetime()?;
+		let months = crate::stdlib::number_argument("DateTime.months", &args, "months", 0)?;
 		if let Some(result) = datetime.with_month(months as u32) {
 			Ok(Value::DateTime(result))
 		} else {
@@ -220,10 +279,10 @@ mod setters {
 	}
 
 	pub fn datetime_days(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-		super::arity("DateTime.days", 1, &args, true);
+		super::arity("DateTime.days", 1, &args, true)?;
 
-		let datetime = context.to_datetime();
-		let days = args.get_from_name_or_index("days".to_string(), 0).unwrap().to_number();
+		let datetime = context.to_datetime()?;
+		let days = crate::stdlib::number_argument("DateTime.days", &args, "days", 0)?;
 		if let Some(result) = datetime.with_day(days as u32) {
 			Ok(Value::DateTime(result))
 		} else {
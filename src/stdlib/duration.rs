@@ -0,0 +1,231 @@
+use chrono::Duration;
+
+use crate::{
+	ast::{ArgumentValues, Op},
+	environment::{NativeMethodCallback, Value},
+	interpreter::{Interpreter, InterpreterResult},
+	numeric,
+};
+
+pub struct DurationObject;
+
+impl DurationObject {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
+			"inDays?" => duration_in_days,
+			"inHours?" => duration_in_hours,
+			"inMinutes?" => duration_in_minutes,
+			"inSeconds?" => duration_in_seconds,
+			"inMilliseconds?" => duration_in_milliseconds,
+			"negative?" => duration_is_negative,
+			"abs!" => duration_abs,
+			"humanize!" => duration_humanize,
+			_ => return None,
+		})
+	}
+}
+
+const UNITS: [(&str, &str, i64); 4] = [("d", "day", 86_400), ("h", "hour", 3_600), ("m", "minute", 60), ("s", "second", 1)];
+
+pub fn seconds(duration: &Duration) -> f64 {
+	duration.num_seconds() as f64 + duration.subsec_nanos() as f64 / 1e9
+}
+
+pub fn from_seconds(seconds: f64) -> Result<Duration, InterpreterResult> {
+	let whole = seconds.floor();
+	let nanos = (((seconds - whole) * 1e9).round() as u32).min(999_999_999);
+
+	if !seconds.is_finite() || whole.abs() >= i64::MAX as f64 / 1000.0 {
+		return Err(InterpreterResult::Error(format!("Duration of {} seconds is out of range.", seconds)));
+	}
+
+	Duration::new(whole as i64, nanos).ok_or_else(|| InterpreterResult::Error(format!("Duration of {} seconds is out of range.", seconds)))
+}
+
+// Datetimes move by durations and their difference is a duration, durations add up and scale by numbers. Kept out
+// of `run_expression` so its recursive frame stays small.
+pub fn temporal_arithmetic(l: Value, op: Op, r: Value) -> Result<Value, InterpreterResult> {
+	let out_of_range = |kind: &str| InterpreterResult::Error(format!("{} out of range.", kind));
+
+	match (&l, &op, &r) {
+		(Value::DateTime(l), Op::Add, Value::Duration(r)) | (Value::Duration(r), Op::Add, Value::DateTime(l)) => {
+			l.checked_add_signed(*r).map(Value::DateTime).ok_or_else(|| out_of_range("DateTime"))
+		}
+		(Value::DateTime(l), Op::Subtract, Value::Duration(r)) => l.checked_sub_signed(*r).map(Value::DateTime).ok_or_else(|| out_of_range("DateTime")),
+		(Value::DateTime(l), Op::Subtract, Value::DateTime(r)) => Ok(Value::Duration(l.signed_duration_since(*r))),
+		(Value::Duration(l), Op::Add, Value::Duration(r)) => l.checked_add(r).map(Value::Duration).ok_or_else(|| out_of_range("Duration")),
+		(Value::Duration(l), Op::Subtract, Value::Duration(r)) => l.checked_sub(r).map(Value::Duration).ok_or_else(|| out_of_range("Duration")),
+		(Value::Duration(_), Op::Divide, r) if numeric::to_float(r) == Some(0.0) => Err(InterpreterResult::Error("Division by zero.".to_string())),
+		(Value::Duration(l), Op::Divide, r) if numeric::is_numeric(r) => from_seconds(seconds(l) / r.clone().expect_number("/")?).map(Value::Duration),
+		(Value::Duration(d), Op::Multiply, n) | (n, Op::Multiply, Value::Duration(d)) if numeric::is_numeric(n) => {
+			from_seconds(seconds(d) * n.clone().expect_number("*")?).map(Value::Duration)
+		}
+		_ => Err(InterpreterResult::TypeError(op.symbol().to_string(), l.typestring(), r.typestring())),
+	}
+}
+
+// Whole days, hours, minutes and seconds of the absolute duration, followed by the milliseconds left.
+fn parts(duration: &Duration) -> (Vec<(usize, i64)>, i64) {
+	let duration = duration.abs();
+	let mut remaining = duration.num_seconds();
+	let mut parts = Vec::new();
+
+	for (index, (_, _, size)) in UNITS.iter().enumerate() {
+		if remaining >= *size {
+			parts.push((index, remaining / size));
+			remaining %= size;
+		}
+	}
+
+	(parts, duration.subsec_nanos() as i64 / 1_000_000)
+}
+
+// Compact form used when printing, like `1d 2h 30m` or `1.5s`.
+pub fn format(duration: &Duration) -> String {
+	let (parts, milliseconds) = parts(duration);
+	let sign = if *duration < Duration::zero() { "-" } else { "" };
+	let mut words = parts.iter().map(|(unit, count)| format!("{}{}", count, UNITS[*unit].0)).collect::<Vec<String>>();
+
+	if milliseconds > 0 {
+		let fraction = format!("{:03}", milliseconds).trim_end_matches('0').to_string();
+
+		match parts.last() {
+			Some((3, seconds)) => *words.last_mut().unwrap() = format!("{}.{}s", seconds, fraction),
+			Some(..) => words.push(format!("{}ms", milliseconds)),
+			None => words.push(format!("0.{}s", fraction)),
+		}
+	}
+
+	if words.is_empty() {
+		return "0s".to_string();
+	}
+
+	format!("{}{}", sign, words.join(" "))
+}
+
+fn duration_in(context: Value, args: &ArgumentValues, name: &str, unit: f64) -> Result<Value, InterpreterResult> {
+	super::arity(name, 0, args, false)?;
+
+	match context.unwrap_constant() {
+		Value::Duration(duration) => Ok(Value::Number(seconds(&duration) / unit)),
+		_ => unreachable!(),
+	}
+}
+
+fn duration_in_days(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	duration_in(context, &args, "Duration.inDays?", 86_400.0)
+}
+
+fn duration_in_hours(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	duration_in(context, &args, "Duration.inHours?", 3_600.0)
+}
+
+fn duration_in_minutes(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	duration_in(context, &args, "Duration.inMinutes?", 60.0)
+}
+
+fn duration_in_seconds(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	duration_in(context, &args, "Duration.inSeconds?", 1.0)
+}
+
+fn duration_in_milliseconds(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	duration_in(context, &args, "Duration.inMilliseconds?", 0.001)
+}
+
+fn duration_is_negative(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Duration.negative?", 0, &args, false)?;
+
+	match context.unwrap_constant() {
+		Value::Duration(duration) => Ok(Value::Bool(duration < Duration::zero())),
+		_ => unreachable!(),
+	}
+}
+
+fn duration_abs(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Duration.abs!", 0, &args, false)?;
+
+	match context.unwrap_constant() {
+		Value::Duration(duration) => Ok(Value::Duration(duration.abs())),
+		_ => unreachable!(),
+	}
+}
+
+// Spelled out for people, like `1 day, 2 hours and 30 minutes` or `3 hours ago` for negative durations. Milliseconds
+// are only mentioned for durations shorter than a second.
+fn duration_humanize(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Duration.humanize!", 0, &args, false)?;
+
+	let duration = match context.unwrap_constant() {
+		Value::Duration(duration) => duration,
+		_ => unreachable!(),
+	};
+
+	let (parts, milliseconds) = parts(&duration);
+	let plural = |count: i64, word: &str| format!("{} {}{}", count, word, if count == 1 { "" } else { "s" });
+
+	let mut words = parts.iter().map(|(unit, count)| plural(*count, UNITS[*unit].1)).collect::<Vec<String>>();
+
+	if words.is_empty() {
+		words.push(plural(milliseconds, "millisecond"));
+	}
+
+	let text = match words.split_last() {
+		Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+		_ => words.join(""),
+	};
+
+	Ok(Value::String(if duration < Duration::zero() { format!("{} ago", text) } else { text }))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::{caught, evaluate};
+
+	fn duration(expression: &str) -> String {
+		evaluate(&format!("create launch = DateTime.parse!(\"2024-03-09 18:30\", \"%Y-%m-%d %H:%M\")\ncreate review = DateTime.parse!(\"2024-03-12\", \"%Y-%m-%d\")\n{}", expression)).unwrap().to_string()
+	}
+
+	#[test]
+	fn it_moves_datetimes_by_durations_and_subtracts_them() {
+		assert_eq!(duration("review - launch"), "2d 5h 30m");
+		assert_eq!(duration("launch - review"), "-2d 5h 30m");
+		assert_eq!(duration("create waited = review - launch\nwaited.inHours?()"), "53.5");
+		assert_eq!(duration("launch + Duration.hours!(6)"), "2024-03-10T00:30:00+00:00");
+		assert_eq!(duration("Duration.hours!(6) + launch"), "2024-03-10T00:30:00+00:00");
+		assert_eq!(duration("launch - Duration.minutes!(90)"), "2024-03-09T17:00:00+00:00");
+		assert_eq!(duration("create waited = review - launch\nlaunch + waited == review"), "true");
+		assert_eq!(duration("launch < review"), "true");
+	}
+
+	#[test]
+	fn it_adds_and_scales_durations() {
+		assert_eq!(evaluate("Duration.seconds!(75.25) * 4").unwrap().to_string(), "5m 1s");
+		assert_eq!(evaluate("2 * Duration.minutes!(1)").unwrap().to_string(), "2m");
+		assert_eq!(evaluate("Duration.seconds!(75.25) + Duration.milliseconds!(250)").unwrap().to_string(), "1m 15.5s");
+		assert_eq!(evaluate("Duration.hours!(1) - Duration.minutes!(90)").unwrap().to_string(), "-30m");
+		assert_eq!(evaluate("Duration.hours!(1) / 8").unwrap().to_string(), "7m 30s");
+		assert_eq!(evaluate("Duration.days!(1) > Duration.hours!(23)").unwrap().to_string(), "true");
+		assert_eq!(evaluate("Duration.seconds!(0)").unwrap().to_string(), "0s");
+	}
+
+	#[test]
+	fn it_reads_and_spells_out_durations() {
+		assert_eq!(evaluate("Duration.minutes!(90).inHours?()").unwrap().to_string(), "1.5");
+		assert_eq!(evaluate("Duration.seconds!(2).inMilliseconds?()").unwrap().to_string(), "2000");
+		assert_eq!(evaluate("create late = Duration.seconds!(0) - Duration.days!(1)\nlate.negative?()").unwrap().to_string(), "true");
+		assert_eq!(evaluate("create late = Duration.seconds!(0) - Duration.days!(1)\nlate.abs!()").unwrap().to_string(), "1d");
+		assert_eq!(evaluate("Duration.hours!(1).humanize!()").unwrap().to_string(), "1 hour");
+		assert_eq!(evaluate("Duration.milliseconds!(300).humanize!()").unwrap().to_string(), "300 milliseconds");
+		assert_eq!(evaluate("create span = Duration.days!(1) + Duration.hours!(2) + Duration.minutes!(30)\nspan.humanize!()").unwrap().to_string(), "1 day, 2 hours and 30 minutes");
+		assert_eq!(evaluate("create late = Duration.seconds!(0) - Duration.hours!(3)\nlate.humanize!()").unwrap().to_string(), "3 hours ago");
+	}
+
+	#[test]
+	fn it_rejects_arithmetic_that_has_no_meaning() {
+		assert_eq!(caught("Duration.hours!(1) / 0"), "Division by zero.");
+		assert_eq!(caught("Duration.seconds!(10000000000000000)"), "Duration of 10000000000000000 seconds is out of range.");
+		assert_eq!(caught("Duration.hours!(\"1\")"), "hours! expects a number, got string.");
+		assert_eq!(caught("Duration.hours!(1) + 1"), "TypeError: unsupported operand types for +: duration and number.");
+		assert_eq!(caught("create launch = DateTime.parse!(\"2024-03-09\", \"%Y-%m-%d\")\nlaunch + launch"), "TypeError: unsupported operand types for +: datetime and datetime.");
+	}
+}
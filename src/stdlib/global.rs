@@ -1,7 +1,5 @@
 use hashbrown::HashMap;
 
-use self::structs::console;
-
 use super::arity;
 use crate::environment::{NativeFunctionCallback, Value};
 
@@ -13,6 +11,12 @@ impl GlobalObject {
 
 		global_functions.insert("type?".to_string(), functions::global_type);
 		global_functions.insert("import!".to_string(), functions::global_import);
+		global_functions.insert("same?".to_string(), functions::global_same);
+		global_functions.insert("is?".to_string(), functions::global_is);
+		global_functions.insert("len?".to_string(), functions::global_len);
+		global_functions.insert("range!".to_string(), functions::global_range);
+		global_functions.insert("enumerate!".to_string(), functions::global_enumerate);
+		global_functions.insert("zip!".to_string(), functions::global_zip);
 
 		global_functions
 	}
@@ -22,8 +26,19 @@ impl GlobalObject {
 
 		let mut application_methods = HashMap::<String, Value>::new();
 		application_methods.insert("exit!".to_string(), Value::NativeFunction { name: "exit!".to_string(), callback: structs::application::exit });
+		application_methods.insert("args?".to_string(), Value::NativeFunction { name: "args?".to_string(), callback: structs::application::args });
+		application_methods.insert("cwd?".to_string(), Value::NativeFunction { name: "cwd?".to_string(), callback: structs::application::cwd });
 		global_struct.insert("Application".to_string(), application_methods);
 
+		let mut env_methods = HashMap::<String, Value>::new();
+		env_methods.insert("get?".to_string(), Value::NativeFunction { name: "get?".to_string(), callback: structs::env::get });
+		env_methods.insert("set!".to_string(), Value::NativeFunction { name: "set!".to_string(), callback: structs::env::set });
+		global_struct.insert("Env".to_string(), env_methods);
+
+		let mut process_methods = HashMap::<String, Value>::new();
+		process_methods.insert("run!".to_string(), Value::NativeFunction { name: "run!".to_string(), callback: structs::process::run });
+		global_struct.insert("Process".to_string(), process_methods);
+
 		let mut console_methods = HashMap::<String, Value>::new();
 		console_methods.insert("print!".to_string(), Value::NativeFunction { name: "print!".to_string(), callback: structs::console::print });
 		console_methods.insert("println!".to_string(), Value::NativeFunction { name: "println!".to_string(), callback: structs::console::println });
@@ -35,8 +50,106 @@ impl GlobalObject {
 		time_methods.insert("sleep!".to_string(), Value::NativeFunction { name: "sleep!".to_string(), callback: structs::time::sleep });
 		time_methods.insert("now?".to_string(), Value::NativeFunction { name: "now?".to_string(), callback: structs::time::now });
 		time_methods.insert("datetime?".to_string(), Value::NativeFunction { name: "datetime?".to_string(), callback: structs::time::datetime });
+		time_methods.insert("local?".to_string(), Value::NativeFunction { name: "local?".to_string(), callback: structs::time::local });
 		global_struct.insert("Time".to_string(), time_methods);
 
+		let mut duration_methods = HashMap::<String, Value>::new();
+		duration_methods.insert("days!".to_string(), Value::NativeFunction { name: "days!".to_string(), callback: structs::duration::days });
+		duration_methods.insert("hours!".to_string(), Value::NativeFunction { name: "hours!".to_string(), callback: structs::duration::hours });
+		duration_methods.insert("minutes!".to_string(), Value::NativeFunction { name: "minutes!".to_string(), callback: structs::duration::minutes });
+		duration_methods.insert("seconds!".to_string(), Value::NativeFunction { name: "seconds!".to_string(), callback: structs::duration::seconds });
+		duration_methods.insert("milliseconds!".to_string(), Value::NativeFunction { name: "milliseconds!".to_string(), callback: structs::duration::milliseconds });
+		global_struct.insert("Duration".to_string(), duration_methods);
+
+		let mut datetime_methods = HashMap::<String, Value>::new();
+		datetime_methods.insert("parse!".to_string(), Value::NativeFunction { name: "parse!".to_string(), callback: structs::datetime::parse });
+		global_struct.insert("DateTime".to_string(), datetime_methods);
+
+		let mut async_methods = HashMap::<String, Value>::new();
+		async_methods.insert("readFile!".to_string(), Value::NativeFunction { name: "readFile!".to_string(), callback: structs::asynchronous::read_file });
+		async_methods.insert("writeFile!".to_string(), Value::NativeFunction { name: "writeFile!".to_string(), callback: structs::asynchronous::write_file });
+		async_methods.insert("run!".to_string(), Value::NativeFunction { name: "run!".to_string(), callback: structs::asynchronous::run });
+		async_methods.insert("serve!".to_string(), Value::NativeFunction { name: "serve!".to_string(), callback: structs::asynchronous::serve });
+		async_methods.insert("send!".to_string(), Value::NativeFunction { name: "send!".to_string(), callback: structs::asynchronous::send });
+		global_struct.insert("Async".to_string(), async_methods);
+
+		let mut worker_methods = HashMap::<String, Value>::new();
+		worker_methods.insert("spawn!".to_string(), Value::NativeFunction { name: "spawn!".to_string(), callback: structs::worker::spawn });
+		worker_methods.insert("receive!".to_string(), Value::NativeFunction { name: "receive!".to_string(), callback: structs::worker::receive });
+		worker_methods.insert("post!".to_string(), Value::NativeFunction { name: "post!".to_string(), callback: structs::worker::post });
+		worker_methods.insert("cores?".to_string(), Value::NativeFunction { name: "cores?".to_string(), callback: structs::worker::cores });
+		global_struct.insert("Worker".to_string(), worker_methods);
+
+		let mut file_methods = HashMap::<String, Value>::new();
+		file_methods.insert("read!".to_string(), Value::NativeFunction { name: "read!".to_string(), callback: structs::file::read });
+		file_methods.insert("write!".to_string(), Value::NativeFunction { name: "write!".to_string(), callback: structs::file::write });
+		file_methods.insert("append!".to_string(), Value::NativeFunction { name: "append!".to_string(), callback: structs::file::append });
+		file_methods.insert("readLines!".to_string(), Value::NativeFunction { name: "readLines!".to_string(), callback: structs::file::read_lines });
+		file_methods.insert("lines!".to_string(), Value::NativeFunction { name: "lines!".to_string(), callback: structs::file::lines });
+		file_methods.insert("exists?".to_string(), Value::NativeFunction { name: "exists?".to_string(), callback: structs::file::exists });
+		file_methods.insert("remove!".to_string(), Value::NativeFunction { name: "remove!".to_string(), callback: structs::file::remove });
+		file_methods.insert("copy!".to_string(), Value::NativeFunction { name: "copy!".to_string(), callback: structs::file::copy });
+		file_methods.insert("rename!".to_string(), Value::NativeFunction { name: "rename!".to_string(), callback: structs::file::rename });
+		file_methods.insert("metadata?".to_string(), Value::NativeFunction { name: "metadata?".to_string(), callback: structs::file::metadata });
+		global_struct.insert("File".to_string(), file_methods);
+
+		let mut dir_methods = HashMap::<String, Value>::new();
+		dir_methods.insert("list!".to_string(), Value::NativeFunction { name: "list!".to_string(), callback: structs::dir::list });
+		dir_methods.insert("create!".to_string(), Value::NativeFunction { name: "create!".to_string(), callback: structs::dir::create });
+		dir_methods.insert("glob!".to_string(), Value::NativeFunction { name: "glob!".to_string(), callback: structs::dir::glob });
+		global_struct.insert("Dir".to_string(), dir_methods);
+
+		let mut json_methods = HashMap::<String, Value>::new();
+		json_methods.insert("parse!".to_string(), Value::NativeFunction { name: "parse!".to_string(), callback: structs::json::parse });
+		json_methods.insert("stringify!".to_string(), Value::NativeFunction { name: "stringify!".to_string(), callback: structs::json::stringify });
+		global_struct.insert("Json".to_string(), json_methods);
+
+		let mut regex_methods = HashMap::<String, Value>::new();
+		regex_methods.insert("new!".to_string(), Value::NativeFunction { name: "new!".to_string(), callback: structs::regexp::new });
+		regex_methods.insert("escape!".to_string(), Value::NativeFunction { name: "escape!".to_string(), callback: structs::regexp::escape });
+		global_struct.insert("Regex".to_string(), regex_methods);
+
+		let mut math_methods = HashMap::<String, Value>::new();
+		math_methods.insert("abs!".to_string(), Value::NativeFunction { name: "abs!".to_string(), callback: structs::math::abs });
+		math_methods.insert("sqrt!".to_string(), Value::NativeFunction { name: "sqrt!".to_string(), callback: structs::math::sqrt });
+		math_methods.insert("cbrt!".to_string(), Value::NativeFunction { name: "cbrt!".to_string(), callback: structs::math::cbrt });
+		math_methods.insert("pow!".to_string(), Value::NativeFunction { name: "pow!".to_string(), callback: structs::math::pow });
+		math_methods.insert("exp!".to_string(), Value::NativeFunction { name: "exp!".to_string(), callback: structs::math::exp });
+		math_methods.insert("log!".to_string(), Value::NativeFunction { name: "log!".to_string(), callback: structs::math::log });
+		math_methods.insert("sin!".to_string(), Value::NativeFunction { name: "sin!".to_string(), callback: structs::math::sin });
+		math_methods.insert("cos!".to_string(), Value::NativeFunction { name: "cos!".to_string(), callback: structs::math::cos });
+		math_methods.insert("tan!".to_string(), Value::NativeFunction { name: "tan!".to_string(), callback: structs::math::tan });
+		math_methods.insert("asin!".to_string(), Value::NativeFunction { name: "asin!".to_string(), callback: structs::math::asin });
+		math_methods.insert("acos!".to_string(), Value::NativeFunction { name: "acos!".to_string(), callback: structs::math::acos });
+		math_methods.insert("atan!".to_string(), Value::NativeFunction { name: "atan!".to_string(), callback: structs::math::atan });
+		math_methods.insert("atan2!".to_string(), Value::NativeFunction { name: "atan2!".to_string(), callback: structs::math::atan2 });
+		math_methods.insert("floor!".to_string(), Value::NativeFunction { name: "floor!".to_string(), callback: structs::math::floor });
+		math_methods.insert("ceil!".to_string(), Value::NativeFunction { name: "ceil!".to_string(), callback: structs::math::ceil });
+		math_methods.insert("round!".to_string(), Value::NativeFunction { name: "round!".to_string(), callback: structs::math::round });
+		math_methods.insert("min!".to_string(), Value::NativeFunction { name: "min!".to_string(), callback: structs::math::min });
+		math_methods.insert("max!".to_string(), Value::NativeFunction { name: "max!".to_string(), callback: structs::math::max });
+		math_methods.insert("clamp!".to_string(), Value::NativeFunction { name: "clamp!".to_string(), callback: structs::math::clamp });
+		math_methods.insert("PI".to_string(), Value::Number(std::f64::consts::PI));
+		math_methods.insert("E".to_string(), Value::Number(std::f64::consts::E));
+		math_methods.insert("INFINITY".to_string(), Value::Number(f64::INFINITY));
+		math_methods.insert("NAN".to_string(), Value::Number(f64::NAN));
+		global_struct.insert("Math".to_string(), math_methods);
+
+		let mut random_methods = HashMap::<String, Value>::new();
+		random_methods.insert("int!".to_string(), Value::NativeFunction { name: "int!".to_string(), callback: structs::random::int });
+		random_methods.insert("float!".to_string(), Value::NativeFunction { name: "float!".to_string(), callback: structs::random::float });
+		random_methods.insert("bool!".to_string(), Value::NativeFunction { name: "bool!".to_string(), callback: structs::random::boolean });
+		random_methods.insert("choice!".to_string(), Value::NativeFunction { name: "choice!".to_string(), callback: structs::random::choice });
+		random_methods.insert("shuffle!".to_string(), Value::NativeFunction { name: "shuffle!".to_string(), callback: structs::random::shuffle });
+		random_methods.insert("sample!".to_string(), Value::NativeFunction { name: "sample!".to_string(), callback: structs::random::sample });
+		random_methods.insert("seed!".to_string(), Value::NativeFunction { name: "seed!".to_string(), callback: structs::random::seed });
+		global_struct.insert("Random".to_string(), random_methods);
+
+		let mut runtime_methods = HashMap::<String, Value>::new();
+		runtime_methods.insert("gc!".to_string(), Value::NativeFunction { name: "gc!".to_string(), callback: structs::runtime::gc });
+		runtime_methods.insert("memory?".to_string(), Value::NativeFunction { name: "memory?".to_string(), callback: structs::runtime::memory });
+		global_struct.insert("Runtime".to_string(), runtime_methods);
+
 		global_struct
 	}
 }
@@ -47,18 +160,102 @@ mod functions {
 	use crate::parser::parse;
 	use crate::token::generate;
 
-	use crate::{environment::Value, interpreter::Interpreter};
+	use crate::{
+		environment::Value,
+		interpreter::{Interpreter, InterpreterResult},
+		iterator::ValueIterator,
+		range::Range,
+	};
 
-	pub fn global_type(_: &mut Interpreter, args: ArgumentValues) -> Value {
-		arity("type!", 1, &args, false);
+	pub fn global_type(_: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("type!", 1, &args, false)?;
 
 		let arg = args.get_from_name_or_index("value".to_string(), 0).unwrap();
 
-		Value::String(arg.clone().typestring())
+		Ok(Value::String(arg.clone().typestring()))
+	}
+
+	pub fn global_same(_: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("same?", 2, &args, false)?;
+
+		let left = args.get_from_name_or_index("left".to_string(), 0).unwrap();
+		let right = args.get_from_name_or_index("right".to_string(), 1).unwrap();
+
+		Ok(Value::Bool(left.same(&right)))
+	}
+
+	pub fn global_is(_: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("is?", 2, &args, false)?;
+
+		let value = args.get_from_name_or_index("value".to_string(), 0).unwrap();
+		let kind = args.get_from_name_or_index("type".to_string(), 1).unwrap().unwrap_constant();
+
+		if !matches!(kind, Value::Struct { .. } | Value::Interface { .. } | Value::Enum { .. }) {
+			return Err(InterpreterResult::Error(format!("is? expects a struct, interface or enum, got {}.", kind.typestring())));
+		}
+
+		Ok(Value::Bool(value.is_instance_of(&kind)))
+	}
+
+	pub fn global_len(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("len?", 1, &args, false)?;
+
+		let value = args.get_from_name_or_index("value".to_string(), 0).unwrap().unwrap_constant();
+
+		Ok(match value {
+			Value::String(s) => Value::Number(s.chars().count() as f64),
+			Value::List(items) => Value::Number(items.borrow().len() as f64),
+			Value::Map(map) => Value::Number(map.borrow().len() as f64),
+			Value::Range(range) => Value::Number(range.len() as f64),
+			value => match interpreter.call_method(value.clone(), "__len", vec![])? {
+				Some(length) => length,
+				None => return Err(InterpreterResult::Error(format!("Value of type {} has no length.", value.typestring()))),
+			},
+		})
+	}
+
+	pub fn global_range(_: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("range!", 1, &args, true)?;
+
+		let values = args.get_all_values().into_iter().map(|v| v.expect_number("range!")).collect::<Result<Vec<f64>, _>>()?;
+
+		let (current, end, step) = match values.as_slice() {
+			[end] => (0.0, *end, 1.0),
+			[start, end] => (*start, *end, 1.0),
+			[start, end, step, ..] => (*start, *end, *step),
+			_ => unreachable!(),
+		};
+
+		if step == 0.0 {
+			return Err(InterpreterResult::Error("range! step cannot be zero.".to_string()));
+		}
+
+		Ok(Range::new(current, end, step, false).iter().shared())
 	}
 
-	pub fn global_import(interpreter: &mut Interpreter, args: ArgumentValues) -> Value {
-		arity("import!", 1, &args, false);
+	pub fn global_enumerate(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("enumerate!", 1, &args, false)?;
+
+		let iterable = args.get_from_name_or_index("iterable".to_string(), 0).unwrap();
+		let inner = interpreter.iterate(iterable)?;
+
+		Ok(ValueIterator::Enumerate { inner: Box::new(inner), index: 0 }.shared())
+	}
+
+	pub fn global_zip(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("zip!", 2, &args, true)?;
+
+		let mut iterators = Vec::new();
+
+		for iterable in args.get_all_values() {
+			iterators.push(interpreter.iterate(iterable)?);
+		}
+
+		Ok(ValueIterator::Zip(iterators).shared())
+	}
+
+	pub fn global_import(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+		arity("import!", 1, &args, false)?;
 
 		let path = args.get_from_name_or_index("path".to_string(), 0).unwrap().to_string();
 		let directory = interpreter.path().parent().unwrap().to_path_buf();
@@ -84,110 +281,1216 @@ mod functions {
 			panic!("Failed to parse module {}.", module_path.to_str().unwrap());
 		};
 
-		let value = match interpreter.exec(ast) {
-			Ok(_) => Value::Null,
-			Err(e) => {
-				e.print();
-				std::process::exit(1);
-			}
-		};
+		crate::checker::report(crate::checker::check(&ast));
 
-		return value;
+		interpreter.exec(ast)?;
+
+		Ok(Value::Null)
 	}
 }
 
 mod structs {
 	use super::arity;
+	use crate::{
+		ast::Parameter,
+		environment::{Environment, Value},
+		gc,
+		interpreter::Interpreter,
+	};
+	use hashbrown::HashMap;
+	use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+	// Relative paths are relative to the running script.
+	fn resolve(interpreter: &Interpreter, path: String) -> PathBuf {
+		interpreter.path().parent().unwrap().join(path)
+	}
+
+	// Instance of a struct with no methods, used to hand back several named values at once.
+	fn record(name: &str, fields: Vec<(&str, Value)>) -> Value {
+		let definition = Value::Struct {
+			name: name.to_string(),
+			fields: fields.iter().map(|(name, _)| Parameter::new(name.to_string(), None)).collect(),
+			methods: Rc::new(RefCell::new(HashMap::new())),
+			parent: None,
+			interfaces: vec![],
+		};
+
+		let mut environment = Environment::new();
+
+		for (name, value) in fields {
+			environment.set(name, value);
+		}
+
+		Value::StructInstance { environment: gc::instance(environment), definition: Box::new(definition) }
+	}
+
 	pub mod application {
-		use crate::{ast::ArgumentValues, environment::Value, interpreter::Interpreter};
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+		};
 
-		pub fn exit(_interpreter: &mut Interpreter, args: ArgumentValues) -> Value {
+		pub fn exit(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
 			// arity("exit!", 0, &args, false); TODO: Implement arity with optional arguments
 
-			std::process::exit(if args.is_empty() {
-				0
-			} else {
-				args.get_from_name_or_index("code".to_string(), 0).unwrap().clone().to_number() as i32
-			});
+			let code = crate::stdlib::optional_number_argument("exit!", &args, "code", 0)?;
+
+			std::process::exit(code.map_or(0, |code| code as i32));
+		}
+
+		// Arguments given after the script path, `lugli run script.lg a b` gives `["a", "b"]`.
+		pub fn args(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("args?", 0, &args, false)?;
+
+			Ok(Value::list(interpreter.args.iter().cloned().map(Value::String).collect()))
+		}
+
+		// Directory the interpreter was started from, relative paths in `File` and `Dir` are relative to the script instead.
+		pub fn cwd(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("cwd?", 0, &args, false)?;
+
+			match std::env::current_dir() {
+				Ok(path) => Ok(Value::String(path.display().to_string())),
+				Err(e) => Err(InterpreterResult::Error(format!("Cannot read the current directory: {}.", e))),
+			}
+		}
+	}
+
+	pub mod env {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+		};
+
+		// Value of an environment variable, `default` (null unless given) when it isn't set.
+		pub fn get(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("get?", 1, &args, true)?;
+
+			let name = args.get_from_name_or_index("name".to_string(), 0).unwrap().to_string();
+
+			if let Some(value) = interpreter.env.get(&name) {
+				return Ok(Value::String(value.clone()));
+			}
+
+			Ok(match std::env::var_os(&name) {
+				Some(value) => Value::String(value.to_string_lossy().to_string()),
+				None => args.get_from_name_or_index("default".to_string(), 1).unwrap_or(Value::Null),
+			})
+		}
+
+		// Sets a variable for this interpreter, its workers and the programs it runs. The process environment is left
+		// untouched, changing it isn't safe while other threads may read it.
+		pub fn set(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("set!", 2, &args, false)?;
+
+			let name = args.get_from_name_or_index("name".to_string(), 0).unwrap().to_string();
+			let value = interpreter.stringify(args.get_from_name_or_index("value".to_string(), 1).unwrap())?;
+
+			if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+				return Err(InterpreterResult::Error(format!("Invalid environment variable {}={}.", name, value)));
+			}
+
+			interpreter.env.insert(name, value);
+
+			Ok(Value::Null)
+		}
+	}
+
+	pub mod process {
+		use super::{arity, record, resolve};
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+		};
+		use std::{
+			io::{Read, Write},
+			process::{Command, Stdio},
+			thread::{self, JoinHandle},
+			time::{Duration, Instant},
+		};
+
+		fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+			thread::spawn(move || {
+				let mut output = Vec::new();
+
+				if let Some(mut pipe) = pipe {
+					pipe.read_to_end(&mut output).ok();
+				}
+
+				String::from_utf8_lossy(&output).to_string()
+			})
+		}
+
+		// Runs `command` with `args` and waits for it, returning its exit code (null when it was killed by a signal),
+		// stdout and stderr. Options are `stdin` (text written to the program, nothing by default), `cwd` (relative to
+		// the script) and `timeout` in milliseconds, after which the program is killed and an error is raised.
+		pub fn run(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("run!", 1, &args, true)?;
+
+			// Only positional arguments, so `run!("ls", cwd = "src")` isn't read as `run!("ls", "src")`.
+			let positional = args.get_raw().into_iter().filter(|argument| argument.get_name().is_none()).map(|argument| argument.get_value()).collect::<Vec<Value>>();
+			let command = args.get_from_name("command".to_string()).or_else(|| positional.first().cloned()).unwrap().to_string();
+
+			let arguments = match args.get_from_name("args".to_string()).or_else(|| positional.get(1).cloned()).map(Value::unwrap_constant) {
+				None => Vec::new(),
+				Some(Value::List(items)) => items.borrow().iter().map(|item| interpreter.stringify(item.clone())).collect::<Result<Vec<String>, InterpreterResult>>()?,
+				Some(other) => return Err(InterpreterResult::Error(format!("run! expects a list of arguments, got {}.", other.typestring()))),
+			};
+
+			let stdin = match args.get_from_name("stdin".to_string()) {
+				Some(stdin) => Some(interpreter.stringify(stdin)?),
+				None => None,
+			};
+			let timeout = match args.get_from_name("timeout".to_string()) {
+				Some(timeout) => Some(Duration::from_millis(timeout.expect_number("Process.run!")?.max(0.0) as u64)),
+				None => None,
+			};
+
+			let mut process = Command::new(&command);
+			process.args(&arguments).envs(&interpreter.env).stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() }).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+			if let Some(cwd) = args.get_from_name("cwd".to_string()) {
+				process.current_dir(resolve(interpreter, cwd.to_string()));
+			}
+
+			let mut child = process.spawn().map_err(|e| InterpreterResult::Error(format!("Cannot run {}: {}.", command, e)))?;
+
+			// Written from another thread, a program that prints before reading could otherwise block on a full pipe.
+			if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+				thread::spawn(move || pipe.write_all(input.as_bytes()));
+			}
+
+			let (stdout, stderr) = (drain(child.stdout.take()), drain(child.stderr.take()));
+			let started = Instant::now();
+
+			let failed = |e: std::io::Error| InterpreterResult::Error(format!("Cannot wait for {}: {}.", command, e));
+
+			let status = match timeout {
+				None => child.wait().map_err(failed)?,
+				Some(timeout) => loop {
+					match child.try_wait().map_err(failed)? {
+						Some(status) => break status,
+						None if started.elapsed() >= timeout => {
+							child.kill().ok();
+							child.wait().ok();
+
+							return Err(InterpreterResult::Error(format!("{} timed out after {} ms.", command, timeout.as_millis())));
+						}
+						None => thread::sleep(Duration::from_millis(1)),
+					}
+				},
+			};
+
+			Ok(record(
+				"Process",
+				vec![
+					("code", status.code().map_or(Value::Null, |code| Value::Number(code as f64))),
+					("success", Value::Bool(status.success())),
+					("stdout", Value::String(stdout.join().unwrap_or_default())),
+					("stderr", Value::String(stderr.join().unwrap_or_default())),
+				],
+			))
 		}
 	}
 
 	pub mod console {
 		use super::arity;
-		use crate::{ast::ArgumentValues, environment::Value, interpreter::Interpreter};
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+		};
 		use std::io::{stdout, Write};
 
-		pub fn println(_: &mut Interpreter, args: ArgumentValues) -> Value {
-			arity("println!", 1, &args, true);
+		pub fn println(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("println!", 1, &args, true)?;
 
-			let content = args.get_from_name_or_index("content".to_string(), 0).unwrap().to_string();
+			let content = args.get_from_name_or_index("content".to_string(), 0).unwrap();
+			let content = interpreter.stringify(content)?;
 			let mut stdout = stdout();
 
-			stdout.write(format!("{}\n", content).as_bytes()).unwrap();
+			stdout.write_all(format!("{}\n", content).as_bytes()).unwrap();
 			stdout.flush().unwrap();
 
-			Value::Null
+			Ok(Value::Null)
 		}
 
-		pub fn print(_: &mut Interpreter, args: ArgumentValues) -> Value {
-			arity("print!", 1, &args, true);
+		pub fn print(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("print!", 1, &args, true)?;
 
-			let content = args.get_from_name_or_index("content".to_string(), 0).unwrap().to_string();
+			let content = args.get_from_name_or_index("content".to_string(), 0).unwrap();
+			let content = interpreter.stringify(content)?;
 			let mut stdout = stdout();
 
-			stdout.write(content.as_bytes()).unwrap();
+			stdout.write_all(content.as_bytes()).unwrap();
 			stdout.flush().unwrap();
 
-			Value::Null
+			Ok(Value::Null)
 		}
 
-		pub fn input(_interpreter: &mut Interpreter, args: ArgumentValues) -> Value {
-			arity("input!", 0, &args, false);
+		pub fn input(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("input!", 0, &args, false)?;
 
 			let mut input = String::new();
 
 			std::io::stdin().read_line(&mut input).unwrap();
 
-			Value::String(input)
+			Ok(Value::String(input))
 		}
 
-		pub fn clear(_interpreter: &mut Interpreter, args: ArgumentValues) -> Value {
-			arity("clear!", 0, &args, false);
+		pub fn clear(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("clear!", 0, &args, false)?;
 
 			let mut stdout = stdout();
 
-			stdout.write(b"\x1b[2J").unwrap();
+			stdout.write_all(b"\x1b[2J").unwrap();
 			stdout.flush().unwrap();
 
-			Value::Null
+			Ok(Value::Null)
 		}
 	}
 
 	pub mod time {
 		use super::arity;
-		use crate::{ast::ArgumentValues, environment::Value, interpreter::Interpreter};
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+		};
 		use chrono::Utc;
 
-		pub fn sleep(_interpreter: &mut Interpreter, args: ArgumentValues) -> Value {
-			arity("sleep!", 1, &args, false);
+		// Inside an async function the sleep is a timer task to `await`, elsewhere it blocks the interpreter. A timer
+		// dropped without `await` blocks too.
+		pub fn sleep(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("sleep!", 1, &args, false)?;
+
+			let duration = crate::stdlib::number_argument("sleep!", &args, "duration", 0)?;
 
-			let duration = args.get_from_name("duration".to_string()).unwrap().to_number();
+			if crate::event_loop::in_task(interpreter) {
+				return Ok(crate::event_loop::timer(duration));
+			}
 
 			std::thread::sleep(std::time::Duration::from_millis(duration as u64));
 
-			Value::Null
+			Ok(Value::Null)
 		}
 
-		pub fn now(_interpreter: &mut Interpreter, args: ArgumentValues) -> Value {
-			arity("now?", 0, &args, false);
+		pub fn now(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("now?", 0, &args, false)?;
 
-			Value::Number(Utc::now().timestamp() as f64)
+			Ok(Value::Number(Utc::now().timestamp() as f64))
 		}
 
-		pub fn datetime(_interpreter: &mut Interpreter, args: ArgumentValues) -> Value {
-			arity("datetime?", 0, &args, false);
+		pub fn datetime(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("datetime?", 0, &args, false)?;
+
+			Ok(Value::DateTime(crate::zone::now()))
+		}
+
+		// Now in the system time zone.
+		pub fn local(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("local?", 0, &args, false)?;
+
+			Ok(Value::DateTime(crate::zone::now().with_timezone(&crate::zone::Zone::local())))
+		}
+	}
+
+	pub mod duration {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+			numeric,
+			stdlib::duration::from_seconds,
+		};
+
+		fn of(name: &str, args: ArgumentValues, unit: f64) -> Result<Value, InterpreterResult> {
+			arity(name, 1, &args, false)?;
+
+			match args.get_from_name_or_index("amount".to_string(), 0).unwrap() {
+				amount if numeric::is_numeric(&amount) => Ok(Value::Duration(from_seconds(amount.expect_number(name)? * unit)?)),
+				amount => Err(InterpreterResult::Error(format!("{} expects a number, got {}.", name, amount.typestring()))),
+			}
+		}
+
+		pub fn days(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			of("days!", args, 86_400.0)
+		}
+
+		pub fn hours(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			of("hours!", args, 3_600.0)
+		}
+
+		pub fn minutes(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			of("minutes!", args, 60.0)
+		}
+
+		pub fn seconds(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			of("seconds!", args, 1.0)
+		}
+
+		pub fn milliseconds(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			of("milliseconds!", args, 0.001)
+		}
+	}
+
+	pub mod datetime {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+		};
+		use crate::zone::{self, Zone};
+		use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+		// Without a format the text is read as ISO 8601 or RFC 2822. A strftime `format` (`%Y-%m-%d %H:%M`) can leave
+		// out the time, which gives midnight, and the offset (`%z`), in which case the time is in `zone`, UTC by default.
+		pub fn parse(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("parse!", 1, &args, true)?;
+
+			// Only positional arguments, so `parse!(text, zone = "UTC")` isn't read as a format.
+			let positional = args.get_raw().into_iter().filter(|argument| argument.get_name().is_none()).map(|argument| argument.get_value()).collect::<Vec<Value>>();
+			let text = args.get_from_name("text".to_string()).or_else(|| positional.first().cloned()).unwrap().to_string();
+			let zone = match args.get_from_name("zone".to_string()) {
+				Some(zone) => Zone::parse(&zone.to_string()).map_err(InterpreterResult::Error)?,
+				None => Zone::utc(),
+			};
+
+			let format = match args.get_from_name("format".to_string()).or_else(|| positional.get(1).cloned()) {
+				Some(format) => format.to_string(),
+				None if args.get_from_name("zone".to_string()).is_some() => {
+					return Err(InterpreterResult::Error("parse! needs a format to read a time in a zone, ISO 8601 text carries its own offset.".to_string()))
+				}
+				None => return zone::parse(&text).map(Value::DateTime).map_err(InterpreterResult::Error),
+			};
+
+			let local = |local: NaiveDateTime| {
+				zone::resolve(&zone, local).ok_or_else(|| InterpreterResult::Error(format!("{} doesn't exist in {}.", local, zone.name())))
+			};
+
+			let parsed = match DateTime::parse_from_str(&text, &format) {
+				Ok(datetime) => datetime.with_timezone(&Zone::Fixed(*datetime.offset())),
+				Err(e) => match NaiveDateTime::parse_from_str(&text, &format) {
+					Ok(datetime) => local(datetime)?,
+					Err(_) => match NaiveDate::parse_from_str(&text, &format) {
+						Ok(date) => local(date.and_time(NaiveTime::MIN))?,
+						Err(_) => return Err(InterpreterResult::Error(format!("Cannot parse \"{}\" as a datetime with format \"{}\": {}.", text, format, e))),
+					},
+				},
+			};
+
+			Ok(Value::DateTime(parsed))
+		}
+	}
+
+	// Native I/O for async functions. Every call starts the work on a helper thread right away and returns a task that
+	// completes with the result, paths are relative to the running script.
+	pub mod asynchronous {
+		use super::{arity, resolve};
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			event_loop,
+			interpreter::{Interpreter, InterpreterResult},
+		};
+		use std::{
+			io::{Read, Write},
+			net::{Shutdown, TcpListener, TcpStream},
+			process::Command,
+		};
+
+		pub fn read_file(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("readFile!", 1, &args, false)?;
+
+			let path = resolve(interpreter, args.get_from_name_or_index("path".to_string(), 0).unwrap().to_string());
+
+			Ok(event_loop::thread(move || std::fs::read_to_string(&path).map(Some).map_err(|e| format!("Cannot read file {}: {}.", path.display(), e))))
+		}
+
+		pub fn write_file(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("writeFile!", 2, &args, false)?;
+
+			let path = resolve(interpreter, args.get_from_name_or_index("path".to_string(), 0).unwrap().to_string());
+			let contents = args.get_from_name_or_index("contents".to_string(), 1).unwrap();
+			let contents = interpreter.stringify(contents)?;
+
+			Ok(event_loop::thread(move || std::fs::write(&path, contents).map(|_| None).map_err(|e| format!("Cannot write file {}: {}.", path.display(), e))))
+		}
+
+		// Completes with the command's standard output, a non-zero exit status fails the task with its standard error.
+		pub fn run(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("run!", 1, &args, true)?;
+
+			let command = args.get_from_name_or_index("command".to_string(), 0).unwrap().to_string();
+			let arguments = match args.get_from_name_or_index("args".to_string(), 1).map(Value::unwrap_constant) {
+				None => Vec::new(),
+				Some(Value::List(items)) => items.borrow().iter().map(|item| interpreter.stringify(item.clone())).collect::<Result<Vec<String>, InterpreterResult>>()?,
+				Some(other) => return Err(InterpreterResult::Error(format!("run! expects a list of arguments, got {}.", other.typestring()))),
+			};
+			let directory = interpreter.path().parent().unwrap().to_path_buf();
+			let env = interpreter.env.clone();
+
+			Ok(event_loop::thread(move || {
+				let output = Command::new(&command).args(&arguments).envs(&env).current_dir(directory).output().map_err(|e| format!("Cannot run {}: {}.", command, e))?;
+
+				if !output.status.success() {
+					let stderr = String::from_utf8_lossy(&output.stderr);
+					return Err(format!("Command {} failed with {}: {}", command, output.status, stderr.trim()));
+				}
+
+				Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+			}))
+		}
+
+		// Listens on a local port and answers `connections` requests with what `handler(request)` returns. The port is
+		// bound before returning, so clients can connect as soon as the task exists.
+		pub fn serve(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("serve!", 2, &args, true)?;
+
+			let port = crate::stdlib::number_argument("serve!", &args, "port", 0)? as u16;
+			let handler = crate::stdlib::parse_callback(args.get_from_name_or_index("handler".to_string(), 1).unwrap())?;
+			let connections = crate::stdlib::optional_number_argument("serve!", &args, "connections", 2)?.map_or(1, |n| n as usize);
+
+			match TcpListener::bind(("127.0.0.1", port)) {
+				Ok(listener) => Ok(event_loop::serve(listener, handler, connections)),
+				Err(e) => Err(InterpreterResult::Error(format!("Cannot listen on port {}: {}.", port, e))),
+			}
+		}
+
+		// Sends `message` to a local port and completes with everything the server writes back.
+		pub fn send(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("send!", 2, &args, false)?;
+
+			let port = crate::stdlib::number_argument("send!", &args, "port", 0)? as u16;
+			let message = args.get_from_name_or_index("message".to_string(), 1).unwrap().to_string();
+
+			Ok(event_loop::thread(move || {
+				let failed = |e: std::io::Error| format!("Cannot send to port {}: {}.", port, e);
+				let mut stream = TcpStream::connect(("127.0.0.1", port)).map_err(failed)?;
+				let mut reply = String::new();
+
+				stream.write_all(message.as_bytes()).map_err(failed)?;
+				stream.shutdown(Shutdown::Write).map_err(failed)?;
+				stream.read_to_string(&mut reply).map_err(failed)?;
+
+				Ok(Some(reply))
+			}))
+		}
+	}
+
+	// `Worker.spawn!` starts a function on its own thread and interpreter, `receive!` and `post!` are the worker's
+	// side of the channels to the interpreter that started it.
+	pub mod worker {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+			worker::{self, WorkerChannels},
+		};
+		use std::rc::Rc;
+
+		fn channels(interpreter: &Interpreter, name: &str) -> Result<Rc<WorkerChannels>, InterpreterResult> {
+			match &interpreter.worker {
+				Some(channels) => Ok(Rc::clone(channels)),
+				None => Err(InterpreterResult::Error(format!("Worker.{}() can only be called inside a worker.", name))),
+			}
+		}
+
+		pub fn spawn(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("spawn!", 1, &args, true)?;
+
+			let mut values = args.get_all_values();
+			let function = crate::stdlib::parse_callback(values.remove(0))?;
+
+			Ok(Value::Worker(Rc::new(worker::spawn(interpreter, &function, values)?)))
+		}
+
+		// Waits for the next value sent with `worker.send!()`, null once the parent dropped the worker.
+		pub fn receive(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("receive!", 0, &args, false)?;
+
+			Ok(channels(interpreter, "receive!")?.inbox.recv().map_or(Value::Null, worker::import))
+		}
+
+		pub fn post(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("post!", 1, &args, false)?;
+
+			let message = worker::export(&args.get_from_name_or_index("message".to_string(), 0).unwrap())?;
+
+			Ok(Value::Bool(channels(interpreter, "post!")?.outbox.send(message).is_ok()))
+		}
+
+		pub fn cores(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("cores?", 0, &args, false)?;
+
+			Ok(Value::Number(worker::cores() as f64))
+		}
+	}
+
+	pub mod runtime {
+		use super::{arity, record};
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			gc,
+			interpreter::{Interpreter, InterpreterResult},
+		};
+
+		// Runs the cycle collector right away, returns how many lists, maps and instances it freed.
+		pub fn gc(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("gc!", 0, &args, false)?;
+
+			Ok(Value::Number(gc::collect() as f64))
+		}
+
+		// Live lists, maps and struct instances, plus how many collections ran and what they freed so far.
+		pub fn memory(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("memory?", 0, &args, false)?;
+
+			let stats = gc::stats();
+			let fields = [("lists", stats.lists), ("maps", stats.maps), ("instances", stats.instances), ("collections", stats.collections), ("collected", stats.collected)];
+
+			Ok(record("Memory", fields.iter().map(|(name, count)| (*name, Value::Number(*count as f64))).collect()))
+		}
+	}
+
+	// Synchronous file system access, every failure is an error a `try` block can catch.
+	pub mod file {
+		use super::{arity, record, resolve};
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+			iterator::ValueIterator,
+			zone::Zone,
+		};
+		use chrono::{DateTime, Utc};
+		use std::{
+			fs::{self, File, OpenOptions},
+			io::{BufRead, BufReader, Write},
+			path::{Path, PathBuf},
+		};
+
+		fn path(interpreter: &Interpreter, args: &ArgumentValues, name: &str, index: usize) -> PathBuf {
+			resolve(interpreter, args.get_from_name_or_index(name.to_string(), index).unwrap().to_string())
+		}
+
+		fn failed<'p>(action: &'p str, path: &'p Path) -> impl FnOnce(std::io::Error) -> InterpreterResult + 'p {
+			move |e| InterpreterResult::Error(format!("Cannot {} {}: {}.", action, path.display(), e))
+		}
+
+		pub fn read(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("read!", 1, &args, false)?;
+
+			let path = path(interpreter, &args, "path", 0);
+
+			fs::read_to_string(&path).map(Value::String).map_err(failed("read file", &path))
+		}
+
+		pub fn write(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("write!", 2, &args, false)?;
+
+			let path = path(interpreter, &args, "path", 0);
+			let contents = interpreter.stringify(args.get_from_name_or_index("contents".to_string(), 1).unwrap())?;
+
+			fs::write(&path, contents).map_err(failed("write file", &path))?;
+
+			Ok(Value::Null)
+		}
+
+		// Creates the file when it doesn't exist yet.
+		pub fn append(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("append!", 2, &args, false)?;
+
+			let path = path(interpreter, &args, "path", 0);
+			let contents = interpreter.stringify(args.get_from_name_or_index("contents".to_string(), 1).unwrap())?;
+
+			OpenOptions::new().append(true).create(true).open(&path).and_then(|mut file| file.write_all(contents.as_bytes())).map_err(failed("append to file", &path))?;
 
-			Value::DateTime(chrono::offset::Utc::now())
+			Ok(Value::Null)
 		}
+
+		pub fn read_lines(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("readLines!", 1, &args, false)?;
+
+			let path = path(interpreter, &args, "path", 0);
+			let contents = fs::read_to_string(&path).map_err(failed("read file", &path))?;
+
+			Ok(Value::list(contents.lines().map(|line| Value::String(line.to_string())).collect()))
+		}
+
+		// Iterator over the lines of a file, only the line being handled is held in memory.
+		pub fn lines(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("lines!", 1, &args, false)?;
+
+			let path = path(interpreter, &args, "path", 0);
+			let file = File::open(&path).map_err(failed("read file", &path))?;
+
+			Ok(ValueIterator::File(BufReader::new(file).lines()).shared())
+		}
+
+		pub fn exists(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("exists?", 1, &args, false)?;
+
+			Ok(Value::Bool(path(interpreter, &args, "path", 0).exists()))
+		}
+
+		pub fn remove(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("remove!", 1, &args, false)?;
+
+			let path = path(interpreter, &args, "path", 0);
+
+			fs::remove_file(&path).map_err(failed("remove file", &path))?;
+
+			Ok(Value::Null)
+		}
+
+		pub fn copy(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("copy!", 2, &args, false)?;
+
+			let (from, to) = (path(interpreter, &args, "from", 0), path(interpreter, &args, "to", 1));
+
+			fs::copy(&from, &to).map_err(failed("copy file", &from))?;
+
+			Ok(Value::Null)
+		}
+
+		pub fn rename(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("rename!", 2, &args, false)?;
+
+			let (from, to) = (path(interpreter, &args, "from", 0), path(interpreter, &args, "to", 1));
+
+			fs::rename(&from, &to).map_err(failed("rename file", &from))?;
+
+			Ok(Value::Null)
+		}
+
+		// Size in bytes, last modification time and whether the path is a directory.
+		pub fn metadata(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("metadata?", 1, &args, false)?;
+
+			let path = path(interpreter, &args, "path", 0);
+			let metadata = fs::metadata(&path).map_err(failed("read metadata of", &path))?;
+			let modified = metadata.modified().map_err(failed("read modification time of", &path))?;
+
+			Ok(record(
+				"Metadata",
+				vec![
+					("size", Value::Number(metadata.len() as f64)),
+					("modified", Value::DateTime(DateTime::<Utc>::from(modified).with_timezone(&Zone::utc()))),
+					("directory", Value::Bool(metadata.is_dir())),
+				],
+			))
+		}
+	}
+
+	pub mod dir {
+		use super::{arity, resolve};
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+		};
+		use std::{
+			fs,
+			path::{Path, PathBuf},
+		};
+
+		// Names of the entries of a directory, sorted. Defaults to the script's directory.
+		pub fn list(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("list!", 0, &args, true)?;
+
+			let path = resolve(interpreter, args.get_from_name_or_index("path".to_string(), 0).map_or(".".to_string(), |path| path.to_string()));
+			let failed = |e: std::io::Error| InterpreterResult::Error(format!("Cannot list directory {}: {}.", path.display(), e));
+
+			let mut names = Vec::new();
+
+			for entry in fs::read_dir(&path).map_err(failed)? {
+				names.push(entry.map_err(failed)?.file_name().to_string_lossy().to_string());
+			}
+
+			names.sort();
+
+			Ok(Value::list(names.into_iter().map(Value::String).collect()))
+		}
+
+		// Creates the directory along with any missing parents.
+		pub fn create(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("create!", 1, &args, false)?;
+
+			let path = resolve(interpreter, args.get_from_name_or_index("path".to_string(), 0).unwrap().to_string());
+
+			fs::create_dir_all(&path).map_err(|e| InterpreterResult::Error(format!("Cannot create directory {}: {}.", path.display(), e)))?;
+
+			Ok(Value::Null)
+		}
+
+		// Paths matching a pattern such as `data/*.csv` or `**/*.lg`, relative to the script unless the pattern is
+		// absolute. `*` and `?` match within a name, `**` any number of directories.
+		pub fn glob(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("glob!", 1, &args, false)?;
+
+			let pattern = args.get_from_name_or_index("pattern".to_string(), 0).unwrap().to_string();
+			let base = resolve(interpreter, String::new());
+			let components = pattern.split('/').filter(|component| !component.is_empty() && *component != ".").collect::<Vec<&str>>();
+			let root = if pattern.starts_with('/') { PathBuf::from("/") } else { PathBuf::new() };
+
+			let mut paths = Vec::new();
+			walk(&base, root, &components, &mut paths);
+
+			paths.sort();
+			paths.dedup();
+
+			Ok(Value::list(paths.into_iter().map(|path| Value::String(path.to_string_lossy().to_string())).collect()))
+		}
+
+		fn walk(base: &Path, path: PathBuf, components: &[&str], paths: &mut Vec<PathBuf>) {
+			let (component, rest) = match components.split_first() {
+				Some(split) => split,
+				None => return paths.push(path),
+			};
+
+			if !component.contains(['*', '?']) {
+				let next = path.join(component);
+
+				if base.join(&next).exists() {
+					walk(base, next, rest, paths);
+				}
+
+				return;
+			}
+
+			if *component == "**" {
+				walk(base, path.clone(), rest, paths);
+			}
+
+			let entries = match fs::read_dir(base.join(&path)) {
+				Ok(entries) => entries,
+				Err(..) => return,
+			};
+
+			for entry in entries.flatten() {
+				let name = entry.file_name().to_string_lossy().to_string();
+
+				// Hidden entries are only matched by patterns that start with a dot.
+				if name.starts_with('.') && !component.starts_with('.') {
+					continue;
+				}
+
+				if *component == "**" {
+					if entry.path().is_dir() {
+						walk(base, path.join(&name), components, paths);
+					}
+				} else if matches(&component.chars().collect::<Vec<char>>(), &name.chars().collect::<Vec<char>>()) {
+					walk(base, path.join(&name), rest, paths);
+				}
+			}
+		}
+
+		fn matches(pattern: &[char], name: &[char]) -> bool {
+			match (pattern.first(), name.first()) {
+				(None, None) => true,
+				(Some('*'), _) => matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..])),
+				(Some('?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+				(Some(p), Some(n)) if p == n => matches(&pattern[1..], &name[1..]),
+				_ => false,
+			}
+		}
+	}
+
+	pub mod json {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+			json,
+		};
+
+		pub fn parse(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("parse!", 1, &args, false)?;
+
+			json::parse(&args.get_from_name_or_index("source".to_string(), 0).unwrap().to_string())
+		}
+
+		// Compact by default, `pretty` indents nested values by two spaces.
+		pub fn stringify(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("stringify!", 1, &args, true)?;
+
+			let value = args.get_from_name_or_index("value".to_string(), 0).unwrap();
+			let pretty = args.get_from_name_or_index("pretty".to_string(), 1).is_some_and(Value::to_bool);
+
+			Ok(Value::String(json::stringify(&value, pretty)?))
+		}
+	}
+
+	pub mod regexp {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+			stdlib::regexp,
+		};
+
+		// `flags` is any of `i` (case insensitive), `m` (multi-line), `s` (`.` matches newlines) and `x` (verbose).
+		pub fn new(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("new!", 1, &args, true)?;
+
+			let pattern = args.get_from_name_or_index("pattern".to_string(), 0).unwrap().to_string();
+			let flags = args.get_from_name_or_index("flags".to_string(), 1).map_or(String::new(), |flags| flags.to_string());
+
+			if let Some(flag) = flags.chars().find(|flag| !"imsx".contains(*flag)) {
+				return Err(InterpreterResult::Error(format!("Unknown regex flag {}, expected i, m, s or x.", flag)));
+			}
+
+			let pattern = if flags.is_empty() { pattern } else { format!("(?{}){}", flags, pattern) };
+
+			Ok(Value::Regex(regexp::compile(&pattern)?))
+		}
+
+		// Escapes every special character, so the text matches only itself.
+		pub fn escape(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("escape!", 1, &args, false)?;
+
+			Ok(Value::String(regex::escape(&args.get_from_name_or_index("text".to_string(), 0).unwrap().to_string())))
+		}
+	}
+
+	pub mod math {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+			numeric,
+			stdlib::{number_argument, numeric_argument},
+		};
+
+		// One float argument in, one float out.
+		fn unary(name: &str, args: ArgumentValues, operation: fn(f64) -> f64) -> Result<Value, InterpreterResult> {
+			arity(name, 1, &args, false)?;
+
+			Ok(Value::Number(operation(number_argument(name, &args, "x", 0)?)))
+		}
+
+		pub fn abs(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("abs!", 1, &args, false)?;
+
+			Ok(numeric::abs(numeric_argument("abs!", &args, "x", 0)?))
+		}
+
+		pub fn floor(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("floor!", 1, &args, false)?;
+
+			Ok(numeric::floor(numeric_argument("floor!", &args, "x", 0)?))
+		}
+
+		pub fn ceil(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("ceil!", 1, &args, false)?;
+
+			Ok(numeric::ceil(numeric_argument("ceil!", &args, "x", 0)?))
+		}
+
+		// Rounds half away from zero.
+		pub fn round(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("round!", args, f64::round)
+		}
+
+		pub fn sqrt(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("sqrt!", args, f64::sqrt)
+		}
+
+		pub fn cbrt(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("cbrt!", args, f64::cbrt)
+		}
+
+		pub fn exp(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("exp!", args, f64::exp)
+		}
+
+		pub fn sin(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("sin!", args, f64::sin)
+		}
+
+		pub fn cos(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("cos!", args, f64::cos)
+		}
+
+		pub fn tan(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("tan!", args, f64::tan)
+		}
+
+		pub fn asin(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("asin!", args, f64::asin)
+		}
+
+		pub fn acos(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("acos!", args, f64::acos)
+		}
+
+		pub fn atan(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			unary("atan!", args, f64::atan)
+		}
+
+		// Angle of the point (x, y), taking the quadrant into account.
+		pub fn atan2(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("atan2!", 2, &args, false)?;
+
+			Ok(Value::Number(number_argument("atan2!", &args, "y", 0)?.atan2(number_argument("atan2!", &args, "x", 1)?)))
+		}
+
+		pub fn pow(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("pow!", 2, &args, false)?;
+
+			Ok(Value::Number(number_argument("pow!", &args, "base", 0)?.powf(number_argument("pow!", &args, "exponent", 1)?)))
+		}
+
+		// Natural logarithm, or in `base` when one is given.
+		pub fn log(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("log!", 1, &args, true)?;
+
+			let x = number_argument("log!", &args, "x", 0)?;
+
+			Ok(Value::Number(match args.get_from_name_or_index("base".to_string(), 1) {
+				Some(_) => x.log(number_argument("log!", &args, "base", 1)?),
+				None => x.ln(),
+			}))
+		}
+
+		// Takes either several numbers or a single list of them, the result keeps its exact type.
+		fn extreme(name: &str, args: ArgumentValues, wanted: std::cmp::Ordering) -> Result<Value, InterpreterResult> {
+			arity(name, 1, &args, true)?;
+
+			let values = match args.get_from_index(0).unwrap().unwrap_constant() {
+				Value::List(items) if args.len() == 1 => items.borrow().clone(),
+				_ => args.get_raw().into_iter().map(|argument| argument.get_value()).collect(),
+			};
+
+			let mut found: Option<Value> = None;
+
+			for value in values {
+				if !numeric::is_numeric(&value) {
+					return Err(InterpreterResult::Error(format!("{} expects numbers, got {}.", name, value.typestring())));
+				}
+
+				if numeric::to_float(&value).is_some_and(f64::is_nan) {
+					return Ok(Value::Number(f64::NAN));
+				}
+
+				if found.as_ref().is_none_or(|found| numeric::compare(&value, found) == Some(wanted)) {
+					found = Some(value);
+				}
+			}
+
+			found.ok_or_else(|| InterpreterResult::Error(format!("{} expects at least one number.", name)))
+		}
+
+		pub fn min(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			extreme("min!", args, std::cmp::Ordering::Less)
+		}
+
+		pub fn max(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			extreme("max!", args, std::cmp::Ordering::Greater)
+		}
+
+		// Limits `x` to the `min..=max` range.
+		pub fn clamp(_interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("clamp!", 3, &args, false)?;
+
+			let x = numeric_argument("clamp!", &args, "x", 0)?;
+			let min = numeric_argument("clamp!", &args, "min", 1)?;
+			let max = numeric_argument("clamp!", &args, "max", 2)?;
+
+			if numeric::compare(&min, &max) == Some(std::cmp::Ordering::Greater) {
+				return Err(InterpreterResult::Error(format!("clamp! expects min to be at most max, got {:?} and {:?}.", min, max)));
+			}
+
+			Ok(if numeric::compare(&x, &min) == Some(std::cmp::Ordering::Less) {
+				min
+			} else if numeric::compare(&x, &max) == Some(std::cmp::Ordering::Greater) {
+				max
+			} else {
+				x
+			})
+		}
+	}
+
+	pub mod random {
+		use super::arity;
+		use crate::{
+			ast::ArgumentValues,
+			environment::Value,
+			interpreter::{Interpreter, InterpreterResult},
+			numeric,
+			random::Random,
+		};
+		use std::{cell::RefCell, rc::Rc};
+
+		fn number(name: &str, args: &ArgumentValues, argument: &str, index: usize) -> Result<f64, InterpreterResult> {
+			match args.get_from_name_or_index(argument.to_string(), index) {
+				Some(value) => match numeric::to_float(&value) {
+					Some(number) if number.is_finite() => Ok(number),
+					_ => Err(InterpreterResult::Error(format!("{} expects a finite number for {}, got {:?}.", name, argument, value))),
+				},
+				None => Err(InterpreterResult::Error(format!("{} expects a number for {}.", name, argument))),
+			}
+		}
+
+		fn list(name: &str, args: &ArgumentValues) -> Result<Rc<RefCell<Vec<Value>>>, InterpreterResult> {
+			match args.get_from_name_or_index("list".to_string(), 0).unwrap().unwrap_constant() {
+				Value::List(items) => Ok(items),
+				value => Err(InterpreterResult::Error(format!("{} expects a list, got {}.", name, value.typestring()))),
+			}
+		}
+
+		// Whole number between `min` and `max`, both included.
+		pub fn int(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("int!", 2, &args, false)?;
+
+			let min = number("int!", &args, "min", 0)?.ceil();
+			let max = number("int!", &args, "max", 1)?.floor();
+
+			if min > max {
+				return Err(InterpreterResult::Error(format!("int! expects min to be at most max, got {} and {}.", min, max)));
+			}
+
+			if max - min >= numeric::MAX_SAFE_INTEGER {
+				return Err(InterpreterResult::Error(format!("int! range from {} to {} is too wide.", min, max)));
+			}
+
+			Ok(Value::Number(min + interpreter.random.below((max - min) as u64 + 1) as f64))
+		}
+
+		// Between 0 and 1 by default, or between `min` and `max`, `max` excluded.
+		pub fn float(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			if args.is_empty() {
+				return Ok(Value::Number(interpreter.random.float()));
+			}
+
+			arity("float!", 2, &args, false)?;
+
+			let min = number("float!", &args, "min", 0)?;
+			let max = number("float!", &args, "max", 1)?;
+
+			if min > max {
+				return Err(InterpreterResult::Error(format!("float! expects min to be at most max, got {} and {}.", min, max)));
+			}
+
+			Ok(Value::Number(min + interpreter.random.float() * (max - min)))
+		}
+
+		// True with the given probability, half of the time by default.
+		pub fn boolean(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			let probability = if args.is_empty() { 0.5 } else { number("bool!", &args, "probability", 0)? };
+
+			if !(0.0..=1.0).contains(&probability) {
+				return Err(InterpreterResult::Error(format!("bool! expects a probability between 0 and 1, got {}.", probability)));
+			}
+
+			Ok(Value::Bool(interpreter.random.float() < probability))
+		}
+
+		pub fn choice(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("choice!", 1, &args, false)?;
+
+			let items = list("choice!", &args)?;
+			let items = items.borrow();
+
+			if items.is_empty() {
+				return Err(InterpreterResult::Error("choice! cannot pick from an empty list.".to_string()));
+			}
+
+			Ok(items[interpreter.random.below(items.len() as u64) as usize].clone())
+		}
+
+		// Shuffles the list in place.
+		pub fn shuffle(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("shuffle!", 1, &args, false)?;
+
+			let items = list("shuffle!", &args)?;
+
+			interpreter.random.shuffle(&mut items.borrow_mut());
+
+			Ok(Value::Null)
+		}
+
+		// `count` items picked from different positions of the list, in random order.
+		pub fn sample(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("sample!", 2, &args, false)?;
+
+			let mut items = list("sample!", &args)?.borrow().clone();
+			let count = number("sample!", &args, "count", 1)?;
+
+			if count < 0.0 || count > items.len() as f64 {
+				return Err(InterpreterResult::Error(format!("sample! cannot pick {} items from a list of {}.", count, items.len())));
+			}
+
+			interpreter.random.shuffle(&mut items);
+			items.truncate(count as usize);
+
+			Ok(Value::list(items))
+		}
+
+		// Restarts the sequence, the same seed always gives the same numbers.
+		pub fn seed(interpreter: &mut Interpreter, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+			arity("seed!", 1, &args, false)?;
+
+			let seed = number("seed!", &args, "seed", 0)?;
+
+			interpreter.random = Random::new(if seed.fract() == 0.0 { seed as i64 as u64 } else { seed.to_bits() });
+
+			Ok(Value::Null)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::{caught, evaluate, evaluate_in, register_global_functions, register_global_structs, Interpreter};
+
+	#[test]
+	fn it_reads_the_arguments_given_after_the_script() {
+		let mut interpreter = Interpreter::new([].iter(), std::env::temp_dir().join("test.lg"));
+		interpreter.args = vec!["hello".to_string(), "--verbose".to_string()];
+
+		register_global_functions(&mut interpreter);
+		register_global_structs(&mut interpreter);
+
+		assert_eq!(evaluate_in(&mut interpreter, "Application.args?()").unwrap().to_string(), "[hello, --verbose]");
+	}
+
+	#[test]
+	fn it_keeps_variables_set_by_the_script_out_of_the_process_environment() {
+		assert_eq!(evaluate("Env.set!(\"LUGLI_TEST_SET\", 1)\nEnv.get?(\"LUGLI_TEST_SET\")").unwrap().to_string(), "1");
+		assert_eq!(evaluate("Env.get?(\"LUGLI_TEST_MISSING\", \"fallback\")").unwrap().to_string(), "fallback");
+		assert!(std::env::var_os("LUGLI_TEST_SET").is_none());
+
+		// Programs and workers the script starts still see them.
+		let child = "Env.set!(\"LUGLI_TEST_CHILD\", \"child\")\ncreate result = Process.run!(\"sh\", [\"-c\", \"echo $LUGLI_TEST_CHILD\"])\nresult.stdout";
+
+		assert_eq!(evaluate(child).unwrap().to_string(), "child\n");
+		assert_eq!(evaluate("Env.set!(\"LUGLI_TEST_TASK\", \"task\")\nawait Async.run!(\"sh\", [\"-c\", \"echo $LUGLI_TEST_TASK\"])").unwrap().to_string(), "task\n");
+		assert_eq!(evaluate("Env.set!(\"LUGLI_TEST_WORKER\", \"worker\")\nWorker.spawn!(fn () { return Env.get?(\"LUGLI_TEST_WORKER\") }).join!()").unwrap().to_string(), "worker");
+		assert_eq!(caught("Env.set!(\"A=B\", 1)"), "Invalid environment variable A=B=1.");
+	}
+
+	#[test]
+	fn it_runs_programs_with_input_and_a_timeout() {
+		assert_eq!(evaluate("create sorted = Process.run!(\"sort\", stdin = \"pear\nfig\n\")\nsorted.stdout").unwrap().to_string(), "fig\npear\n");
+
+		let failing = "create failing = Process.run!(\"sh\", [\"-c\", \"echo oops >&2; exit 3\"])\ncreate result = [failing.code, failing.success, failing.stderr.trim!()]\nresult";
+
+		assert_eq!(evaluate(failing).unwrap().to_string(), "[3, false, oops]");
+		assert_eq!(caught("Process.run!(\"sleep\", [\"5\"], timeout = 50)"), "sleep timed out after 50 ms.");
+		assert!(caught("Process.run!(\"lugli-command-that-does-not-exist\")").starts_with("Cannot run lugli-command-that-does-not-exist: "));
 	}
 }
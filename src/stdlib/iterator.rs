@@ -0,0 +1,55 @@
+use crate::{
+	ast::ArgumentValues,
+	environment::{NativeMethodCallback, Value},
+	interpreter::{Interpreter, InterpreterResult},
+	iterator::ValueIterator,
+};
+
+pub struct IteratorObject;
+
+impl IteratorObject {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
+			"next!" => iterator_next,
+			"take!" => iterator_take,
+			"collect!" => iterator_collect,
+			_ => return None,
+		})
+	}
+}
+
+fn iterator_next(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Iterator.next!()", 0, &args, false)?;
+
+	let mut iterator = interpreter.iterate(context)?;
+
+	Ok(iterator.next(interpreter)?.unwrap_or(Value::Null))
+}
+
+// Lazily limits the iterator to its next `n` items, so infinite generators can be collected.
+fn iterator_take(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Iterator.take!()", 1, &args, false)?;
+
+	let count = args.get_from_name_or_index("count".to_string(), 0).unwrap();
+	let remaining = match count.clone().unwrap_constant() {
+		Value::Number(n) if n >= 0.0 && n.fract() == 0.0 => n as usize,
+		value => return Err(InterpreterResult::TypeMismatch("Iterator.take!()".to_string(), "a non-negative integer".to_string(), value.typestring())),
+	};
+
+	let inner = interpreter.iterate(context)?;
+
+	Ok(ValueIterator::Take { inner: Box::new(inner), remaining }.shared())
+}
+
+fn iterator_collect(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Iterator.collect!()", 0, &args, false)?;
+
+	let mut iterator = interpreter.iterate(context)?;
+	let mut items = Vec::new();
+
+	while let Some(item) = iterator.next(interpreter)? {
+		items.push(item);
+	}
+
+	Ok(Value::list(items))
+}
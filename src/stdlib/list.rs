@@ -1,58 +1,84 @@
-use std::{cell::RefCell, rc::Rc};
+use std::cmp::Ordering;
 
 use crate::{
-	ast::{ArgumentValued, ArgumentValues},
+	ast::{ArgumentValued, ArgumentValues, Op},
 	environment::{NativeMethodCallback, Value},
 	interpreter::{Interpreter, InterpreterResult},
+	map::Map,
+	numeric,
 };
 
 pub struct ListObject;
 
 impl ListObject {
-	pub fn get(name: String) -> NativeMethodCallback {
-		match name.as_str() {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
 			"empty?" => list_is_empty,
 			"reverse!" => list_reverse,
 			"join!" => list_join,
 			"filter!" => list_filter,
 			"each!" => list_each,
 			"map!" => list_map,
+			"parallelMap!" => list_parallel_map,
 			"first!" => list_first,
 			"push!" => list_push,
-			_ => panic!("Undefined method: {} for List Object", name),
-		}
+			"len?" => list_len,
+			"sort!" => list_sort,
+			"sortBy!" => list_sort_by,
+			"slice!" => list_slice,
+			"pop!" => list_pop,
+			"insert!" => list_insert,
+			"remove!" => list_remove,
+			"indexOf?" => list_index_of,
+			"reduce!" => list_reduce,
+			"any?" => list_any,
+			"all?" => list_all,
+			"sum?" => list_sum,
+			"min?" => list_min,
+			"max?" => list_max,
+			"flatten!" => list_flatten,
+			"unique!" => list_unique,
+			"zip!" => list_zip,
+			"chunk!" => list_chunk,
+			"groupBy!" => list_group_by,
+			_ => return None,
+		})
 	}
 }
 
 fn list_is_empty(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("List.empty?()", 0, &args, false);
+	super::arity("List.empty?()", 0, &args, false)?;
 
 	Ok(Value::Bool(context.to_vec().borrow().is_empty()))
 }
 
 fn list_reverse(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("List.reverse!()", 0, &args, false);
+	super::arity("List.reverse!()", 0, &args, false)?;
 
 	context.to_vec().borrow_mut().reverse();
 
 	Ok(Value::Null)
 }
 
-fn list_join(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("List.join!()", 1, &args, false);
+fn list_join(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.join!()", 1, &args, false)?;
 
 	let list = context.to_vec().borrow().clone();
 	let separator = args.get_from_name_or_index("list".to_string(), 0).unwrap().to_string();
-	let result = list.into_iter().map(|a| a.to_string()).collect::<Vec<String>>().join(&separator);
+	let mut items = Vec::new();
+
+	for item in list {
+		items.push(interpreter.stringify(item)?);
+	}
 
-	Ok(Value::String(result))
+	Ok(Value::String(items.join(&separator)))
 }
 
 fn list_filter(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("List.filter!()", 1, &args, false);
+	super::arity("List.filter!()", 1, &args, false)?;
 
 	let mut callback = args.get_from_name_or_index("callback".to_string(), 0).unwrap();
-	callback = super::parse_callback(callback);
+	callback = super::parse_callback(callback)?;
 
 	let mut new_list = Vec::new();
 
@@ -65,14 +91,14 @@ fn list_filter(interpreter: &mut Interpreter, context: Value, args: ArgumentValu
 		}
 	}
 
-	Ok(Value::List(Rc::new(RefCell::new(new_list))))
+	Ok(Value::list(new_list))
 }
 
 fn list_each(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("List.each!()", 1, &args, false);
+	super::arity("List.each!()", 1, &args, false)?;
 
 	let mut callback = args.get_from_name_or_index("callback".to_string(), 0).unwrap();
-	callback = super::parse_callback(callback);
+	callback = super::parse_callback(callback)?;
 
 	for v in context.clone().to_vec().borrow().iter() {
 		let mut args = ArgumentValues::new();
@@ -85,10 +111,10 @@ fn list_each(interpreter: &mut Interpreter, context: Value, args: ArgumentValues
 }
 
 fn list_map(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("List.map!()", 1, &args, false);
+	super::arity("List.map!()", 1, &args, false)?;
 
 	let mut callback = args.get_from_name_or_index("callback".to_string(), 0).unwrap();
-	callback = super::parse_callback(callback);
+	callback = super::parse_callback(callback)?;
 
 	let mut list = context.clone().to_vec().borrow().clone();
 
@@ -101,7 +127,7 @@ fn list_map(interpreter: &mut Interpreter, context: Value, args: ArgumentValues)
 		list[i] = result;
 	}
 
-	Ok(Value::List(Rc::new(RefCell::new(list))))
+	Ok(Value::list(list))
 }
 
 fn list_first(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
@@ -113,7 +139,7 @@ fn list_first(interpreter: &mut Interpreter, context: Value, args: ArgumentValue
 
 	if args.len() == 1 {
 		let mut callback = args.get_from_name_or_index("callback".to_string(), 0).unwrap();
-		callback = super::parse_callback(callback);
+		callback = super::parse_callback(callback)?;
 
 		for v in list.iter() {
 			let mut args = ArgumentValues::new();
@@ -131,7 +157,7 @@ fn list_first(interpreter: &mut Interpreter, context: Value, args: ArgumentValue
 }
 
 fn list_push(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("List.push!()", 1, &args, false);
+	super::arity("List.push!()", 1, &args, false)?;
 
 	let list = context.to_vec();
 
@@ -139,3 +165,391 @@ fn list_push(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Resul
 
 	Ok(Value::Null)
 }
+
+// Like `map!`, but the items are split between worker threads, each with its own interpreter. The callback and the
+// items are deep copied, so the callback can't change anything outside of the item it's given.
+fn list_parallel_map(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.parallelMap!()", 1, &args, true)?;
+
+	let callback = super::parse_callback(args.get_from_name_or_index("callback".to_string(), 0).unwrap())?;
+	let workers = super::optional_number_argument("List.parallelMap!()", &args, "workers", 1)?.map_or_else(crate::worker::cores, |n| n as usize);
+	let items = context.to_vec().borrow().clone();
+
+	let results = crate::worker::parallel_map(interpreter, &callback, items, workers)?;
+
+	Ok(Value::list(results))
+}
+
+fn invoke(interpreter: &mut Interpreter, callback: &Value, values: Vec<Value>) -> Result<Value, InterpreterResult> {
+	let mut args = ArgumentValues::new();
+
+	for value in values {
+		args.push(ArgumentValued::new(None, value));
+	}
+
+	interpreter.call(callback.clone(), args)
+}
+
+fn compare(left: &Value, right: &Value) -> Result<Ordering, InterpreterResult> {
+	left.compare(right).ok_or_else(|| InterpreterResult::Error(format!("Cannot compare {} with {}.", left.clone().typestring(), right.clone().typestring())))
+}
+
+// Stable merge sort. Unlike `slice::sort_by` it stops at the first failing comparison and copes with comparators
+// that aren't a total order.
+fn merge_sort(items: Vec<Value>, compare: &mut impl FnMut(&Value, &Value) -> Result<Ordering, InterpreterResult>) -> Result<Vec<Value>, InterpreterResult> {
+	if items.len() <= 1 {
+		return Ok(items);
+	}
+
+	let mut left = items;
+	let right = left.split_off(left.len() / 2);
+	let (left, right) = (merge_sort(left, compare)?, merge_sort(right, compare)?);
+
+	let mut sorted = Vec::with_capacity(left.len() + right.len());
+	let mut left = left.into_iter().peekable();
+	let mut right = right.into_iter().peekable();
+
+	while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+		if compare(l, r)? == Ordering::Greater {
+			sorted.push(right.next().unwrap());
+		} else {
+			sorted.push(left.next().unwrap());
+		}
+	}
+
+	sorted.extend(left);
+	sorted.extend(right);
+
+	Ok(sorted)
+}
+
+fn list_len(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.len?()", 0, &args, false)?;
+
+	Ok(Value::Number(context.to_vec().borrow().len() as f64))
+}
+
+// Sorts in place in ascending order, numbers, strings, datetimes and lists can be sorted but not mixed.
+fn list_sort(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.sort!()", 0, &args, false)?;
+
+	let list = context.to_vec();
+	let items = list.borrow().clone();
+	let sorted = merge_sort(items, &mut compare)?;
+
+	*list.borrow_mut() = sorted;
+
+	Ok(Value::Null)
+}
+
+// Sorts in place with `comparator(a, b)`, which returns a negative number when `a` goes first, a positive one when
+// `b` does and zero to keep their order.
+fn list_sort_by(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.sortBy!()", 1, &args, false)?;
+
+	let comparator = super::parse_callback(args.get_from_name_or_index("comparator".to_string(), 0).unwrap())?;
+	let list = context.to_vec();
+	let items = list.borrow().clone();
+
+	let sorted = merge_sort(items, &mut |l, r| {
+		let result = invoke(interpreter, &comparator, vec![l.clone(), r.clone()])?;
+
+		if !numeric::is_numeric(&result.clone().unwrap_constant()) {
+			return Err(InterpreterResult::Error(format!("List.sortBy!() comparator must return a number, got {}.", result.typestring())));
+		}
+
+		compare(&result, &Value::Number(0.0))
+	})?;
+
+	*list.borrow_mut() = sorted;
+
+	Ok(Value::Null)
+}
+
+// Items from `start` up to, not including, `end` (the end of the list by default). Negative bounds count from the end.
+fn list_slice(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.slice!()", 1, &args, true)?;
+
+	let items = context.to_vec().borrow().clone();
+	let start = super::bound(super::number_argument("List.slice!()", &args, "start", 0)?, items.len());
+	let end = super::optional_number_argument("List.slice!()", &args, "end", 1)?.map_or(items.len(), |end| super::bound(end, items.len()));
+
+	Ok(Value::list(items[start..end.max(start)].to_vec()))
+}
+
+fn list_pop(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.pop!()", 0, &args, false)?;
+
+	context.to_vec().borrow_mut().pop().ok_or_else(|| InterpreterResult::Error("Cannot pop from an empty list.".to_string()))
+}
+
+// Inserts before the item at `index`, which can also be the length of the list to append.
+fn list_insert(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.insert!()", 2, &args, false)?;
+
+	let list = context.to_vec();
+	let index = super::number_argument("List.insert!()", &args, "index", 0)?;
+	let value = args.get_from_name_or_index("value".to_string(), 1).unwrap();
+	let len = list.borrow().len();
+	// A negative index inserts before the item it refers to, like everywhere else.
+	let len = if index < 0.0 { len } else { len + 1 };
+
+	match super::position(index, len) {
+		Some(position) => list.borrow_mut().insert(position, value),
+		None => return Err(InterpreterResult::UndefinedIndex(index as i64)),
+	}
+
+	Ok(Value::Null)
+}
+
+// Removes and returns the item at `index`.
+fn list_remove(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.remove!()", 1, &args, false)?;
+
+	let list = context.to_vec();
+	let index = super::number_argument("List.remove!()", &args, "index", 0)?;
+	let len = list.borrow().len();
+
+	match super::position(index, len) {
+		Some(position) => Ok(list.borrow_mut().remove(position)),
+		None => Err(InterpreterResult::UndefinedIndex(index as i64)),
+	}
+}
+
+// Position of the first item equal to `value`, -1 when there is none.
+fn list_index_of(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.indexOf?()", 1, &args, false)?;
+
+	let value = args.get_from_name_or_index("value".to_string(), 0).unwrap();
+	let position = context.to_vec().borrow().iter().position(|item| item.equals(&value));
+
+	Ok(Value::Number(position.map_or(-1.0, |position| position as f64)))
+}
+
+// Folds the items with `callback(accumulator, item)`, starting from `initial` or else the first item.
+fn list_reduce(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.reduce!()", 1, &args, true)?;
+
+	let callback = super::parse_callback(args.get_from_name_or_index("callback".to_string(), 0).unwrap())?;
+	let mut items = context.to_vec().borrow().clone().into_iter();
+
+	let mut accumulator = match args.get_from_name_or_index("initial".to_string(), 1) {
+		Some(initial) => initial,
+		None => items.next().ok_or_else(|| InterpreterResult::Error("Cannot reduce an empty list without an initial value.".to_string()))?,
+	};
+
+	for item in items {
+		accumulator = invoke(interpreter, &callback, vec![accumulator, item])?;
+	}
+
+	Ok(accumulator)
+}
+
+fn list_any(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.any?()", 1, &args, false)?;
+
+	let callback = super::parse_callback(args.get_from_name_or_index("callback".to_string(), 0).unwrap())?;
+
+	for item in context.to_vec().borrow().clone() {
+		if invoke(interpreter, &callback, vec![item])?.to_bool() {
+			return Ok(Value::Bool(true));
+		}
+	}
+
+	Ok(Value::Bool(false))
+}
+
+fn list_all(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.all?()", 1, &args, false)?;
+
+	let callback = super::parse_callback(args.get_from_name_or_index("callback".to_string(), 0).unwrap())?;
+
+	for item in context.to_vec().borrow().clone() {
+		if !invoke(interpreter, &callback, vec![item])?.to_bool() {
+			return Ok(Value::Bool(false));
+		}
+	}
+
+	Ok(Value::Bool(true))
+}
+
+fn list_sum(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.sum?()", 0, &args, false)?;
+
+	let mut total = Value::Number(0.0);
+
+	for item in context.to_vec().borrow().iter() {
+		if !numeric::is_numeric(&item.clone().unwrap_constant()) {
+			return Err(InterpreterResult::Error(format!("List.sum?() expects a list of numbers, found {}.", item.clone().typestring())));
+		}
+
+		total = numeric::arithmetic(total, Op::Add, item.clone())?;
+	}
+
+	Ok(total)
+}
+
+fn extreme(context: Value, wanted: Ordering) -> Result<Value, InterpreterResult> {
+	let items = context.to_vec().borrow().clone();
+	let mut items = items.into_iter();
+	let mut found = match items.next() {
+		Some(first) => first,
+		None => return Ok(Value::Null),
+	};
+
+	for item in items {
+		if compare(&item, &found)? == wanted {
+			found = item;
+		}
+	}
+
+	Ok(found)
+}
+
+// Smallest item, null for an empty list.
+fn list_min(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.min?()", 0, &args, false)?;
+
+	extreme(context, Ordering::Less)
+}
+
+fn list_max(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.max?()", 0, &args, false)?;
+
+	extreme(context, Ordering::Greater)
+}
+
+fn flatten(items: Vec<Value>, depth: usize) -> Vec<Value> {
+	let mut flat = Vec::new();
+
+	for item in items {
+		match item.clone().unwrap_constant() {
+			Value::List(inner) if depth > 0 => flat.extend(flatten(inner.borrow().clone(), depth - 1)),
+			_ => flat.push(item),
+		}
+	}
+
+	flat
+}
+
+// Flattens nested lists `depth` levels deep, one by default.
+fn list_flatten(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.flatten!()", 0, &args, true)?;
+
+	let depth = super::optional_number_argument("List.flatten!()", &args, "depth", 0)?.unwrap_or(1.0).max(0.0) as usize;
+
+	Ok(Value::list(flatten(context.to_vec().borrow().clone(), depth)))
+}
+
+// Keeps the first of each group of equal items.
+fn list_unique(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.unique!()", 0, &args, false)?;
+
+	let mut unique: Vec<Value> = Vec::new();
+
+	for item in context.to_vec().borrow().iter() {
+		if !unique.iter().any(|seen| seen.equals(item)) {
+			unique.push(item.clone());
+		}
+	}
+
+	Ok(Value::list(unique))
+}
+
+// Pairs items by position, stopping at the end of the shorter list.
+fn list_zip(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.zip!()", 1, &args, false)?;
+
+	let other = match args.get_from_name_or_index("other".to_string(), 0).unwrap().unwrap_constant() {
+		Value::List(other) => other.borrow().clone(),
+		other => return Err(InterpreterResult::Error(format!("List.zip!() expects a list, got {}.", other.typestring()))),
+	};
+
+	let items = context.to_vec().borrow().clone();
+
+	Ok(Value::list(items.into_iter().zip(other).map(|(l, r)| Value::list(vec![l, r])).collect()))
+}
+
+// Splits into lists of `size` items, the last one holds what is left.
+fn list_chunk(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.chunk!()", 1, &args, false)?;
+
+	let size = super::number_argument("List.chunk!()", &args, "size", 0)?;
+
+	if size < 1.0 || !size.is_finite() {
+		return Err(InterpreterResult::Error(format!("List.chunk!() expects a size of at least 1, got {}.", size)));
+	}
+
+	let items = context.to_vec().borrow().clone();
+
+	Ok(Value::list(items.chunks(size as usize).map(|chunk| Value::list(chunk.to_vec())).collect()))
+}
+
+// Map from what `callback` returns for each item, as a string, to the list of those items, in first seen order.
+fn list_group_by(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("List.groupBy!()", 1, &args, false)?;
+
+	let callback = super::parse_callback(args.get_from_name_or_index("callback".to_string(), 0).unwrap())?;
+	let mut groups = Map::new();
+
+	for item in context.to_vec().borrow().clone() {
+		let key = invoke(interpreter, &callback, vec![item.clone()])?;
+		let key = interpreter.stringify(key)?;
+
+		match groups.get(&key) {
+			Some(Value::List(group)) => group.borrow_mut().push(item),
+			_ => groups.set(key, Value::list(vec![item])),
+		}
+	}
+
+	Ok(Value::map(groups))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::{caught, evaluate};
+
+	fn list(source: &str) -> String {
+		evaluate(source).unwrap().to_string()
+	}
+
+	#[test]
+	fn it_reads_and_changes_lists_with_negative_indexes() {
+		assert_eq!(list("create l = [1, 2, 3]\nl[-1]"), "3");
+		assert_eq!(list("create l = [1, 2, 3]\nl[-1] = 9\nl"), "[1, 2, 9]");
+		assert_eq!(list("create l = [1, 2, 3, 4]\nl.slice!(1, -1)"), "[2, 3]");
+		assert_eq!(list("create l = [1, 3]\nl.insert!(1, 2)\nl"), "[1, 2, 3]");
+		assert_eq!(list("create l = [1, 2, 3]\nl.remove!(-1)\nl"), "[1, 2]");
+		assert_eq!(list("create l = [1, 2]\nl.pop!()\nl"), "[1]");
+		assert_eq!(caught("create l = [1, 2]\nl[5]"), "Undefined index: 5.");
+		assert_eq!(caught("create l = []\nl.pop!()"), "Cannot pop from an empty list.");
+	}
+
+	#[test]
+	fn it_rejects_indexes_that_are_not_numbers() {
+		assert_eq!(caught("create l = [1, 2]\nl[[0]]"), "TypeError: unsupported operand types for []: list and list.");
+		assert_eq!(caught("create l = [1, 2]\nl[[0]] = 3"), "TypeError: unsupported operand types for []: list and list.");
+		assert_eq!(caught("create l = [1, 2]\nl[\"0\"]"), "TypeError: unsupported operand types for []: list and string.");
+	}
+
+	#[test]
+	fn it_sorts_searches_and_reduces_lists() {
+		assert_eq!(list("create l = [3, 1, 2]\nl.sort!()\nl"), "[1, 2, 3]");
+		assert_eq!(list("create l = [3, 1, 2]\nl.sortBy!(fn (a, b) {\n\treturn b - a\n})\nl"), "[3, 2, 1]");
+		assert_eq!(list("create l = [1, 2, 3]\nl.indexOf?(2)"), "1");
+		assert_eq!(list("create l = [1, 2, 3]\nl.reduce!(fn (total, n) {\n\treturn total + n\n}, 10)"), "16");
+		assert_eq!(list("create l = [1, 2, 3]\nl.any?(fn (n) {\n\treturn n > 2\n})"), "true");
+		assert_eq!(list("create l = [1, 2, 3]\nl.all?(fn (n) {\n\treturn n > 2\n})"), "false");
+		assert_eq!(list("create l = [4, 1, 3]\ncreate result = [l.sum?(), l.min?(), l.max?()]\nresult"), "[8, 1, 4]");
+		assert_eq!(caught("create l = [1, \"a\"]\nl.sum?()"), "List.sum?() expects a list of numbers, found string.");
+	}
+
+	#[test]
+	fn it_reshapes_lists() {
+		assert_eq!(list("create l = [1, [2, [3]]]\nl.flatten!()"), "[1, 2, [3]]");
+		assert_eq!(list("create l = [1, 2, 1, 3]\nl.unique!()"), "[1, 2, 3]");
+		assert_eq!(list("create l = [1, 2]\nl.zip!([\"a\", \"b\"])"), "[[1, a], [2, b]]");
+		assert_eq!(list("create l = [1, 2, 3]\nl.chunk!(2)"), "[[1, 2], [3]]");
+		assert_eq!(list("create l = [1, 2, 3]\nl.groupBy!(fn (n) {\n\treturn n % 2\n})"), "{1: [1, 3], 0: [2]}");
+	}
+}
@@ -0,0 +1,93 @@
+use crate::{
+	ast::ArgumentValues,
+	environment::{NativeMethodCallback, Value},
+	interpreter::{Interpreter, InterpreterResult},
+};
+
+pub struct MapObject;
+
+impl MapObject {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
+			"empty?" => map_is_empty,
+			"len?" => map_len,
+			"has?" => map_has,
+			"get?" => map_get,
+			"set!" => map_set,
+			"remove!" => map_remove,
+			"keys!" => map_keys,
+			"values!" => map_values,
+			"entries!" => map_entries,
+			_ => return None,
+		})
+	}
+}
+
+fn key(args: &ArgumentValues) -> String {
+	args.get_from_name_or_index("key".to_string(), 0).unwrap().to_string()
+}
+
+fn map_is_empty(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.empty?()", 0, &args, false)?;
+
+	Ok(Value::Bool(context.to_map().borrow().is_empty()))
+}
+
+fn map_len(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.len?()", 0, &args, false)?;
+
+	Ok(Value::Number(context.to_map().borrow().len() as f64))
+}
+
+fn map_has(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.has?()", 1, &args, false)?;
+
+	Ok(Value::Bool(context.to_map().borrow().contains(&key(&args))))
+}
+
+// Value of `key`, or `default` (null unless given) when the map doesn't have it.
+fn map_get(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.get?()", 1, &args, true)?;
+
+	let default = args.get_from_name_or_index("default".to_string(), 1).unwrap_or(Value::Null);
+
+	Ok(context.to_map().borrow().get(&key(&args)).cloned().unwrap_or(default))
+}
+
+fn map_set(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.set!()", 2, &args, false)?;
+
+	let value = args.get_from_name_or_index("value".to_string(), 1).unwrap();
+
+	context.to_map().borrow_mut().set(key(&args), value);
+
+	Ok(Value::Null)
+}
+
+// Removes `key` and returns its value, null when it wasn't there.
+fn map_remove(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.remove!()", 1, &args, false)?;
+
+	Ok(context.to_map().borrow_mut().remove(&key(&args)).unwrap_or(Value::Null))
+}
+
+fn map_keys(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.keys!()", 0, &args, false)?;
+
+	Ok(Value::list(context.to_map().borrow().keys().map(|key| Value::String(key.clone())).collect()))
+}
+
+fn map_values(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.values!()", 0, &args, false)?;
+
+	Ok(Value::list(context.to_map().borrow().values().cloned().collect()))
+}
+
+fn map_entries(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Map.entries!()", 0, &args, false)?;
+
+	let map = context.to_map();
+	let entries = map.borrow().iter().map(|(key, value)| Value::list(vec![Value::String(key.clone()), value.clone()])).collect();
+
+	Ok(Value::list(entries))
+}
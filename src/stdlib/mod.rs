@@ -1,34 +1,96 @@
-use crate::{ast::ArgumentValues, environment::Value};
+use crate::{ast::ArgumentValues, environment::Value, interpreter::InterpreterResult, numeric};
 
 mod datetime;
+pub mod duration;
 mod global;
+mod iterator;
 mod list;
+mod map;
 mod number;
+mod range;
+mod regexp;
 mod string;
+mod worker;
 
 pub use datetime::DateTimeObject;
+pub use duration::DurationObject;
 pub use global::GlobalObject;
+pub use iterator::IteratorObject;
 pub use list::ListObject;
+pub use map::MapObject;
 pub use number::NumberObject;
+pub use range::RangeObject;
+pub use regexp::RegexObject;
 pub use string::StringObject;
+pub use worker::WorkerObject;
 
-pub fn arity(name: &str, arity: usize, arguments: &ArgumentValues, multiples_entry: bool) -> () {
+pub fn arity(name: &str, arity: usize, arguments: &ArgumentValues, multiples_entry: bool) -> Result<(), InterpreterResult> {
 	if multiples_entry {
 		if arguments.len() < arity {
-			panic!("{} expects {} arguments, but {} were given", name, arity, arguments.len());
+			return Err(InterpreterResult::Error(format!("{} expects {} arguments, but {} were given.", name, arity, arguments.len())));
 		}
 	} else {
 		if arguments.len() != arity {
-			panic!("{} expects exactly {} arguments, but {} were given", name, arity, arguments.len());
+			return Err(InterpreterResult::Error(format!("{} expects exactly {} arguments, but {} were given.", name, arity, arguments.len())));
 		}
 	}
+
+	Ok(())
+}
+
+// An argument that must be a number, a catchable error naming it when it is missing or isn't one.
+pub fn numeric_argument(name: &str, args: &ArgumentValues, argument: &str, index: usize) -> Result<Value, InterpreterResult> {
+	match args.get_from_name_or_index(argument.to_string(), index) {
+		Some(value) if numeric::is_numeric(&value) => Ok(value),
+		Some(value) => Err(InterpreterResult::Error(format!("{} expects a number for {}, got {}.", name, argument, value.typestring()))),
+		None => Err(InterpreterResult::Error(format!("{} expects a number for {}.", name, argument))),
+	}
+}
+
+pub fn number_argument(name: &str, args: &ArgumentValues, argument: &str, index: usize) -> Result<f64, InterpreterResult> {
+	numeric_argument(name, args, argument, index)?.expect_number(name)
 }
 
-pub fn parse_callback(callback: Value) -> Value {
-	match &callback {
-		Value::Function { .. } => callback,
-		Value::NativeFunction { .. } => callback,
-		Value::NativeMethod { .. } => callback,
-		_ => panic!("Callback is not a function callable"),
+// Like `number_argument`, `None` when the argument is left out.
+pub fn optional_number_argument(name: &str, args: &ArgumentValues, argument: &str, index: usize) -> Result<Option<f64>, InterpreterResult> {
+	match args.get_from_name_or_index(argument.to_string(), index) {
+		Some(_) => number_argument(name, args, argument, index).map(Some),
+		None => Ok(None),
+	}
+}
+
+pub fn parse_callback(callback: Value) -> Result<Value, InterpreterResult> {
+	match callback.unwrap_constant() {
+		callback @ (Value::Function { .. } | Value::NativeFunction { .. } | Value::NativeMethod { .. }) => Ok(callback),
+		callback => Err(InterpreterResult::TypeMismatch("callback".to_string(), "function".to_string(), callback.typestring())),
+	}
+}
+
+// Position of `index` in a sequence of `len` items, negative indexes count from the end. `None` when out of range.
+pub fn position(index: f64, len: usize) -> Option<usize> {
+	let index = if index < 0.0 { len as f64 + index.trunc() } else { index.trunc() };
+
+	if index >= 0.0 && index < len as f64 {
+		Some(index as usize)
+	} else {
+		None
+	}
+}
+
+// Like `position`, but clamped to `0..=len` for slice bounds.
+pub fn bound(index: f64, len: usize) -> usize {
+	let index = if index < 0.0 { len as f64 + index.trunc() } else { index.trunc() };
+
+	index.max(0.0).min(len as f64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::caught;
+
+	#[test]
+	fn it_raises_catchable_errors_for_bad_arguments() {
+		assert_eq!(caught("create l = [1, 2]\nl.map!(3)"), "TypeError: callback expects function, got number.");
+		assert_eq!(caught("create s = \"abc\"\ns.upper!(1)"), "String.upper! expects exactly 0 arguments, but 1 were given.");
 	}
 }
@@ -1,47 +1,63 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+
 use crate::{
 	ast::ArgumentValues,
 	environment::{NativeMethodCallback, Value},
 	interpreter::{Interpreter, InterpreterResult},
+	numeric,
 };
 
 pub struct NumberObject;
 
 impl NumberObject {
-	pub fn get(name: String) -> NativeMethodCallback {
-		match name.as_str() {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
 			"integer?" => number_is_integer,
 			"float?" => number_is_float,
 			"round!" => round_number,
 			"even?" => number_is_even,
 			"odd?" => number_is_odd,
-			_ => panic!("Undefined method: `{}` for Number object", name),
-		}
+			"gcd!" => number_gcd,
+			"lcm!" => number_lcm,
+			"isPrime?" => number_is_prime,
+			"factorial!" => number_factorial,
+			"abs!" => number_abs,
+			"floor!" => number_floor,
+			"ceil!" => number_ceil,
+			"toFixed!" => number_to_fixed,
+			"toString!" => number_to_string,
+			_ => return None,
+		})
 	}
 }
 
 fn number_is_integer(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("Number.integer?", 0, &args, false);
-
-	let number = context.to_number();
+	super::arity("Number.integer?", 0, &args, false)?;
 
-	Ok(Value::Bool(number == number.trunc()))
+	Ok(Value::Bool(numeric::to_integer(&context).is_some()))
 }
 
 fn number_is_float(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("Number.float?", 0, &args, false);
+	super::arity("Number.float?", 0, &args, false)?;
 
-	let number = context.to_number();
-
-	Ok(Value::Bool(number != number.trunc()))
+	Ok(Value::Bool(numeric::to_integer(&context).is_none()))
 }
 
+// Rounds half away from zero, like `Math.round!`, to `precision` decimals (none by default).
 fn round_number(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	let number = context.to_number();
+	super::arity("Number.round!", 0, &args, true)?;
+
+	if let Value::BigInt(..) = context.clone().unwrap_constant() {
+		return Ok(context);
+	}
+
+	let number = context.expect_number("Number.round!")?;
 
-	let precision = if args.is_empty() { 0 } else { args.get_from_name_or_index("precision".to_string(), 0).unwrap().clone().to_number() as usize };
+	let precision = if args.is_empty() { 0 } else { super::number_argument("Number.round!", &args, "precision", 0)?.max(0.0) as usize };
 
 	if precision == 0 {
-		return Ok(Value::Number(number.trunc()));
+		return Ok(Value::Number(number.round()));
 	}
 
 	let rounded: f64 = format!("{:.1$}", number, precision).parse().unwrap();
@@ -50,16 +66,168 @@ fn round_number(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Re
 }
 
 fn number_is_even(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("Number.even?", 0, &args, false);
+	super::arity("Number.even?", 0, &args, false)?;
 
-	let number = context.to_number();
-	Ok(Value::Bool(number % 2.0 == 0.0))
+	Ok(Value::Bool(numeric::to_integer(&context).is_some_and(|n| n.is_even())))
 }
 
 fn number_is_odd(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("Number.odd?", 0, &args, false);
+	super::arity("Number.odd?", 0, &args, false)?;
+
+	Ok(Value::Bool(numeric::to_integer(&context).is_some_and(|n| n.is_odd())))
+}
+
+fn is_plain_number(value: Value) -> bool {
+	matches!(value.unwrap_constant(), Value::Number(..))
+}
+
+fn integer_operands(name: &str, context: &Value, args: &ArgumentValues) -> Result<(BigInt, BigInt), InterpreterResult> {
+	let other = args.get_from_name_or_index("other".to_string(), 0).unwrap();
+
+	match (numeric::to_integer(context), numeric::to_integer(&other)) {
+		(Some(l), Some(r)) => Ok((l, r)),
+		_ => Err(InterpreterResult::Error(format!("{} expects integers, but got {:?} and {:?}", name, context, other))),
+	}
+}
+
+fn number_gcd(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.gcd!", 1, &args, false)?;
+
+	let (l, r) = integer_operands("Number.gcd!", &context, &args)?;
+	let prefer_number = is_plain_number(context) && is_plain_number(args.get_from_index(0).unwrap());
+
+	Ok(numeric::integer_value(l.gcd(&r), prefer_number))
+}
+
+fn number_lcm(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.lcm!", 1, &args, false)?;
+
+	let (l, r) = integer_operands("Number.lcm!", &context, &args)?;
+	let prefer_number = is_plain_number(context) && is_plain_number(args.get_from_index(0).unwrap());
+
+	Ok(numeric::integer_value(l.lcm(&r), prefer_number))
+}
+
+fn number_is_prime(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.isPrime?", 0, &args, false)?;
+
+	Ok(Value::Bool(numeric::to_integer(&context).is_some_and(|n| numeric::is_prime(&n))))
+}
+
+fn number_factorial(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.factorial!", 0, &args, false)?;
+
+	let prefer_number = is_plain_number(context.clone());
+
+	match numeric::to_integer(&context).and_then(|n| numeric::factorial(&n)) {
+		Some(result) => Ok(numeric::integer_value(result, prefer_number)),
+		None => Err(InterpreterResult::Error(format!("Number.factorial! expects a non-negative integer, but got {:?}", context))),
+	}
+}
+
+fn number_abs(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.abs!", 0, &args, false)?;
+
+	Ok(numeric::abs(context))
+}
+
+fn number_floor(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.floor!", 0, &args, false)?;
+
+	Ok(numeric::floor(context))
+}
+
+fn number_ceil(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.ceil!", 0, &args, false)?;
+
+	Ok(numeric::ceil(context))
+}
+
+// String with exactly `digits` decimals.
+fn number_to_fixed(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.toFixed!", 1, &args, false)?;
+
+	let digits = super::number_argument("Number.toFixed!", &args, "digits", 0)?;
+
+	if !(0.0..=100.0).contains(&digits) {
+		return Err(InterpreterResult::Error(format!("Number.toFixed! expects between 0 and 100 digits, got {}.", numeric::format(digits))));
+	}
+
+	let digits = digits as usize;
+
+	Ok(Value::String(match context.unwrap_constant() {
+		Value::BigInt(n) if digits == 0 => n.to_string(),
+		Value::BigInt(n) => format!("{}.{}", n, "0".repeat(digits)),
+		number => match number.expect_number("Number.toFixed!")? {
+			n if n.is_finite() => format!("{:.1$}", n, digits),
+			n => numeric::format(n),
+		},
+	}))
+}
+
+// Integers can be written in any radix from 2 to 36, other numbers only in base 10.
+fn number_to_string(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Number.toString!", 0, &args, true)?;
+
+	let radix = if args.is_empty() { 10.0 } else { super::number_argument("Number.toString!", &args, "radix", 0)? };
 
-	let number = context.to_number();
+	if !(2.0..=36.0).contains(&radix) || radix.fract() != 0.0 {
+		return Err(InterpreterResult::Error(format!("Number.toString! expects a radix between 2 and 36, got {}.", numeric::format(radix))));
+	}
+
+	if radix == 10.0 {
+		return Ok(Value::String(format!("{:?}", context.unwrap_constant())));
+	}
+
+	match numeric::to_integer(&context) {
+		Some(n) => Ok(Value::String(n.to_str_radix(radix as u32))),
+		None => Err(InterpreterResult::Error(format!("Number.toString! only converts integers to radix {}, got {:?}.", radix, context))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::{caught, evaluate};
+
+	fn number(source: &str) -> String {
+		evaluate(source).unwrap().to_string()
+	}
+
+	#[test]
+	fn it_rounds_like_math_round() {
+		assert_eq!(number("2.7.round!()"), "3");
+		assert_eq!(number("Math.round!(2.7)"), "3");
+		assert_eq!(number("2.5.round!()"), "3");
+		assert_eq!(number("Math.round!(2.5)"), "3");
+		assert_eq!(number("create n = -2.5\nn.round!()"), "-3");
+		assert_eq!(number("Math.round!(-2.5)"), "-3");
+		assert_eq!(number("3.14159.round!(2)"), "3.14");
+		assert_eq!(number("create n = 2 ** 100\nn.round!()"), "1267650600228229401496703205376");
+	}
+
+	#[test]
+	fn it_formats_numbers_with_digits_and_radixes() {
+		assert_eq!(number("3.14159.toFixed!(2)"), "3.14");
+		assert_eq!(number("2.toFixed!(3)"), "2.000");
+		assert_eq!(number("255.toString!(16)"), "ff");
+		assert_eq!(number("5.toString!(2)"), "101");
+		assert_eq!(number("create n = -3.5\ncreate result = [n.abs!(), n.floor!(), n.ceil!()]\nresult"), "[3.5, -4, -3]");
+		assert_eq!(number("create result = [12.gcd!(18), 4.lcm!(6), 97.isPrime?(), 5.factorial!()]\nresult"), "[6, 12, true, 120]");
+	}
+
+	#[test]
+	fn it_rejects_arguments_that_are_not_numbers() {
+		assert_eq!(caught("create n = 2\nn.toFixed!([1])"), "Number.toFixed! expects a number for digits, got list.");
+		assert_eq!(caught("create n = 2\nn.toString!([1])"), "Number.toString! expects a number for radix, got list.");
+		assert_eq!(caught("create n = 2\nn.round!([1])"), "Number.round! expects a number for precision, got list.");
+	}
 
-	Ok(Value::Bool(number % 2.0 == 1.0))
+	#[test]
+	fn it_computes_math_functions() {
+		assert_eq!(number("create result = [Math.sqrt!(16), Math.abs!(-2), Math.floor!(2.7), Math.ceil!(2.1), Math.pow!(2, 10)]\nresult"), "[4, 2, 2, 3, 1024]");
+		assert_eq!(number("create result = [Math.min!(3, 1, 2), Math.max!([3, 1, 2]), Math.clamp!(15, 0, 10), Math.log!(8, 2)]\nresult"), "[1, 3, 10, 3]");
+		assert_eq!(number("Math.floor!(Math.PI * 100)"), "314");
+		assert_eq!(number("Math.sqrt!(-1)"), "NaN");
+		assert_eq!(caught("Math.sqrt!(\"a\")"), "sqrt! expects a number for x, got string.");
+	}
 }
@@ -0,0 +1,47 @@
+use crate::{
+	ast::ArgumentValues,
+	environment::{NativeMethodCallback, Value},
+	interpreter::{Interpreter, InterpreterResult},
+	numeric,
+	range::Range,
+};
+
+pub struct RangeObject;
+
+impl RangeObject {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
+			"toList!" => range_to_list,
+			"reverse!" => range_reverse,
+			"contains?" => range_contains,
+			_ => return None,
+		})
+	}
+}
+
+fn to_range(context: Value) -> Range {
+	match context.unwrap_constant() {
+		Value::Range(range) => range,
+		_ => unreachable!(),
+	}
+}
+
+fn range_to_list(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Range.toList!()", 0, &args, false)?;
+
+	Ok(to_range(context).to_list())
+}
+
+fn range_reverse(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Range.reverse!()", 0, &args, false)?;
+
+	Ok(Value::Range(to_range(context).reverse()))
+}
+
+fn range_contains(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Range.contains?(value)", 1, &args, false)?;
+
+	let value = args.get_from_name_or_index("value".to_string(), 0).unwrap();
+
+	Ok(Value::Bool(numeric::to_float(&value).is_some_and(|n| to_range(context).contains(n))))
+}
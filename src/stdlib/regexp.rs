@@ -0,0 +1,116 @@
+use hashbrown::HashMap;
+use regex::{Captures, Regex};
+use std::cell::RefCell;
+
+use crate::{
+	ast::ArgumentValues,
+	environment::{NativeMethodCallback, Value},
+	interpreter::{Interpreter, InterpreterResult},
+	map::Map,
+};
+
+// Compiled patterns by source, so a pattern used inside a loop is only compiled once.
+thread_local! {
+	static CACHE: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
+}
+
+const CACHE_SIZE: usize = 256;
+
+pub struct RegexObject;
+
+impl RegexObject {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
+			"matches?" => regex_matches,
+			"find!" => regex_find,
+			"findAll!" => regex_find_all,
+			"pattern?" => regex_pattern,
+			_ => return None,
+		})
+	}
+}
+
+pub fn compile(pattern: &str) -> Result<Regex, InterpreterResult> {
+	if let Some(regex) = CACHE.with(|cache| cache.borrow().get(pattern).cloned()) {
+		return Ok(regex);
+	}
+
+	let regex = Regex::new(pattern).map_err(|e| InterpreterResult::Error(format!("Invalid regex {}: {}", pattern, e)))?;
+
+	CACHE.with(|cache| {
+		let mut cache = cache.borrow_mut();
+
+		if cache.len() >= CACHE_SIZE {
+			cache.clear();
+		}
+
+		cache.insert(pattern.to_string(), regex.clone());
+	});
+
+	Ok(regex)
+}
+
+// A `Regex` value as is, a string is compiled as a pattern. `Regex.escape!` gives a pattern matching a string literally.
+pub fn pattern(value: Value) -> Result<Regex, InterpreterResult> {
+	match value.unwrap_constant() {
+		Value::Regex(regex) => Ok(regex),
+		Value::String(pattern) => compile(&pattern),
+		value => Err(InterpreterResult::TypeMismatch("pattern".to_string(), "regex | string".to_string(), value.typestring())),
+	}
+}
+
+// A match as a map: the matched `text`, its `start` and `end` in characters, the positional `groups` (null when a
+// group didn't take part) and the `named` groups.
+pub fn found(text: &str, regex: &Regex, captures: &Captures) -> Value {
+	let whole = captures.get(0).unwrap();
+	let position = |byte: usize| Value::Number(text[..byte].chars().count() as f64);
+	let group = |group: Option<regex::Match>| group.map_or(Value::Null, |group| Value::String(group.as_str().to_string()));
+
+	let groups = captures.iter().skip(1).map(group).collect::<Vec<Value>>();
+	let named = regex.capture_names().flatten().map(|name| (name.to_string(), group(captures.name(name)))).collect::<Map>();
+
+	let mut map = Map::new();
+	map.set("text".to_string(), Value::String(whole.as_str().to_string()));
+	map.set("start".to_string(), position(whole.start()));
+	map.set("end".to_string(), position(whole.end()));
+	map.set("groups".to_string(), Value::list(groups));
+	map.set("named".to_string(), Value::map(named));
+
+	Value::map(map)
+}
+
+pub fn find(regex: &Regex, text: &str) -> Value {
+	regex.captures(text).map_or(Value::Null, |captures| found(text, regex, &captures))
+}
+
+pub fn find_all(regex: &Regex, text: &str) -> Value {
+	Value::list(regex.captures_iter(text).map(|captures| found(text, regex, &captures)).collect())
+}
+
+fn text(args: &ArgumentValues) -> String {
+	args.get_from_name_or_index("text".to_string(), 0).unwrap().to_string()
+}
+
+fn regex_matches(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Regex.matches?", 1, &args, false)?;
+
+	Ok(Value::Bool(pattern(context)?.is_match(&text(&args))))
+}
+
+fn regex_find(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Regex.find!", 1, &args, false)?;
+
+	Ok(find(&pattern(context)?, &text(&args)))
+}
+
+fn regex_find_all(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Regex.findAll!", 1, &args, false)?;
+
+	Ok(find_all(&pattern(context)?, &text(&args)))
+}
+
+fn regex_pattern(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Regex.pattern?", 0, &args, false)?;
+
+	Ok(Value::String(pattern(context)?.as_str().to_string()))
+}
@@ -4,11 +4,15 @@ use crate::{
 	interpreter::{Interpreter, InterpreterResult},
 };
 
+use super::regexp;
+
 pub struct StringObject;
 
 impl StringObject {
-	pub fn get(name: String) -> NativeMethodCallback {
-		match name.as_str() {
+	// Pattern methods (`matches?`, `find!`, `findAll!`, `replace!`, `replaceAll!`, `split!`) take a string or a `Regex`,
+	// a string is compiled as a regular expression too, so `"."` matches any character.
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
 			"contains?" => string_contains,
 			"startsWith?" => string_starts_with,
 			"endsWith?" => string_ends_with,
@@ -17,13 +21,33 @@ impl StringObject {
 			"tap" => string_tap,
 			"upper!" => string_to_upper,
 			"lower!" => string_to_lower,
-			_ => panic!("Undefined method: `{}` for String object", name),
-		}
+			"matches?" => string_matches,
+			"find!" => string_find,
+			"findAll!" => string_find_all,
+			"replace!" => string_replace,
+			"replaceAll!" => string_replace_all,
+			"split!" => string_split,
+			"len?" => string_len,
+			"trim!" => string_trim,
+			"trimStart!" => string_trim_start,
+			"trimEnd!" => string_trim_end,
+			"slice!" => string_slice,
+			"indexOf?" => string_index_of,
+			"repeat!" => string_repeat,
+			"padStart!" => string_pad_start,
+			"padEnd!" => string_pad_end,
+			"chars!" => string_chars,
+			"bytes!" => string_bytes,
+			"lines!" => string_lines,
+			"toNumber!" => string_to_number,
+			"format!" => string_format,
+			_ => return None,
+		})
 	}
 }
 
 fn string_contains(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.contains?", 1, &args, false);
+	super::arity("String.contains?", 1, &args, false)?;
 
 	let string = context.to_string();
 
@@ -37,7 +61,7 @@ fn string_contains(_: &mut Interpreter, context: Value, args: ArgumentValues) ->
 }
 
 fn string_starts_with(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.startsWith?", 1, &args, false);
+	super::arity("String.startsWith?", 1, &args, false)?;
 
 	let string = context.to_string();
 
@@ -51,7 +75,7 @@ fn string_starts_with(_: &mut Interpreter, context: Value, args: ArgumentValues)
 }
 
 fn string_ends_with(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.endsWith?", 1, &args, false);
+	super::arity("String.endsWith?", 1, &args, false)?;
 
 	let string = context.to_string();
 
@@ -65,7 +89,7 @@ fn string_ends_with(_: &mut Interpreter, context: Value, args: ArgumentValues) -
 }
 
 fn string_finish(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.finish!", 1, &args, false);
+	super::arity("String.finish!", 1, &args, false)?;
 
 	let mut string = context.to_string();
 	let append = args.get_from_name_or_index("content".to_string(), 0).unwrap().to_string();
@@ -78,7 +102,7 @@ fn string_finish(_: &mut Interpreter, context: Value, args: ArgumentValues) -> R
 }
 
 fn string_append(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.append!", 1, &args, false);
+	super::arity("String.append!", 1, &args, false)?;
 
 	let mut string = context.to_string();
 	let append = args.get_from_name_or_index("content".to_string(), 0).unwrap().to_string();
@@ -89,13 +113,13 @@ fn string_append(_: &mut Interpreter, context: Value, args: ArgumentValues) -> R
 }
 
 fn string_tap(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.tap", 1, &args, false);
+	super::arity("String.tap", 1, &args, false)?;
 
 	let string = context.clone();
 
 	let mut callback = args.get_from_name_or_index("callback".to_string(), 0).unwrap();
 
-	callback = super::parse_callback(callback);
+	callback = super::parse_callback(callback)?;
 
 	let mut arguments_values = ArgumentValues::new();
 	arguments_values.push(ArgumentValued::new(None, string));
@@ -106,13 +130,303 @@ fn string_tap(interpreter: &mut Interpreter, context: Value, args: ArgumentValue
 }
 
 fn string_to_upper(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.upper!", 0, &args, false);
+	super::arity("String.upper!", 0, &args, false)?;
 
 	Ok(Value::String(context.to_string().to_uppercase()))
 }
 
 fn string_to_lower(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
-	super::arity("String.lower!", 0, &args, false);
+	super::arity("String.lower!", 0, &args, false)?;
 
 	Ok(Value::String(context.to_string().to_lowercase()))
 }
+
+fn string_matches(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.matches?", 1, &args, false)?;
+
+	let regex = regexp::pattern(args.get_from_name_or_index("pattern".to_string(), 0).unwrap())?;
+
+	Ok(Value::Bool(regex.is_match(&context.to_string())))
+}
+
+// First match of the pattern as a map, null when there is none.
+fn string_find(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.find!", 1, &args, false)?;
+
+	let regex = regexp::pattern(args.get_from_name_or_index("pattern".to_string(), 0).unwrap())?;
+
+	Ok(regexp::find(&regex, &context.to_string()))
+}
+
+fn string_find_all(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.findAll!", 1, &args, false)?;
+
+	let regex = regexp::pattern(args.get_from_name_or_index("pattern".to_string(), 0).unwrap())?;
+
+	Ok(regexp::find_all(&regex, &context.to_string()))
+}
+
+// The replacement can refer to the groups of the pattern with `$1` or `${name}`.
+fn replace(context: Value, args: ArgumentValues, limit: usize) -> Result<Value, InterpreterResult> {
+	let regex = regexp::pattern(args.get_from_name_or_index("pattern".to_string(), 0).unwrap())?;
+	let replacement = args.get_from_name_or_index("replacement".to_string(), 1).unwrap().to_string();
+
+	Ok(Value::String(regex.replacen(&context.to_string(), limit, replacement.as_str()).to_string()))
+}
+
+fn string_replace(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.replace!", 2, &args, false)?;
+
+	replace(context, args, 1)
+}
+
+fn string_replace_all(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.replaceAll!", 2, &args, false)?;
+
+	replace(context, args, 0)
+}
+
+fn string_split(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.split!", 1, &args, false)?;
+
+	let string = context.to_string();
+	let parts = match args.get_from_name_or_index("separator".to_string(), 0).unwrap().unwrap_constant() {
+		// An empty separator splits between every character.
+		Value::String(separator) if separator.is_empty() => string.chars().map(String::from).collect(),
+		separator => regexp::pattern(separator)?.split(&string).map(str::to_string).collect::<Vec<String>>(),
+	};
+
+	Ok(Value::list(parts.into_iter().map(Value::String).collect()))
+}
+
+// Lengths, positions and padding count characters, not bytes.
+fn string_len(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.len?", 0, &args, false)?;
+
+	Ok(Value::Number(context.to_string().chars().count() as f64))
+}
+
+fn string_trim(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.trim!", 0, &args, false)?;
+
+	Ok(Value::String(context.to_string().trim().to_string()))
+}
+
+fn string_trim_start(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.trimStart!", 0, &args, false)?;
+
+	Ok(Value::String(context.to_string().trim_start().to_string()))
+}
+
+fn string_trim_end(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.trimEnd!", 0, &args, false)?;
+
+	Ok(Value::String(context.to_string().trim_end().to_string()))
+}
+
+// Characters from `start` up to, not including, `end` (the end of the string by default). Negative bounds count from
+// the end and bounds past either end are clamped.
+fn string_slice(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.slice!", 1, &args, true)?;
+
+	let chars = context.to_string().chars().collect::<Vec<char>>();
+	let start = super::bound(super::number_argument("String.slice!", &args, "start", 0)?, chars.len());
+	let end = super::optional_number_argument("String.slice!", &args, "end", 1)?.map_or(chars.len(), |end| super::bound(end, chars.len()));
+
+	Ok(Value::String(chars[start..end.max(start)].iter().collect()))
+}
+
+// Character position of the first occurrence of `search`, -1 when it isn't found.
+fn string_index_of(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.indexOf?", 1, &args, false)?;
+
+	let string = context.to_string();
+	let search = args.get_from_name_or_index("search".to_string(), 0).unwrap().to_string();
+
+	Ok(Value::Number(string.find(&search).map_or(-1.0, |byte| string[..byte].chars().count() as f64)))
+}
+
+fn string_repeat(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.repeat!", 1, &args, false)?;
+
+	let count = super::number_argument("String.repeat!", &args, "count", 0)?;
+
+	if count < 0.0 || !count.is_finite() {
+		return Err(InterpreterResult::Error(format!("String.repeat! expects a count of zero or more, got {}.", count)));
+	}
+
+	Ok(Value::String(context.to_string().repeat(count as usize)))
+}
+
+fn padding(name: &str, context: Value, args: &ArgumentValues) -> Result<(String, String), InterpreterResult> {
+	let string = context.to_string();
+	let length = super::number_argument(name, args, "length", 0)?.max(0.0) as usize;
+	let fill = args.get_from_name_or_index("fill".to_string(), 1).map_or(" ".to_string(), |fill| fill.to_string());
+	let missing = length.saturating_sub(string.chars().count());
+
+	Ok((string, fill.chars().cycle().take(if fill.is_empty() { 0 } else { missing }).collect()))
+}
+
+// Pads up to `length` characters with `fill` (a space by default), longer strings are left as they are.
+fn string_pad_start(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.padStart!", 1, &args, true)?;
+
+	let (string, padding) = padding("String.padStart!", context, &args)?;
+
+	Ok(Value::String(padding + &string))
+}
+
+fn string_pad_end(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.padEnd!", 1, &args, true)?;
+
+	let (string, padding) = padding("String.padEnd!", context, &args)?;
+
+	Ok(Value::String(string + &padding))
+}
+
+fn string_chars(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.chars!", 0, &args, false)?;
+
+	Ok(Value::list(context.to_string().chars().map(|c| Value::String(c.to_string())).collect()))
+}
+
+// UTF-8 bytes as numbers.
+fn string_bytes(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.bytes!", 0, &args, false)?;
+
+	Ok(Value::list(context.to_string().bytes().map(|byte| Value::Number(byte as f64)).collect()))
+}
+
+fn string_lines(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.lines!", 0, &args, false)?;
+
+	Ok(Value::list(context.to_string().lines().map(|line| Value::String(line.to_string())).collect()))
+}
+
+fn string_to_number(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("String.toNumber!", 0, &args, false)?;
+
+	let string = context.to_string();
+
+	match string.trim().parse::<f64>() {
+		Ok(number) => Ok(Value::Number(number)),
+		Err(_) => Err(InterpreterResult::Error(format!("Cannot convert \"{}\" to a number.", string))),
+	}
+}
+
+// `{}` takes the next positional argument, `{1}` a positional argument by index and `{name}` a named argument or,
+// when the only argument is a map, one of its keys. `{{` and `}}` are literal braces.
+fn string_format(interpreter: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	let template = context.to_string();
+	let positional = args.get_raw().into_iter().filter(|argument| argument.get_name().is_none()).map(|argument| argument.get_value()).collect::<Vec<Value>>();
+	let named = |name: &str| match (args.get_from_name(name.to_string()), positional.as_slice()) {
+		(Some(value), _) => Some(value),
+		(None, [Value::Map(map)]) => map.borrow().get(name).cloned(),
+		_ => None,
+	};
+
+	let mut output = String::new();
+	let mut next = 0;
+	let mut chars = template.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'{' if chars.peek() == Some(&'{') => {
+				chars.next();
+				output.push('{');
+			}
+			'}' if chars.peek() == Some(&'}') => {
+				chars.next();
+				output.push('}');
+			}
+			'{' => {
+				let mut placeholder = String::new();
+
+				loop {
+					match chars.next() {
+						Some('}') => break,
+						Some(c) => placeholder.push(c),
+						None => return Err(InterpreterResult::Error(format!("Unclosed placeholder {{{} in format string.", placeholder))),
+					}
+				}
+
+				let placeholder = placeholder.trim();
+				let value = if placeholder.is_empty() {
+					next += 1;
+					positional.get(next - 1).cloned()
+				} else if let Ok(index) = placeholder.parse::<usize>() {
+					positional.get(index).cloned()
+				} else {
+					named(placeholder)
+				};
+
+				match value {
+					Some(value) => output.push_str(&interpreter.stringify(value)?),
+					None => return Err(InterpreterResult::Error(format!("No argument for placeholder {{{}}} in format string.", placeholder))),
+				}
+			}
+			'}' => return Err(InterpreterResult::Error("Unmatched } in format string, use }} for a literal brace.".to_string())),
+			c => output.push(c),
+		}
+	}
+
+	Ok(Value::String(output))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::{caught, evaluate};
+
+	fn string(source: &str) -> String {
+		evaluate(source).unwrap().to_string()
+	}
+
+	#[test]
+	fn it_compiles_string_patterns_like_regexes() {
+		assert_eq!(evaluate(r#""abc".matches?(".")"#).unwrap().to_string(), "true");
+		assert_eq!(evaluate(r#""xa1".find!("[0-9]")["start"]"#).unwrap().to_string(), "2");
+		assert_eq!(evaluate(r#""a1b22".findAll!("[0-9]+").len?()"#).unwrap().to_string(), "2");
+		assert_eq!(evaluate(r#""2024-01-02".replace!("(\d+)-(\d+)-(\d+)", "$3/$2/$1")"#).unwrap().to_string(), "02/01/2024");
+		assert_eq!(evaluate(r#""abc".replaceAll!(".", "-")"#).unwrap().to_string(), "---");
+		assert_eq!(evaluate(r#""one, two;three".split!("[,;] ?")"#).unwrap().to_string(), "[one, two, three]");
+		assert_eq!(evaluate(r#""abc".split!("")"#).unwrap().to_string(), "[a, b, c]");
+		// Escaping gives a pattern that matches the string literally.
+		assert_eq!(evaluate(r#""a.b.c".split!(Regex.escape!("."))"#).unwrap().to_string(), "[a, b, c]");
+		assert_eq!(evaluate(r#""abc".matches?(Regex.new!("^A", "i"))"#).unwrap().to_string(), "true");
+	}
+
+	#[test]
+	fn it_counts_and_indexes_strings_by_character() {
+		assert_eq!(string(r#""héllo".len?()"#), "5");
+		assert_eq!(string(r#""héllo wörld".slice!(1, 4)"#), "éll");
+		assert_eq!(string(r#""héllo wörld".slice!(-5)"#), "wörld");
+		assert_eq!(string(r#""héllo wörld".indexOf?("w")"#), "6");
+		assert_eq!(string(r#""héllo".indexOf?("z")"#), "-1");
+		assert_eq!(string(r#""héllo"[1]"#), "é");
+		assert_eq!(string(r#""héllo"[-1]"#), "o");
+		assert_eq!(string(r#""héllo".chars!()"#), "[h, é, l, l, o]");
+		assert_eq!(string(r#""hé".bytes!()"#), "[104, 195, 169]");
+		assert_eq!(caught(r#""abc"[10]"#), "Undefined index: 10.");
+	}
+
+	#[test]
+	fn it_trims_pads_and_repeats_strings() {
+		assert_eq!(string(r#""  hi  ".trim!() + "|""#), "hi|");
+		assert_eq!(string(r#""  hi  ".trimStart!() + "|""#), "hi  |");
+		assert_eq!(string(r#""  hi  ".trimEnd!() + "|""#), "  hi|");
+		assert_eq!(string(r#""7".padStart!(3, "0")"#), "007");
+		assert_eq!(string(r#""7".padEnd!(3) + "|""#), "7  |");
+		assert_eq!(string(r#""ab".repeat!(3)"#), "ababab");
+		assert_eq!(string("\"a\nb\".lines!()"), "[a, b]");
+		assert_eq!(caught(r#""ab".repeat!(-1)"#), "String.repeat! expects a count of zero or more, got -1.");
+	}
+
+	#[test]
+	fn it_converts_and_formats_strings() {
+		assert_eq!(string(r#""3.5".toNumber!() + 1"#), "4.5");
+		assert_eq!(caught(r#""abc".toNumber!()"#), "Cannot convert \"abc\" to a number.");
+		assert_eq!(string(r#""{} + {} = {}".format!(1, 2, 3)"#), "1 + 2 = 3");
+		assert_eq!(string(r#""{1} before {0}".format!("a", "b")"#), "b before a");
+		assert_eq!(string(r#""{name} is {age} {{ok}}".format!(name = "Ana", age = 3)"#), "Ana is 3 {ok}");
+	}
+}
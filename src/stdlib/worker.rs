@@ -0,0 +1,50 @@
+use crate::{
+	ast::ArgumentValues,
+	environment::{NativeMethodCallback, Value},
+	interpreter::{Interpreter, InterpreterResult},
+	worker::{self, WorkerHandle},
+};
+use std::rc::Rc;
+
+pub struct WorkerObject;
+
+impl WorkerObject {
+	pub fn get(name: &str) -> Option<NativeMethodCallback> {
+		Some(match name {
+			"send!" => worker_send,
+			"receive!" => worker_receive,
+			"join!" => worker_join,
+			_ => return None,
+		})
+	}
+}
+
+fn to_worker(context: Value) -> Rc<WorkerHandle> {
+	match context.unwrap_constant() {
+		Value::Worker(handle) => handle,
+		_ => unreachable!(),
+	}
+}
+
+// Sends a copy of the value to the worker, it reads it with `Worker.receive!()`.
+fn worker_send(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Worker.send!()", 1, &args, false)?;
+
+	let message = worker::export(&args.get_from_name_or_index("message".to_string(), 0).unwrap())?;
+
+	Ok(Value::Bool(to_worker(context).inbox.send(message).is_ok()))
+}
+
+// Waits for the next value the worker posted with `Worker.post!()`, null once the worker finished.
+fn worker_receive(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Worker.receive!()", 0, &args, false)?;
+
+	Ok(to_worker(context).outbox.recv().map_or(Value::Null, worker::import))
+}
+
+// Waits for the worker function to return and gives back a copy of its result, errors in the worker are raised here.
+fn worker_join(_: &mut Interpreter, context: Value, args: ArgumentValues) -> Result<Value, InterpreterResult> {
+	super::arity("Worker.join!()", 0, &args, false)?;
+
+	to_worker(context).join()
+}
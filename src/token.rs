@@ -1,4 +1,8 @@
 use logos::{Lexer, Logos};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+
+use crate::numeric;
 
 pub fn generate(input: &str) -> Vec<Token> {
 	Token::lexer(input).collect()
@@ -23,7 +27,15 @@ fn to_string(lex: &mut Lexer<Token>) -> Option<String> {
 }
 
 fn to_float(lex: &mut Lexer<Token>) -> Option<f64> {
-	Some(lex.slice().parse().ok()?)
+	lex.slice().parse().ok()
+}
+
+fn to_bigint(lex: &mut Lexer<Token>) -> Option<BigInt> {
+	lex.slice().trim_end_matches('n').parse().ok()
+}
+
+fn to_rational(lex: &mut Lexer<Token>) -> Option<BigRational> {
+	numeric::parse_decimal(lex.slice().trim_end_matches('r'))
 }
 
 #[derive(Debug, Clone, Logos, PartialEq)]
@@ -49,15 +61,39 @@ pub enum Token {
 	#[token("struct")]
 	Struct,
 
+	#[token("enum")]
+	Enum,
+
+	#[token("interface")]
+	Interface,
+
 	#[token("while")]
 	While,
 
 	#[token("loop")]
 	Loop,
 
+	#[token("try")]
+	Try,
+
+	#[token("catch")]
+	Catch,
+
 	#[token("return")]
 	Return,
 
+	#[token("yield")]
+	Yield,
+
+	#[token("async")]
+	Async,
+
+	#[token("await")]
+	Await,
+
+	#[token("spawn")]
+	Spawn,
+
 	#[token("break")]
 	Break,
 
@@ -73,11 +109,21 @@ pub enum Token {
 	#[token("not in")]
 	NotIn,
 
-	#[regex(r"[a-zA-Z_?!]+", to_string)]
+	#[token("step")]
+	Step,
+
+	#[token("match")]
+	Match,
+
+	#[regex(r"[a-zA-Z_?!][a-zA-Z0-9_?!]*", to_string)]
 	Identifier(String),
 
 	#[regex(r"([0-9]+[.])?[0-9]+", to_float)]
 	Number(f64),
+	#[regex(r"[0-9]+n", to_bigint)]
+	BigInt(BigInt),
+	#[regex(r"([0-9]+[.])?[0-9]+r", to_rational)]
+	Rational(BigRational),
 	#[regex(r##""(?:[^"\\]|\\.)*""##, to_string)]
 	String(String),
 
@@ -123,6 +169,12 @@ pub enum Token {
 
 	#[token("=")]
 	Assign,
+	#[token("=>")]
+	FatArrow,
+	#[token("->")]
+	Arrow,
+	#[token("|")]
+	Pipe,
 	#[token("==")]
 	Equals,
 	#[token("!=")]
@@ -156,6 +208,10 @@ pub enum Token {
 	Bang,
 	#[token(".")]
 	Dot,
+	#[token("..")]
+	DotDot,
+	#[token("..=")]
+	DotDotEquals,
 
 	Eof,
 
@@ -165,9 +221,9 @@ pub enum Token {
 	Error,
 }
 
-impl Into<String> for Token {
-	fn into(self) -> String {
-		match self {
+impl From<Token> for String {
+	fn from(token: Token) -> String {
+		match token {
 			Token::Identifier(s) => s,
 			Token::String(s) => s,
 			_ => unreachable!(),
@@ -180,7 +236,7 @@ mod tests {
 	use super::*;
 	#[test]
 	fn it_can_skip_comments() {
-		let mut lexer = Token::lexer("-- foo");
+		let mut lexer = Token::lexer("# foo");
 		assert_eq!(lexer.next(), None);
 	}
 
@@ -220,6 +276,20 @@ mod tests {
 		assert_eq!(lexer.next(), Some(Token::Dot));
 	}
 
+	#[test]
+	fn it_can_recognise_ranges() {
+		let mut lexer = Token::lexer("0..10 1..=5 step 2");
+
+		assert_eq!(lexer.next(), Some(Token::Number(0.0)));
+		assert_eq!(lexer.next(), Some(Token::DotDot));
+		assert_eq!(lexer.next(), Some(Token::Number(10.0)));
+		assert_eq!(lexer.next(), Some(Token::Number(1.0)));
+		assert_eq!(lexer.next(), Some(Token::DotDotEquals));
+		assert_eq!(lexer.next(), Some(Token::Number(5.0)));
+		assert_eq!(lexer.next(), Some(Token::Step));
+		assert_eq!(lexer.next(), Some(Token::Number(2.0)));
+	}
+
 	#[test]
 	fn it_can_recognise_symbols_equal() {
 		let mut lexer = Token::lexer("+= -= *= /=");
@@ -240,6 +310,16 @@ mod tests {
 		assert_eq!(lexer.next(), Some(Token::Identifier("helloWorld".to_owned())));
 	}
 
+	#[test]
+	fn it_can_recognise_identifiers_with_digits() {
+		let mut lexer = Token::lexer("atan2! utf8 x 1");
+
+		assert_eq!(lexer.next(), Some(Token::Identifier("atan2!".to_owned())));
+		assert_eq!(lexer.next(), Some(Token::Identifier("utf8".to_owned())));
+		assert_eq!(lexer.next(), Some(Token::Identifier("x".to_owned())));
+		assert_eq!(lexer.next(), Some(Token::Number(1.0)));
+	}
+
 	#[test]
 	fn it_can_recognise_numbers() {
 		let mut lexer = Token::lexer("12345 6789.01");
@@ -248,6 +328,15 @@ mod tests {
 		assert_eq!(lexer.next(), Some(Token::Number(6789.01)));
 	}
 
+	#[test]
+	fn it_can_recognise_suffixed_numbers() {
+		let mut lexer = Token::lexer("100n 3r 0.1r");
+
+		assert_eq!(lexer.next(), Some(Token::BigInt(BigInt::from(100))));
+		assert_eq!(lexer.next(), Some(Token::Rational(BigRational::from_integer(BigInt::from(3)))));
+		assert_eq!(lexer.next(), Some(Token::Rational(BigRational::new(BigInt::from(1), BigInt::from(10)))));
+	}
+
 	#[test]
 	fn it_can_recognise_strings() {
 		let mut lexer = Token::lexer(r##""testing" "testing with \"" "testing \n""##);
@@ -0,0 +1,366 @@
+use chrono::{DateTime, Duration};
+use hashbrown::HashMap;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use regex::Regex;
+use std::{
+	cell::RefCell,
+	path::PathBuf,
+	rc::Rc,
+	sync::mpsc::{channel, Receiver, Sender},
+	thread::JoinHandle,
+};
+
+use crate::{
+	ast::{ArgumentValued, ArgumentValues, Block, Expression, Parameter, Type, Variant},
+	environment::{Environment, NativeFunctionCallback, NativeMethodCallback, Value},
+	interpreter::{self, Interpreter, InterpreterResult, STACK_SIZE},
+	range::Range,
+	zone::Zone,
+};
+
+// Deep copy of a value that can cross threads. Lists, maps and struct instances are copied, so a worker never shares
+// memory with the interpreter that sent them.
+#[derive(Clone)]
+pub enum Portable {
+	Number(f64),
+	BigInt(BigInt),
+	Rational(BigRational),
+	String(String),
+	Null,
+	Bool(bool),
+	DateTime(DateTime<Zone>),
+	Duration(Duration),
+	Regex(Regex),
+	Range(Range),
+	List(Vec<Portable>),
+	Map(Vec<(String, Portable)>),
+	Struct { name: String, fields: Vec<Parameter>, methods: Vec<(String, Portable)>, parent: Option<Box<Portable>>, interfaces: Vec<String> },
+	Interface { name: String, methods: Vec<(String, Vec<Parameter>)> },
+	Instance { fields: Vec<(String, Portable)>, definition: Box<Portable> },
+	Enum { name: String, variants: Vec<Variant>, methods: Vec<(String, Portable)> },
+	EnumVariant { definition: Box<Portable>, variant: String, values: Vec<Portable> },
+	Function { name: String, params: Vec<Parameter>, body: Block, return_type: Option<Type>, is_async: bool, environment: Option<Vec<(String, Portable)>>, context: Option<Box<Expression>> },
+	NativeFunction { name: String, callback: NativeFunctionCallback },
+	NativeMethod { name: String, callback: NativeMethodCallback, context: Expression },
+	Constant(Box<Portable>),
+}
+
+type Outcome = Result<Portable, String>;
+
+// Channels a worker interpreter uses for `Worker.receive!()` and `Worker.post!()`.
+#[derive(Debug)]
+pub struct WorkerChannels {
+	pub inbox: Receiver<Portable>,
+	pub outbox: Sender<Portable>,
+}
+
+// The parent's side of a running worker.
+pub struct WorkerHandle {
+	pub inbox: Sender<Portable>,
+	pub outbox: Receiver<Portable>,
+	thread: RefCell<Option<JoinHandle<Outcome>>>,
+	result: RefCell<Option<Outcome>>,
+}
+
+impl WorkerHandle {
+	// Waits for the worker function to return, the result is kept so `join!` can be called again.
+	pub fn join(&self) -> Result<Value, InterpreterResult> {
+		if let Some(thread) = self.thread.borrow_mut().take() {
+			let outcome = thread.join().unwrap_or_else(|_| Err("Worker thread panicked.".to_string()));
+			*self.result.borrow_mut() = Some(outcome);
+		}
+
+		match self.result.borrow().as_ref().unwrap() {
+			Ok(value) => Ok(import(value.clone())),
+			Err(message) => Err(InterpreterResult::Error(message.clone())),
+		}
+	}
+}
+
+pub fn export(value: &Value) -> Result<Portable, InterpreterResult> {
+	Exporter::default().export(value)
+}
+
+#[derive(Default)]
+struct Exporter {
+	// Lists, maps and instances being copied, meeting one of them again means the value contains itself.
+	visiting: Vec<*const ()>,
+}
+
+impl Exporter {
+	fn enter<T>(&mut self, rc: &Rc<T>) -> Result<(), InterpreterResult> {
+		let pointer = Rc::as_ptr(rc) as *const ();
+
+		if self.visiting.contains(&pointer) {
+			return Err(InterpreterResult::Error("Cannot send a value that contains itself to a worker.".to_string()));
+		}
+
+		self.visiting.push(pointer);
+
+		Ok(())
+	}
+
+	fn export(&mut self, value: &Value) -> Result<Portable, InterpreterResult> {
+		Ok(match value {
+			Value::Number(n) => Portable::Number(*n),
+			Value::BigInt(n) => Portable::BigInt(n.clone()),
+			Value::Rational(r) => Portable::Rational(r.clone()),
+			Value::String(s) => Portable::String(s.clone()),
+			Value::Null => Portable::Null,
+			Value::Bool(b) => Portable::Bool(*b),
+			Value::DateTime(dt) => Portable::DateTime(*dt),
+			Value::Duration(duration) => Portable::Duration(*duration),
+			Value::Regex(regex) => Portable::Regex(regex.clone()),
+			Value::Range(range) => Portable::Range(range.clone()),
+			Value::Constant(v) => Portable::Constant(Box::new(self.export(v)?)),
+			Value::List(items) => {
+				self.enter(items)?;
+				let items = items.borrow().iter().map(|item| self.export(item)).collect::<Result<Vec<Portable>, InterpreterResult>>()?;
+				self.visiting.pop();
+
+				Portable::List(items)
+			}
+			Value::Map(map) => {
+				self.enter(map)?;
+				let entries = map.borrow().iter().map(|(key, value)| Ok((key.clone(), self.export(value)?))).collect::<Result<Vec<(String, Portable)>, InterpreterResult>>()?;
+				self.visiting.pop();
+
+				Portable::Map(entries)
+			}
+			Value::StructInstance { environment, definition } => {
+				self.enter(environment)?;
+				let fields = self.entries(&environment.borrow(), false)?;
+				self.visiting.pop();
+
+				Portable::Instance { fields, definition: Box::new(self.export(definition)?) }
+			}
+			Value::Struct { name, fields, methods, parent, interfaces } => Portable::Struct {
+				name: name.clone(),
+				fields: fields.clone(),
+				methods: self.methods(methods)?,
+				parent: parent.as_ref().map(|parent| self.export(parent)).transpose()?.map(Box::new),
+				interfaces: interfaces.clone(),
+			},
+			Value::Interface { name, methods } => Portable::Interface { name: name.clone(), methods: methods.clone() },
+			Value::Enum { name, variants, methods } => Portable::Enum { name: name.clone(), variants: variants.clone(), methods: self.methods(methods)? },
+			Value::EnumVariant { definition, variant, values } => Portable::EnumVariant {
+				definition: Box::new(self.export(definition)?),
+				variant: variant.clone(),
+				values: values.iter().map(|value| self.export(value)).collect::<Result<Vec<Portable>, InterpreterResult>>()?,
+			},
+			Value::Function { name, params, body, return_type, is_async, environment, context } => Portable::Function {
+				name: name.clone(),
+				params: params.clone(),
+				body: body.clone(),
+				return_type: return_type.clone(),
+				is_async: *is_async,
+				environment: environment.as_ref().map(|environment| self.entries(environment, true)).transpose()?,
+				context: context.clone(),
+			},
+			Value::NativeFunction { name, callback } => Portable::NativeFunction { name: name.clone(), callback: *callback },
+			Value::NativeMethod { name, callback, context } => Portable::NativeMethod { name: name.clone(), callback: *callback, context: context.clone() },
+			Value::Iterator(..) | Value::Task(..) | Value::Worker(..) => {
+				return Err(InterpreterResult::Error(format!("Cannot send a value of type {} to a worker.", value.clone().typestring())))
+			}
+		})
+	}
+
+	fn methods(&mut self, methods: &Rc<RefCell<HashMap<String, Value>>>) -> Result<Vec<(String, Portable)>, InterpreterResult> {
+		methods.borrow().iter().map(|(name, method)| Ok((name.clone(), self.export(method)?))).collect()
+	}
+
+	// Captured variables a closure can't take along, like iterators, are left out instead of failing the whole send.
+	fn entries(&mut self, environment: &Environment, lenient: bool) -> Result<Vec<(String, Portable)>, InterpreterResult> {
+		let mut entries = Vec::new();
+
+		for (name, value) in environment.entries() {
+			match self.export(&value) {
+				Ok(value) => entries.push((name, value)),
+				Err(_) if lenient => (),
+				Err(e) => return Err(e),
+			}
+		}
+
+		Ok(entries)
+	}
+}
+
+pub fn import(portable: Portable) -> Value {
+	let environment = |entries: Vec<(String, Portable)>| {
+		let mut environment = Environment::new();
+
+		for (name, value) in entries {
+			environment.set(name, import(value));
+		}
+
+		environment
+	};
+	let methods = |methods: Vec<(String, Portable)>| Rc::new(RefCell::new(methods.into_iter().map(|(name, method)| (name, import(method))).collect()));
+
+	match portable {
+		Portable::Number(n) => Value::Number(n),
+		Portable::BigInt(n) => Value::BigInt(n),
+		Portable::Rational(r) => Value::Rational(r),
+		Portable::String(s) => Value::String(s),
+		Portable::Null => Value::Null,
+		Portable::Bool(b) => Value::Bool(b),
+		Portable::DateTime(dt) => Value::DateTime(dt),
+		Portable::Duration(duration) => Value::Duration(duration),
+		Portable::Regex(regex) => Value::Regex(regex),
+		Portable::Range(range) => Value::Range(range),
+		Portable::Constant(v) => Value::Constant(Box::new(import(*v))),
+		Portable::List(items) => Value::list(items.into_iter().map(import).collect()),
+		Portable::Map(entries) => Value::map(entries.into_iter().map(|(key, value)| (key, import(value))).collect()),
+		Portable::Instance { fields, definition } => {
+			Value::StructInstance { environment: crate::gc::instance(environment(fields)), definition: Box::new(import(*definition)) }
+		}
+		Portable::Struct { name, fields, methods: declared, parent, interfaces } => {
+			Value::Struct { name, fields, methods: methods(declared), parent: parent.map(|parent| Box::new(import(*parent))), interfaces }
+		}
+		Portable::Interface { name, methods } => Value::Interface { name, methods },
+		Portable::Enum { name, variants, methods: declared } => Value::Enum { name, variants, methods: methods(declared) },
+		Portable::EnumVariant { definition, variant, values } => {
+			Value::EnumVariant { definition: Box::new(import(*definition)), variant, values: values.into_iter().map(import).collect() }
+		}
+		Portable::Function { name, params, body, return_type, is_async, environment: captured, context } => {
+			Value::Function { name, params, body, return_type, is_async, environment: captured.map(environment), context }
+		}
+		Portable::NativeFunction { name, callback } => Value::NativeFunction { name, callback },
+		Portable::NativeMethod { name, callback, context } => Value::NativeMethod { name, callback, context },
+	}
+}
+
+// Everything a worker interpreter needs to run a function of the script: its declared functions, structs, enums
+// and interfaces.
+fn declarations(interpreter: &Interpreter) -> Vec<(String, Portable)> {
+	interpreter
+		.globals
+		.iter()
+		.filter(|(_, value)| matches!(value, Value::Function { .. } | Value::Struct { .. } | Value::Enum { .. } | Value::Interface { .. }))
+		.filter_map(|(name, value)| export(value).ok().map(|value| (name.clone(), value)))
+		.collect()
+}
+
+// A fresh interpreter for a worker thread, with the standard library and the parent's declarations.
+fn worker_interpreter(path: PathBuf, args: Vec<String>, env: HashMap<String, String>, declarations: Vec<(String, Portable)>) -> Interpreter<'static> {
+	let mut interpreter = Interpreter::new([].iter(), path);
+	interpreter.args = args;
+	interpreter.env = env;
+
+	interpreter::register_global_functions(&mut interpreter);
+	interpreter::register_global_structs(&mut interpreter);
+
+	for (name, value) in declarations {
+		interpreter.globals.entry(name).or_insert_with(|| import(value));
+	}
+
+	interpreter
+}
+
+fn arguments(values: Vec<Value>) -> ArgumentValues {
+	let mut arguments = ArgumentValues::new();
+
+	for value in values {
+		arguments.push(ArgumentValued::new(None, value));
+	}
+
+	arguments
+}
+
+fn call(interpreter: &mut Interpreter, function: Portable, values: Vec<Portable>) -> Outcome {
+	let result = match interpreter.call(import(function), arguments(values.into_iter().map(import).collect())) {
+		Ok(Value::Task(task)) => crate::event_loop::block_on(interpreter, &task),
+		result => result,
+	}
+	.map_err(|e| e.to_string())?;
+
+	export(&result).map_err(|e| e.to_string())
+}
+
+// Runs `function(arguments...)` on its own OS thread and interpreter.
+pub fn spawn(interpreter: &Interpreter, function: &Value, values: Vec<Value>) -> Result<WorkerHandle, InterpreterResult> {
+	let function = export(function)?;
+	let values = values.iter().map(export).collect::<Result<Vec<Portable>, InterpreterResult>>()?;
+	let declarations = declarations(interpreter);
+	let (path, args, env) = (interpreter.path(), interpreter.args.clone(), interpreter.env.clone());
+
+	let (inbox, worker_inbox) = channel();
+	let (worker_outbox, outbox) = channel();
+
+	let thread = std::thread::Builder::new()
+		.stack_size(STACK_SIZE)
+		.spawn(move || {
+			let mut interpreter = worker_interpreter(path, args, env, declarations);
+			interpreter.worker = Some(Rc::new(WorkerChannels { inbox: worker_inbox, outbox: worker_outbox }));
+
+			call(&mut interpreter, function, values)
+		})
+		.map_err(|e| InterpreterResult::Error(format!("Cannot start worker: {}.", e)))?;
+
+	Ok(WorkerHandle { inbox, outbox, thread: RefCell::new(Some(thread)), result: RefCell::new(None) })
+}
+
+// Splits `items` into one contiguous chunk per worker and maps each chunk on its own thread, results keep their order.
+pub fn parallel_map(interpreter: &Interpreter, callback: &Value, items: Vec<Value>, workers: usize) -> Result<Vec<Value>, InterpreterResult> {
+	let callback = export(callback)?;
+	let items = items.iter().map(export).collect::<Result<Vec<Portable>, InterpreterResult>>()?;
+	let declarations = declarations(interpreter);
+	let size = items.len().div_ceil(workers.max(1)).max(1);
+
+	let threads = items
+		.chunks(size)
+		.map(|chunk| {
+			let (callback, chunk, declarations) = (callback.clone(), chunk.to_vec(), declarations.clone());
+			let (path, args, env) = (interpreter.path(), interpreter.args.clone(), interpreter.env.clone());
+
+			std::thread::Builder::new().stack_size(STACK_SIZE).spawn(move || {
+				let mut interpreter = worker_interpreter(path, args, env, declarations);
+
+				chunk.into_iter().map(|item| call(&mut interpreter, callback.clone(), vec![item])).collect::<Result<Vec<Portable>, String>>()
+			})
+		})
+		.collect::<Result<Vec<JoinHandle<Result<Vec<Portable>, String>>>, std::io::Error>>()
+		.map_err(|e| InterpreterResult::Error(format!("Cannot start worker: {}.", e)))?;
+
+	let mut results = Vec::new();
+
+	for thread in threads {
+		let chunk = thread.join().unwrap_or_else(|_| Err("Worker thread panicked.".to_string())).map_err(InterpreterResult::Error)?;
+		results.extend(chunk.into_iter().map(import));
+	}
+
+	Ok(results)
+}
+
+pub fn cores() -> usize {
+	std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interpreter::{caught, evaluate};
+
+	const ECHO: &str = "struct Point {\n\tx, y\n}\ncreate worker = Worker.spawn!(fn (origin) {\n\tcreate count = 0\n\tcreate point = Worker.receive!()\n\twhile point != null {\n\t\tcount += 1\n\t\tWorker.post!(Point { x: point.x - origin.x, y: point.y - origin.y })\n\t\tpoint = Worker.receive!()\n\t}\n\treturn count\n}, Point { x: 1, y: 1 })";
+
+	#[test]
+	fn it_exchanges_messages_with_workers() {
+		assert_eq!(evaluate(&format!("{}\nworker.send!(Point {{ x: 3, y: 4 }})\nworker.receive!().y", ECHO)).unwrap().to_string(), "3");
+		assert_eq!(evaluate(&format!("{}\nworker.send!(Point {{ x: 3, y: 4 }})\nworker.send!(Point {{ x: 5, y: 5 }})\nworker.send!(null)\nworker.join!()", ECHO)).unwrap().to_string(), "2");
+		// Arguments are copies, the worker never changes the original.
+		assert_eq!(evaluate("create items = [1, 2]\nWorker.spawn!(fn (items) { items.push!(3) }, items).join!()\nitems").unwrap().to_string(), "[1, 2]");
+	}
+
+	#[test]
+	fn it_maps_lists_in_parallel_keeping_their_order() {
+		assert_eq!(evaluate("[1, 2, 3, 4, 5].parallelMap!(fn (n) { return n * n }, 2)").unwrap().to_string(), "[1, 4, 9, 16, 25]");
+		assert_eq!(evaluate("fn double(n) {\n\treturn n * 2\n}\n[3, 1, 2].parallelMap!(double)").unwrap().to_string(), "[6, 2, 4]");
+		assert_eq!(caught("[1, 2].parallelMap!(fn (n) { return n.nope!() })"), "Undefined method: Number.nope!()");
+	}
+
+	#[test]
+	fn it_raises_worker_failures_when_joined() {
+		assert_eq!(caught("Worker.spawn!(fn () { return [1][3] }).join!()"), "Undefined index: 3.");
+		assert_eq!(caught("fn numbers() {\n\tyield 1\n}\nWorker.spawn!(fn (items) { return 1 }, numbers())"), "Cannot send a value of type iterator to a worker.");
+	}
+}
@@ -0,0 +1,202 @@
+use chrono::{DateTime, FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{Tz, TzOffset};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// Time zone of `Value::DateTime`, either a fixed offset from UTC or a named zone from the IANA database bundled by
+// chrono-tz, which follows daylight saving time. Datetimes compare and subtract by the instant they represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+	Fixed(FixedOffset),
+	Named(Tz),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ZoneOffset {
+	Fixed(FixedOffset),
+	Named(TzOffset),
+}
+
+impl Zone {
+	pub fn utc() -> Self {
+		Zone::Named(Tz::UTC)
+	}
+
+	// The zone in `TZ` when it is set, then the system zone by name when the system reports one, otherwise its
+	// current offset.
+	pub fn local() -> Self {
+		if let Some(zone) = std::env::var("TZ").ok().and_then(|name| Zone::parse(name.trim_start_matches(':')).ok()) {
+			return zone;
+		}
+
+		match iana_time_zone::get_timezone().ok().and_then(|name| name.parse::<Tz>().ok()) {
+			Some(tz) => Zone::Named(tz),
+			None => Zone::Fixed(chrono::Local::now().offset().fix()),
+		}
+	}
+
+	// `UTC`, `Z`, an offset like `+03:00`, `-0530` or `+09`, or an IANA name like `America/Sao_Paulo`.
+	pub fn parse(name: &str) -> Result<Self, String> {
+		let name = name.trim();
+
+		if name.eq_ignore_ascii_case("utc") || name == "Z" {
+			return Ok(Zone::utc());
+		}
+
+		if let Some(sign) = name.chars().next().filter(|c| *c == '+' || *c == '-') {
+			let digits = name[1..].replace(':', "");
+			let (hours, minutes) = match digits.len() {
+				2 => (digits.parse::<i32>().ok(), Some(0)),
+				4 => (digits[..2].parse::<i32>().ok(), digits[2..].parse::<i32>().ok()),
+				_ => (None, None),
+			};
+
+			let seconds = match (hours, minutes) {
+				(Some(hours), Some(minutes)) if minutes < 60 => (hours * 3600 + minutes * 60) * if sign == '-' { -1 } else { 1 },
+				_ => return Err(format!("Invalid UTC offset {}, expected something like +03:00.", name)),
+			};
+
+			return FixedOffset::east_opt(seconds).map(Zone::Fixed).ok_or_else(|| format!("UTC offset {} is out of range.", name));
+		}
+
+		name.parse::<Tz>().map(Zone::Named).map_err(|_| format!("Unknown time zone {}.", name))
+	}
+
+	pub fn name(&self) -> String {
+		match self {
+			Zone::Fixed(offset) => offset.to_string(),
+			Zone::Named(tz) => tz.name().to_string(),
+		}
+	}
+}
+
+impl Offset for ZoneOffset {
+	fn fix(&self) -> FixedOffset {
+		match self {
+			ZoneOffset::Fixed(offset) => *offset,
+			ZoneOffset::Named(offset) => offset.fix(),
+		}
+	}
+}
+
+// What `%Z` prints, the abbreviation (`BRT`, `EST`) for named zones and the offset for fixed ones.
+impl Display for ZoneOffset {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			ZoneOffset::Fixed(offset) => write!(f, "{}", offset),
+			ZoneOffset::Named(offset) => write!(f, "{}", offset),
+		}
+	}
+}
+
+impl TimeZone for Zone {
+	type Offset = ZoneOffset;
+
+	fn from_offset(offset: &ZoneOffset) -> Self {
+		match offset {
+			ZoneOffset::Fixed(offset) => Zone::Fixed(*offset),
+			ZoneOffset::Named(offset) => Zone::Named(Tz::from_offset(offset)),
+		}
+	}
+
+	fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<ZoneOffset> {
+		match self {
+			Zone::Fixed(offset) => offset.offset_from_local_date(local).map(ZoneOffset::Fixed),
+			Zone::Named(tz) => tz.offset_from_local_date(local).map(ZoneOffset::Named),
+		}
+	}
+
+	fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<ZoneOffset> {
+		match self {
+			Zone::Fixed(offset) => offset.offset_from_local_datetime(local).map(ZoneOffset::Fixed),
+			Zone::Named(tz) => tz.offset_from_local_datetime(local).map(ZoneOffset::Named),
+		}
+	}
+
+	fn offset_from_utc_date(&self, utc: &NaiveDate) -> ZoneOffset {
+		match self {
+			Zone::Fixed(offset) => ZoneOffset::Fixed(offset.offset_from_utc_date(utc)),
+			Zone::Named(tz) => ZoneOffset::Named(tz.offset_from_utc_date(utc)),
+		}
+	}
+
+	fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> ZoneOffset {
+		match self {
+			Zone::Fixed(offset) => ZoneOffset::Fixed(offset.offset_from_utc_datetime(utc)),
+			Zone::Named(tz) => ZoneOffset::Named(tz.offset_from_utc_datetime(utc)),
+		}
+	}
+}
+
+pub fn now() -> DateTime<Zone> {
+	Utc::now().with_timezone(&Zone::utc())
+}
+
+// Local times that a daylight saving change skips resolve to the instant the clocks jumped to.
+pub fn resolve(zone: &Zone, local: NaiveDateTime) -> Option<DateTime<Zone>> {
+	match zone.from_local_datetime(&local) {
+		MappedLocalTime::Single(datetime) | MappedLocalTime::Ambiguous(datetime, _) => Some(datetime),
+		MappedLocalTime::None => (1..=4).find_map(|quarters| zone.from_local_datetime(&(local + chrono::Duration::minutes(quarters * 15))).earliest()),
+	}
+}
+
+const NAIVE_FORMATS: [&str; 6] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"];
+
+// ISO 8601 (`2024-03-09T18:30:00-03:00`, also with a space, without seconds or without an offset, which means UTC),
+// a plain date for midnight UTC, or RFC 2822 (`Sat, 9 Mar 2024 18:30:00 -0300`). Offsets are kept as fixed zones.
+pub fn parse(text: &str) -> Result<DateTime<Zone>, String> {
+	let text = text.trim();
+	let fixed = |datetime: DateTime<FixedOffset>| datetime.with_timezone(&Zone::Fixed(*datetime.offset()));
+
+	if let Ok(datetime) = DateTime::parse_from_rfc3339(text).or_else(|_| DateTime::parse_from_rfc3339(&text.replacen(' ', "T", 1))) {
+		return Ok(if text.ends_with('Z') { datetime.with_timezone(&Zone::utc()) } else { fixed(datetime) });
+	}
+
+	if let Ok(datetime) = DateTime::parse_from_rfc2822(text) {
+		return Ok(fixed(datetime));
+	}
+
+	for format in ["%Y-%m-%dT%H:%M%:z", "%Y-%m-%d %H:%M%:z", "%Y-%m-%dT%H:%M:%S%z", "%Y-%m-%d %H:%M:%S%z"] {
+		if let Ok(datetime) = DateTime::parse_from_str(text, format) {
+			return Ok(fixed(datetime));
+		}
+	}
+
+	for format in NAIVE_FORMATS {
+		if let Ok(datetime) = NaiveDateTime::parse_from_str(text, format) {
+			return Ok(datetime.and_utc().with_timezone(&Zone::utc()));
+		}
+	}
+
+	match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+		Ok(date) => Ok(date.and_time(chrono::NaiveTime::MIN).and_utc().with_timezone(&Zone::utc())),
+		Err(_) => Err(format!("Invalid datetime \"{}\", expected ISO 8601 like 2024-03-09T18:30:00-03:00 or RFC 2822.", text)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn it_can_parse_zones() {
+		assert_eq!(Zone::parse("utc"), Ok(Zone::utc()));
+		assert_eq!(Zone::parse("-03:00"), Ok(Zone::Fixed(FixedOffset::west_opt(3 * 3600).unwrap())));
+		assert_eq!(Zone::parse("+0530"), Ok(Zone::Fixed(FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap())));
+		assert_eq!(Zone::parse("America/Sao_Paulo"), Ok(Zone::Named(Tz::America__Sao_Paulo)));
+		assert!(Zone::parse("Mars/Olympus_Mons").is_err());
+		assert!(Zone::parse("+3:7").is_err());
+	}
+
+	#[test]
+	fn it_can_parse_iso_and_rfc_2822() {
+		let expected = Utc.with_ymd_and_hms(2024, 3, 9, 21, 30, 0).unwrap();
+
+		for text in ["2024-03-09T18:30:00-03:00", "2024-03-09 18:30:00-03:00", "2024-03-09T21:30:00Z", "2024-03-09T21:30", "Sat, 9 Mar 2024 18:30:00 -0300"] {
+			assert_eq!(parse(text).unwrap(), expected, "{}", text);
+		}
+
+		assert_eq!(parse("2024-03-09T18:30:00-03:00").unwrap().offset().fix(), FixedOffset::west_opt(3 * 3600).unwrap());
+		assert!(parse("2024-13-40").is_err());
+		assert!(parse("yesterday").is_err());
+	}
+}